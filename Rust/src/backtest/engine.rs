@@ -0,0 +1,455 @@
+// Replay engine
+// Runs a trader's trade history through the copy strategy against a simulated wallet
+
+use std::collections::HashMap;
+use serde::Serialize;
+use crate::config::copy_strategy::{get_trade_multiplier, CopyStrategyConfig};
+use crate::interfaces::user::UserActivityInterface;
+use crate::utils::constants::{BACKTEST_CONSTANTS, DB_FIELDS, TRADING_CONSTANTS};
+use crate::utils::post_order::trader_sell_fraction;
+
+/// Simulation parameters that are not part of the copy strategy
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestSettings {
+    pub starting_balance: f64,
+    /// Price penalty applied to every fill, in basis points
+    pub slippage_bps: f64,
+    /// Fee charged on the notional of every fill, in basis points
+    pub fee_bps: f64,
+    /// Combine small BUYs like TRADE_AGGREGATION_ENABLED does (None = disabled)
+    pub aggregation_window_seconds: Option<u64>,
+}
+
+impl Default for BacktestSettings {
+    fn default() -> Self {
+        Self {
+            starting_balance: BACKTEST_CONSTANTS::DEFAULT_STARTING_BALANCE,
+            slippage_bps: BACKTEST_CONSTANTS::DEFAULT_SLIPPAGE_BPS,
+            fee_bps: BACKTEST_CONSTANTS::DEFAULT_FEE_BPS,
+            aggregation_window_seconds: None,
+        }
+    }
+}
+
+/// Wallet equity after a replayed trade
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct EquityPoint {
+    pub timestamp: i64,
+    pub equity: f64,
+}
+
+/// Result of a replay
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestReport {
+    pub trader_trades: usize,
+    pub copied_trades: usize,
+    pub skipped_trades: usize,
+    pub starting_balance: f64,
+    pub final_balance: f64,
+    pub final_equity: f64,
+    pub roi_percent: f64,
+    pub max_drawdown_percent: f64,
+    /// Share of closed positions (sold out or resolved) that made money
+    pub win_rate_percent: f64,
+    pub closed_positions: usize,
+    /// Positions still open at the end, valued at their last traded price
+    pub open_positions: usize,
+    pub total_fees: f64,
+    pub total_volume: f64,
+    pub equity_curve: Vec<EquityPoint>,
+}
+
+/// A simulated position in one outcome token
+#[derive(Debug, Clone, Default)]
+struct Position {
+    tokens: f64,
+    /// USDC spent on the current round, fees included
+    cost: f64,
+    /// USDC received in the current round, fees deducted
+    proceeds: f64,
+}
+
+/// Small BUYs waiting for the aggregation window to pass
+#[derive(Debug, Clone)]
+struct PendingBuy {
+    first_timestamp: i64,
+    total_usdc: f64,
+    /// Sum of usdc × price, for the weighted average price
+    weighted_price: f64,
+    trades: usize,
+}
+
+struct Simulation<'a> {
+    config: &'a CopyStrategyConfig,
+    settings: &'a BacktestSettings,
+    balance: f64,
+    positions: HashMap<String, Position>,
+    trader_positions: HashMap<String, f64>,
+    last_prices: HashMap<String, f64>,
+    pending: HashMap<String, PendingBuy>,
+    copied_trades: usize,
+    skipped_trades: usize,
+    wins: usize,
+    closed_positions: usize,
+    total_fees: f64,
+    total_volume: f64,
+    equity_curve: Vec<EquityPoint>,
+}
+
+impl<'a> Simulation<'a> {
+    fn new(config: &'a CopyStrategyConfig, settings: &'a BacktestSettings) -> Self {
+        Self {
+            config,
+            settings,
+            balance: settings.starting_balance,
+            positions: HashMap::new(),
+            trader_positions: HashMap::new(),
+            last_prices: HashMap::new(),
+            pending: HashMap::new(),
+            copied_trades: 0,
+            skipped_trades: 0,
+            wins: 0,
+            closed_positions: 0,
+            total_fees: 0.0,
+            total_volume: 0.0,
+            equity_curve: Vec::new(),
+        }
+    }
+
+    fn equity(&self) -> f64 {
+        self.balance
+            + self
+                .positions
+                .iter()
+                .map(|(asset, position)| position.tokens * self.last_prices.get(asset).copied().unwrap_or(0.0))
+                .sum::<f64>()
+    }
+
+    fn record_equity(&mut self, timestamp: i64) {
+        let equity = self.equity();
+        self.equity_curve.push(EquityPoint { timestamp, equity });
+    }
+
+    fn close_round(&mut self, asset: &str) {
+        if let Some(position) = self.positions.remove(asset) {
+            self.closed_positions += 1;
+            if position.proceeds > position.cost {
+                self.wins += 1;
+            }
+        }
+    }
+
+    /// Copy a BUY of `trader_usdc` at `price`, returns whether an order was placed
+    fn buy(&mut self, asset: &str, trader_usdc: f64, price: f64) -> bool {
+        let current_position_value = self
+            .positions
+            .get(asset)
+            .map(|position| position.tokens * price)
+            .unwrap_or(0.0);
+        let amount = self
+            .config
+            .calculate_order_size(trader_usdc, self.balance, current_position_value)
+            .final_amount;
+        if amount <= 0.0 {
+            return false;
+        }
+
+        let fill_price = (price * (1.0 + self.settings.slippage_bps / 10_000.0)).min(1.0);
+        let fee = amount * self.settings.fee_bps / 10_000.0;
+        let position = self.positions.entry(asset.to_string()).or_default();
+        position.tokens += (amount - fee) / fill_price;
+        position.cost += amount;
+        self.balance -= amount;
+        self.total_fees += fee;
+        self.total_volume += amount;
+        true
+    }
+
+    /// Mirror a SELL of `trader_tokens` out of `trader_position_before`, returns whether an order was placed
+    fn sell(&mut self, asset: &str, trader_tokens: f64, trader_position_before: f64, trader_usdc: f64, price: f64) -> bool {
+        let Some(held) = self.positions.get(asset).map(|position| position.tokens) else {
+            return false;
+        };
+        let position_before = (trader_position_before > 0.0).then_some(trader_position_before);
+        let fraction = trader_sell_fraction(trader_tokens, position_before);
+        let multiplier = get_trade_multiplier(self.config, trader_usdc);
+        let tokens = (held * fraction * multiplier).min(held);
+        if tokens < TRADING_CONSTANTS::MIN_ORDER_SIZE_TOKENS {
+            return false;
+        }
+
+        let fill_price = (price * (1.0 - self.settings.slippage_bps / 10_000.0)).max(0.0);
+        let notional = tokens * fill_price;
+        let fee = notional * self.settings.fee_bps / 10_000.0;
+        if let Some(position) = self.positions.get_mut(asset) {
+            position.tokens -= tokens;
+            position.proceeds += notional - fee;
+        }
+        self.balance += notional - fee;
+        self.total_fees += fee;
+        self.total_volume += notional;
+        if held - tokens < TRADING_CONSTANTS::MIN_ORDER_SIZE_TOKENS / 1_000.0 {
+            self.close_round(asset);
+        }
+        true
+    }
+
+    /// Execute or drop aggregated BUYs whose window ended before `now`
+    fn flush_pending(&mut self, now: Option<i64>) {
+        let Some(window) = self.settings.aggregation_window_seconds else {
+            return;
+        };
+        let mut due: Vec<String> = self
+            .pending
+            .iter()
+            .filter(|(_, group)| now.is_none_or(|now| now >= group.first_timestamp + window as i64))
+            .map(|(asset, _)| asset.clone())
+            .collect();
+        due.sort();
+        for asset in due {
+            let Some(group) = self.pending.remove(&asset) else {
+                continue;
+            };
+            let executed = group.total_usdc >= TRADING_CONSTANTS::TRADE_AGGREGATION_MIN_TOTAL_USD
+                && self.buy(&asset, group.total_usdc, group.weighted_price / group.total_usdc);
+            if executed {
+                self.copied_trades += group.trades;
+            } else {
+                self.skipped_trades += group.trades;
+            }
+        }
+    }
+
+    fn replay(&mut self, trade: &UserActivityInterface) {
+        self.flush_pending(Some(trade.timestamp));
+        if trade.price > 0.0 {
+            self.last_prices.insert(trade.asset.clone(), trade.price);
+        }
+
+        // The trader's own position is rebuilt from the same history
+        let trader_position = self.trader_positions.entry(trade.asset.clone()).or_insert(0.0);
+        let trader_position_before = *trader_position;
+        let copied = if trade.side == DB_FIELDS::SIDE_BUY {
+            *trader_position += trade.size;
+            let aggregate = self.settings.aggregation_window_seconds.is_some()
+                && trade.usdc_size < TRADING_CONSTANTS::TRADE_AGGREGATION_MIN_TOTAL_USD;
+            if aggregate {
+                let group = self.pending.entry(trade.asset.clone()).or_insert(PendingBuy {
+                    first_timestamp: trade.timestamp,
+                    total_usdc: 0.0,
+                    weighted_price: 0.0,
+                    trades: 0,
+                });
+                group.total_usdc += trade.usdc_size;
+                group.weighted_price += trade.usdc_size * trade.price;
+                group.trades += 1;
+                None
+            } else {
+                Some(self.buy(&trade.asset, trade.usdc_size, trade.price))
+            }
+        } else if trade.side == DB_FIELDS::SIDE_SELL {
+            *trader_position = (*trader_position - trade.size).max(0.0);
+            Some(self.sell(&trade.asset, trade.size, trader_position_before, trade.usdc_size, trade.price))
+        } else {
+            Some(false)
+        };
+
+        match copied {
+            Some(true) => self.copied_trades += 1,
+            Some(false) => self.skipped_trades += 1,
+            None => {}
+        }
+        self.record_equity(trade.timestamp);
+    }
+
+    /// Redeem resolved positions at their payout
+    fn settle(&mut self, resolutions: &HashMap<String, f64>, timestamp: i64) {
+        let mut resolved: Vec<String> = self
+            .positions
+            .keys()
+            .filter(|asset| resolutions.contains_key(*asset))
+            .cloned()
+            .collect();
+        resolved.sort();
+        for asset in resolved {
+            let payout = resolutions[&asset];
+            if let Some(position) = self.positions.get_mut(&asset) {
+                self.balance += position.tokens * payout;
+                position.proceeds += position.tokens * payout;
+                position.tokens = 0.0;
+            }
+            self.last_prices.insert(asset.clone(), payout);
+            self.close_round(&asset);
+        }
+        self.record_equity(timestamp);
+    }
+}
+
+/// Largest peak-to-trough equity decline, in percent of the peak
+pub fn max_drawdown_percent(equity_curve: &[EquityPoint]) -> f64 {
+    let mut peak = f64::MIN;
+    let mut max_drawdown: f64 = 0.0;
+    for point in equity_curve {
+        peak = peak.max(point.equity);
+        if peak > 0.0 {
+            max_drawdown = max_drawdown.max((peak - point.equity) / peak * 100.0);
+        }
+    }
+    max_drawdown
+}
+
+/// Replay `trades` (any order) and settle positions whose market resolved
+///
+/// `resolutions` maps outcome token IDs of resolved markets to their payout per token (0 or 1).
+pub fn run_backtest(
+    trades: &[UserActivityInterface],
+    config: &CopyStrategyConfig,
+    settings: &BacktestSettings,
+    resolutions: &HashMap<String, f64>,
+) -> BacktestReport {
+    let mut trades: Vec<&UserActivityInterface> = trades
+        .iter()
+        .filter(|trade| trade.r#type == DB_FIELDS::TYPE_TRADE && !trade.asset.is_empty())
+        .collect();
+    trades.sort_by_key(|trade| trade.timestamp);
+
+    let mut simulation = Simulation::new(config, settings);
+    let start = trades.first().map(|trade| trade.timestamp).unwrap_or(0);
+    simulation.record_equity(start);
+    for trade in &trades {
+        simulation.replay(trade);
+    }
+    simulation.flush_pending(None);
+
+    let end = trades.last().map(|trade| trade.timestamp).unwrap_or(start);
+    simulation.settle(resolutions, end);
+
+    let final_equity = simulation.equity();
+    BacktestReport {
+        trader_trades: trades.len(),
+        copied_trades: simulation.copied_trades,
+        skipped_trades: simulation.skipped_trades,
+        starting_balance: settings.starting_balance,
+        final_balance: simulation.balance,
+        final_equity,
+        roi_percent: if settings.starting_balance > 0.0 {
+            (final_equity - settings.starting_balance) / settings.starting_balance * 100.0
+        } else {
+            0.0
+        },
+        max_drawdown_percent: max_drawdown_percent(&simulation.equity_curve),
+        win_rate_percent: if simulation.closed_positions > 0 {
+            simulation.wins as f64 / simulation.closed_positions as f64 * 100.0
+        } else {
+            0.0
+        },
+        closed_positions: simulation.closed_positions,
+        open_positions: simulation.positions.len(),
+        total_fees: simulation.total_fees,
+        total_volume: simulation.total_volume,
+        equity_curve: simulation.equity_curve,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::copy_strategy::CopyStrategy;
+
+    fn config(strategy: CopyStrategy, copy_size: f64) -> CopyStrategyConfig {
+        CopyStrategyConfig { strategy, copy_size, ..Default::default() }
+    }
+
+    fn trade(timestamp: i64, asset: &str, side: &str, size: f64, price: f64) -> UserActivityInterface {
+        serde_json::from_value(serde_json::json!({
+            "timestamp": timestamp,
+            "type": "TRADE",
+            "asset": asset,
+            "side": side,
+            "size": size,
+            "usdcSize": size * price,
+            "price": price,
+        }))
+        .unwrap()
+    }
+
+    fn no_costs() -> BacktestSettings {
+        BacktestSettings { slippage_bps: 0.0, fee_bps: 0.0, ..Default::default() }
+    }
+
+    fn approx(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn round_trip_realizes_profit() {
+        // Trader buys 200 @ 0.50 ($100), sells half @ 0.80, then the rest @ 0.60
+        let trades = vec![
+            trade(1, "A", "BUY", 200.0, 0.5),
+            trade(2, "A", "SELL", 100.0, 0.8),
+            trade(3, "A", "SELL", 100.0, 0.6),
+        ];
+        let report = run_backtest(&trades, &config(CopyStrategy::PERCENTAGE, 10.0), &no_costs(), &HashMap::new());
+
+        // $10 → 20 tokens; sell 10 @ 0.8 = $8, sell 10 @ 0.6 = $6 → +$4
+        assert_eq!((report.copied_trades, report.skipped_trades), (3, 0));
+        assert!(approx(report.final_equity, 1004.0), "{}", report.final_equity);
+        assert!(approx(report.roi_percent, 0.4));
+        assert_eq!((report.closed_positions, report.open_positions), (1, 0));
+        assert_eq!(report.win_rate_percent, 100.0);
+        assert_eq!(report.equity_curve.len(), 5);
+    }
+
+    #[test]
+    fn resolution_settles_open_positions() {
+        let trades = vec![trade(1, "A", "BUY", 100.0, 0.4), trade(2, "B", "BUY", 100.0, 0.6)];
+        let resolutions = HashMap::from([("A".to_string(), 1.0), ("B".to_string(), 0.0)]);
+        let settings = BacktestSettings { slippage_bps: 0.0, fee_bps: 0.0, ..Default::default() };
+        let report = run_backtest(&trades, &config(CopyStrategy::FIXED, 10.0), &settings, &resolutions);
+
+        // A: $10 → 25 tokens paying $25; B: $10 → worthless
+        assert!(approx(report.final_equity, 1005.0), "{}", report.final_equity);
+        assert_eq!((report.closed_positions, report.open_positions), (2, 0));
+        assert_eq!(report.win_rate_percent, 50.0);
+        // Both payouts are booked before the final equity point
+        assert_eq!(report.max_drawdown_percent, 0.0);
+    }
+
+    #[test]
+    fn slippage_and_fees_reduce_returns() {
+        let trades = vec![trade(1, "A", "BUY", 100.0, 0.5), trade(2, "A", "SELL", 100.0, 0.5)];
+        let settings = BacktestSettings { slippage_bps: 100.0, fee_bps: 50.0, ..Default::default() };
+        let report = run_backtest(&trades, &config(CopyStrategy::FIXED, 10.0), &settings, &HashMap::new());
+
+        // Buy $10 at 0.505 with $0.05 fee → 19.70 tokens; sell at 0.495 less 0.5% fee
+        let tokens = 9.95 / 0.505;
+        let notional = tokens * 0.495;
+        assert!(approx(report.total_fees, 0.05 + notional * 0.005));
+        assert!(approx(report.final_equity, 990.0 + notional * 0.995));
+        assert_eq!(report.win_rate_percent, 0.0);
+    }
+
+    #[test]
+    fn aggregation_window_combines_small_buys() {
+        let min = TRADING_CONSTANTS::TRADE_AGGREGATION_MIN_TOTAL_USD;
+        let small = |timestamp| trade(timestamp, "A", "BUY", min * 0.6 / 0.5, 0.5);
+        let trades = vec![small(0), small(30), small(1_000)];
+        let settings = BacktestSettings { aggregation_window_seconds: Some(60), ..no_costs() };
+        let report = run_backtest(&trades, &config(CopyStrategy::PERCENTAGE, 100.0), &settings, &HashMap::new());
+
+        // The first two combine above the minimum; the third is alone when the replay ends
+        assert_eq!((report.copied_trades, report.skipped_trades), (2, 1));
+        assert!(approx(report.total_volume, min * 1.2));
+    }
+
+    #[test]
+    fn drawdown_is_measured_from_the_running_peak() {
+        let curve: Vec<EquityPoint> = [100.0, 120.0, 90.0, 130.0, 117.0]
+            .iter()
+            .enumerate()
+            .map(|(i, equity)| EquityPoint { timestamp: i as i64, equity: *equity })
+            .collect();
+        assert!(approx(max_drawdown_percent(&curve), 25.0));
+        assert_eq!(max_drawdown_percent(&[]), 0.0);
+    }
+}
@@ -0,0 +1,237 @@
+// Backtest inputs
+// Trader history from storage or the data API, and market resolutions from the Gamma API
+
+use std::collections::{HashMap, HashSet};
+use futures::{stream, StreamExt};
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use crate::interfaces::user::UserActivityInterface;
+use crate::storage::storage;
+use crate::utils::constants::{BACKTEST_CONSTANTS, DB_FIELDS, POLYMARKET_API};
+use crate::utils::errors::{AppError, AppResult};
+
+fn http_client() -> AppResult<Client> {
+    Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .build()
+        .map_err(|e| AppError::NetworkError(e.to_string()))
+}
+
+async fn get_json<T: DeserializeOwned>(client: &Client, url: &str) -> AppResult<T> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| AppError::NetworkError(format!("GET {} failed: {}", url, e)))?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(AppError::NetworkError(format!("GET {}: HTTP {}", url, status)));
+    }
+    response
+        .json::<T>()
+        .await
+        .map_err(|e| AppError::NetworkError(format!("Invalid response from {}: {}", url, e)))
+}
+
+/// Trades journaled by the bot (requires `init_storage`)
+pub async fn load_trades_from_db(address: &str, since: Option<i64>) -> AppResult<Vec<UserActivityInterface>> {
+    storage().trades_since(address, since).await
+}
+
+/// Trades from the data API `/activity` endpoint, paging back until `since` or the offset limit
+pub async fn fetch_trades_from_api(address: &str, since: Option<i64>) -> AppResult<Vec<UserActivityInterface>> {
+    let client = http_client()?;
+    let page_size = BACKTEST_CONSTANTS::ACTIVITY_PAGE_SIZE;
+    let mut trades = Vec::new();
+    let mut offset = 0;
+
+    while offset <= BACKTEST_CONSTANTS::MAX_ACTIVITY_OFFSET {
+        let url = format!(
+            "{}{}?user={}&type={}&limit={}&offset={}",
+            POLYMARKET_API::DATA_API_BASE,
+            POLYMARKET_API::ACTIVITY_ENDPOINT,
+            address,
+            DB_FIELDS::TYPE_TRADE,
+            page_size,
+            offset
+        );
+        let page: Vec<UserActivityInterface> = get_json(&client, &url).await?;
+        let page_len = page.len();
+        // Newest first: stop once the page reaches past `since`
+        let reached_since = since.is_some_and(|since| page.iter().any(|trade| trade.timestamp < since));
+        trades.extend(page.into_iter().filter(|trade| since.is_none_or(|since| trade.timestamp >= since)));
+        if page_len < page_size || reached_since {
+            break;
+        }
+        offset += page_size;
+    }
+
+    trades.sort_by_key(|trade| trade.timestamp);
+    Ok(trades)
+}
+
+/// Market as returned by the Gamma API (list fields are JSON-encoded strings)
+#[derive(Debug, Deserialize)]
+struct GammaMarket {
+    #[serde(rename = "conditionId", default)]
+    condition_id: String,
+    #[serde(default)]
+    slug: String,
+    #[serde(default)]
+    closed: bool,
+    /// `resolved` once the UMA oracle settled the market; closed markets can still be disputed
+    #[serde(rename = "umaResolutionStatus", default)]
+    uma_resolution_status: Option<String>,
+    #[serde(rename = "outcomePrices", default)]
+    outcome_prices: Option<String>,
+    #[serde(rename = "clobTokenIds", default)]
+    clob_token_ids: Option<String>,
+}
+
+/// Price per outcome token of a market
+fn market_prices(market: &GammaMarket) -> Vec<(String, f64)> {
+    let parse = |field: &Option<String>| -> Vec<String> {
+        field
+            .as_deref()
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default()
+    };
+    let token_ids = parse(&market.clob_token_ids);
+    let prices = parse(&market.outcome_prices);
+    if token_ids.len() != prices.len() {
+        return Vec::new();
+    }
+    token_ids
+        .into_iter()
+        .zip(prices)
+        .filter_map(|(token_id, price)| price.parse::<f64>().ok().map(|price| (token_id, price)))
+        .collect()
+}
+
+/// Outcome token prices of a market: current ones until it resolves, payouts after
+#[derive(Debug, Clone, Default)]
+pub struct MarketQuote {
+    pub slug: String,
+    pub resolved: bool,
+    pub prices: Vec<(String, f64)>,
+}
+
+impl MarketQuote {
+    /// A closed market is only resolved once the oracle says so or its prices are final
+    ///
+    /// Trading closes before resolution, so a closed market's prices can still be last trades.
+    fn from_market(market: GammaMarket) -> Self {
+        let prices = market_prices(&market);
+        let settled_prices = !prices.is_empty() && prices.iter().all(|(_, price)| *price == 0.0 || *price == 1.0);
+        let resolved = market.closed
+            && (market.uma_resolution_status.as_deref() == Some("resolved") || settled_prices);
+        MarketQuote { prices, slug: market.slug, resolved }
+    }
+
+    /// Payout per outcome token, empty until the market is resolved
+    pub fn payouts(&self) -> &[(String, f64)] {
+        if self.resolved {
+            &self.prices
+        } else {
+            &[]
+        }
+    }
+}
+
+/// Gamma API quotes of the given markets, keyed by condition ID
+///
+/// Markets that could not be looked up are left out.
+pub async fn fetch_market_quotes<'a>(
+    condition_ids: impl IntoIterator<Item = &'a str>,
+) -> AppResult<HashMap<String, MarketQuote>> {
+    let client = http_client()?;
+    let condition_ids: HashSet<&str> = condition_ids
+        .into_iter()
+        .filter(|condition_id| !condition_id.is_empty())
+        .collect();
+
+    let markets: Vec<Vec<GammaMarket>> = stream::iter(condition_ids)
+        .map(|condition_id| {
+            let client = &client;
+            async move {
+                let url = format!(
+                    "{}{}?condition_ids={}",
+                    POLYMARKET_API::GAMMA_API_BASE,
+                    POLYMARKET_API::MARKETS_ENDPOINT,
+                    condition_id
+                );
+                get_json::<Vec<GammaMarket>>(client, &url).await.unwrap_or_default()
+            }
+        })
+        .buffer_unordered(BACKTEST_CONSTANTS::RESOLUTION_CONCURRENCY)
+        .collect()
+        .await;
+
+    Ok(markets
+        .into_iter()
+        .flatten()
+        .map(|market| (market.condition_id.clone(), MarketQuote::from_market(market)))
+        .collect())
+}
+
+/// Payout per outcome token for the resolved markets among `trades`
+///
+/// Markets that are not resolved yet (or could not be looked up) are left out and valued at their
+/// last traded price instead.
+pub async fn fetch_resolutions(trades: &[UserActivityInterface]) -> AppResult<HashMap<String, f64>> {
+    let quotes = fetch_market_quotes(trades.iter().map(|trade| trade.condition_id.as_str())).await?;
+    Ok(quotes
+        .values()
+        .flat_map(|quote| quote.payouts().iter().cloned())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_payouts_of_resolved_markets_only() {
+        let markets: Vec<GammaMarket> = serde_json::from_value(serde_json::json!([
+            {
+                "conditionId": "0xabc",
+                "closed": true,
+                "outcomes": "[\"Yes\", \"No\"]",
+                "outcomePrices": "[\"0\", \"1\"]",
+                "clobTokenIds": "[\"111\", \"222\"]"
+            },
+            {
+                "conditionId": "0xdef",
+                "closed": false,
+                "outcomePrices": "[\"0.42\", \"0.58\"]",
+                "clobTokenIds": "[\"333\", \"444\"]"
+            },
+            { "conditionId": "0x123", "closed": true },
+            {
+                "conditionId": "0x456",
+                "closed": true,
+                "umaResolutionStatus": "proposed",
+                "outcomePrices": "[\"0.97\", \"0.03\"]",
+                "clobTokenIds": "[\"555\", \"666\"]"
+            },
+            {
+                "conditionId": "0x789",
+                "closed": true,
+                "umaResolutionStatus": "resolved",
+                "outcomePrices": "[\"0.5\", \"0.5\"]",
+                "clobTokenIds": "[\"777\", \"888\"]"
+            }
+        ]))
+        .unwrap();
+
+        let quotes: Vec<MarketQuote> = markets.into_iter().map(MarketQuote::from_market).collect();
+        assert_eq!(quotes[0].payouts(), [("111".to_string(), 0.0), ("222".to_string(), 1.0)]);
+        assert!(quotes[1].payouts().is_empty());
+        assert_eq!(quotes[1].prices, [("333".to_string(), 0.42), ("444".to_string(), 0.58)]);
+        assert!(quotes[2].payouts().is_empty());
+        // Closed for trading but not settled yet: last trade prices, not payouts
+        assert!(quotes[3].payouts().is_empty());
+        assert_eq!(quotes[4].payouts(), [("777".to_string(), 0.5), ("888".to_string(), 0.5)]);
+    }
+}
@@ -0,0 +1,8 @@
+// Historical backtesting
+// Replays stored or fetched trader history through the copy strategy
+
+pub mod engine;
+pub mod history;
+pub mod sweep;
+
+pub use engine::{run_backtest, BacktestReport, BacktestSettings, EquityPoint};
@@ -0,0 +1,365 @@
+// Parameter sweeps
+// Backtests every combination of a settings grid and ranks the results
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use serde::Serialize;
+use crate::backtest::engine::{run_backtest, BacktestSettings};
+use crate::config::copy_strategy::{parse_tiered_multipliers, CopyStrategy, CopyStrategyConfig};
+use crate::interfaces::user::UserActivityInterface;
+use crate::utils::errors::AppResult;
+
+/// Values to try for each swept setting; settings left out keep their env value
+#[derive(Debug, Clone)]
+pub struct SweepGrid {
+    pub strategies: Vec<CopyStrategy>,
+    pub copy_sizes: Vec<f64>,
+    pub max_order_sizes: Vec<f64>,
+    pub min_order_sizes: Vec<f64>,
+    /// None disables aggregation
+    pub aggregation_windows: Vec<Option<u64>>,
+    /// TIERED_MULTIPLIERS specs, "" for no tiers
+    pub tier_specs: Vec<String>,
+}
+
+impl SweepGrid {
+    /// A grid with only the values from `base`
+    pub fn from_config(base: &CopyStrategyConfig, settings: &BacktestSettings) -> Self {
+        Self {
+            strategies: vec![base.strategy],
+            copy_sizes: vec![base.copy_size],
+            max_order_sizes: vec![base.max_order_size_usd],
+            min_order_sizes: vec![base.min_order_size_usd],
+            aggregation_windows: vec![settings.aggregation_window_seconds],
+            tier_specs: vec![tiers_to_spec(base)],
+        }
+    }
+
+    /// Every combination of the grid values
+    pub fn combinations(&self) -> Vec<SweepParameters> {
+        let mut combinations = Vec::new();
+        for strategy in &self.strategies {
+            for copy_size in &self.copy_sizes {
+                for max_order_size_usd in &self.max_order_sizes {
+                    for min_order_size_usd in &self.min_order_sizes {
+                        for aggregation_window_seconds in &self.aggregation_windows {
+                            for tiered_multipliers in &self.tier_specs {
+                                combinations.push(SweepParameters {
+                                    strategy: *strategy,
+                                    copy_size: *copy_size,
+                                    max_order_size_usd: *max_order_size_usd,
+                                    min_order_size_usd: *min_order_size_usd,
+                                    aggregation_window_seconds: *aggregation_window_seconds,
+                                    tiered_multipliers: tiered_multipliers.clone(),
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        combinations
+    }
+
+    /// Reject tier specs up front rather than in every worker
+    pub fn validate(&self) -> AppResult<()> {
+        for spec in self.tier_specs.iter().filter(|spec| !spec.is_empty()) {
+            parse_tiered_multipliers(spec)?;
+        }
+        Ok(())
+    }
+}
+
+/// Rebuild a TIERED_MULTIPLIERS spec from parsed tiers
+fn tiers_to_spec(config: &CopyStrategyConfig) -> String {
+    config
+        .tiered_multipliers
+        .iter()
+        .map(|tier| match tier.max {
+            Some(max) => format!("{}-{}:{}", tier.min, max, tier.multiplier),
+            None => format!("{}+:{}", tier.min, tier.multiplier),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// One point of the grid
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SweepParameters {
+    pub strategy: CopyStrategy,
+    pub copy_size: f64,
+    pub max_order_size_usd: f64,
+    pub min_order_size_usd: f64,
+    pub aggregation_window_seconds: Option<u64>,
+    pub tiered_multipliers: String,
+}
+
+impl SweepParameters {
+    /// Strategy config for this point, keeping the rest of `base`
+    pub fn apply(&self, base: &CopyStrategyConfig) -> CopyStrategyConfig {
+        let adaptive = self.strategy == CopyStrategy::ADAPTIVE;
+        CopyStrategyConfig {
+            strategy: self.strategy,
+            copy_size: self.copy_size,
+            max_order_size_usd: self.max_order_size_usd,
+            min_order_size_usd: self.min_order_size_usd,
+            adaptive_min_percent: adaptive.then(|| base.adaptive_min_percent.unwrap_or(self.copy_size)),
+            adaptive_max_percent: adaptive.then(|| base.adaptive_max_percent.unwrap_or(self.copy_size)),
+            adaptive_threshold: adaptive.then(|| base.adaptive_threshold.unwrap_or(500.0)),
+            tiered_multipliers: if self.tiered_multipliers.is_empty() {
+                Vec::new()
+            } else {
+                parse_tiered_multipliers(&self.tiered_multipliers).unwrap_or_default()
+            },
+            trade_multiplier: base.trade_multiplier,
+        }
+    }
+
+    fn label(&self) -> String {
+        let mut label = format!(
+            "{:?} {}, max ${}, min ${}",
+            self.strategy, self.copy_size, self.max_order_size_usd, self.min_order_size_usd
+        );
+        if let Some(window) = self.aggregation_window_seconds {
+            label.push_str(&format!(", agg {}s", window));
+        }
+        if !self.tiered_multipliers.is_empty() {
+            label.push_str(&format!(", tiers {}", self.tiered_multipliers));
+        }
+        label
+    }
+}
+
+/// Backtest of one grid point against one trader (equity curve left out)
+#[derive(Debug, Clone, Serialize)]
+pub struct SweepRun {
+    pub trader: String,
+    pub roi_percent: f64,
+    pub max_drawdown_percent: f64,
+    pub win_rate_percent: f64,
+    pub copied_trades: usize,
+    pub total_fees: f64,
+    pub final_equity: f64,
+}
+
+/// A grid point's results across all traders
+#[derive(Debug, Clone, Serialize)]
+pub struct SweepResult {
+    pub rank: usize,
+    pub parameters: SweepParameters,
+    pub avg_roi_percent: f64,
+    pub worst_roi_percent: f64,
+    pub worst_drawdown_percent: f64,
+    pub avg_win_rate_percent: f64,
+    pub copied_trades: usize,
+    pub total_fees: f64,
+    pub runs: Vec<SweepRun>,
+}
+
+fn summarize(parameters: SweepParameters, runs: Vec<SweepRun>) -> SweepResult {
+    let count = runs.len().max(1) as f64;
+    SweepResult {
+        rank: 0,
+        parameters,
+        avg_roi_percent: runs.iter().map(|run| run.roi_percent).sum::<f64>() / count,
+        worst_roi_percent: runs.iter().map(|run| run.roi_percent).fold(f64::INFINITY, f64::min),
+        worst_drawdown_percent: runs.iter().map(|run| run.max_drawdown_percent).fold(0.0, f64::max),
+        avg_win_rate_percent: runs.iter().map(|run| run.win_rate_percent).sum::<f64>() / count,
+        copied_trades: runs.iter().map(|run| run.copied_trades).sum(),
+        total_fees: runs.iter().map(|run| run.total_fees).sum(),
+        runs,
+    }
+}
+
+/// Order by average ROI, then by the smaller worst drawdown, and number the ranks
+pub fn rank_results(mut results: Vec<SweepResult>) -> Vec<SweepResult> {
+    results.sort_by(|a, b| {
+        b.avg_roi_percent
+            .partial_cmp(&a.avg_roi_percent)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(
+                a.worst_drawdown_percent
+                    .partial_cmp(&b.worst_drawdown_percent)
+                    .unwrap_or(std::cmp::Ordering::Equal),
+            )
+    });
+    for (index, result) in results.iter_mut().enumerate() {
+        result.rank = index + 1;
+    }
+    results
+}
+
+/// Input of a sweep: each trader's history and resolved payouts
+pub struct TraderHistory {
+    pub trader: String,
+    pub trades: Vec<UserActivityInterface>,
+    pub resolutions: HashMap<String, f64>,
+}
+
+/// Backtest every grid point against every trader on the blocking pool and rank the results
+pub async fn run_sweep(
+    histories: Vec<TraderHistory>,
+    grid: &SweepGrid,
+    base_config: &CopyStrategyConfig,
+    base_settings: &BacktestSettings,
+) -> AppResult<Vec<SweepResult>> {
+    grid.validate()?;
+    let histories = Arc::new(histories);
+
+    let mut tasks = Vec::new();
+    for parameters in grid.combinations() {
+        let config = parameters.apply(base_config);
+        let settings = BacktestSettings {
+            aggregation_window_seconds: parameters.aggregation_window_seconds,
+            ..base_settings.clone()
+        };
+        let histories = Arc::clone(&histories);
+        tasks.push(tokio::task::spawn_blocking(move || {
+            let runs = histories
+                .iter()
+                .map(|history| {
+                    let report = run_backtest(&history.trades, &config, &settings, &history.resolutions);
+                    SweepRun {
+                        trader: history.trader.clone(),
+                        roi_percent: report.roi_percent,
+                        max_drawdown_percent: report.max_drawdown_percent,
+                        win_rate_percent: report.win_rate_percent,
+                        copied_trades: report.copied_trades,
+                        total_fees: report.total_fees,
+                        final_equity: report.final_equity,
+                    }
+                })
+                .collect();
+            summarize(parameters, runs)
+        }));
+    }
+
+    let mut results = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        results.push(task.await.map_err(|e| crate::utils::errors::AppError::Unknown(e.to_string()))?);
+    }
+    Ok(rank_results(results))
+}
+
+/// Ranked results as a Markdown table
+pub fn to_markdown(results: &[SweepResult]) -> String {
+    let mut markdown = String::from(
+        "| Rank | Parameters | Avg ROI | Worst ROI | Worst DD | Avg Win Rate | Trades | Fees |\n\
+         |---:|---|---:|---:|---:|---:|---:|---:|\n",
+    );
+    for result in results {
+        markdown.push_str(&format!(
+            "| {} | {} | {:+.2}% | {:+.2}% | {:.2}% | {:.1}% | {} | ${:.2} |\n",
+            result.rank,
+            result.parameters.label(),
+            result.avg_roi_percent,
+            result.worst_roi_percent,
+            result.worst_drawdown_percent,
+            result.avg_win_rate_percent,
+            result.copied_trades,
+            result.total_fees
+        ));
+    }
+    markdown
+}
+
+/// Ranked results as CSV, one row per grid point
+pub fn to_csv(results: &[SweepResult]) -> String {
+    let mut csv = String::from(
+        "rank,strategy,copy_size,max_order_size_usd,min_order_size_usd,aggregation_window_seconds,\
+         tiered_multipliers,avg_roi_percent,worst_roi_percent,worst_drawdown_percent,avg_win_rate_percent,\
+         copied_trades,total_fees\n",
+    );
+    for result in results {
+        let parameters = &result.parameters;
+        csv.push_str(&format!(
+            "{},{:?},{},{},{},{},\"{}\",{:.4},{:.4},{:.4},{:.4},{},{:.4}\n",
+            result.rank,
+            parameters.strategy,
+            parameters.copy_size,
+            parameters.max_order_size_usd,
+            parameters.min_order_size_usd,
+            parameters.aggregation_window_seconds.map(|w| w.to_string()).unwrap_or_default(),
+            parameters.tiered_multipliers,
+            result.avg_roi_percent,
+            result.worst_roi_percent,
+            result.worst_drawdown_percent,
+            result.avg_win_rate_percent,
+            result.copied_trades,
+            result.total_fees
+        ));
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base() -> CopyStrategyConfig {
+        CopyStrategyConfig::default()
+    }
+
+    fn trade(timestamp: i64, side: &str, size: f64, price: f64) -> UserActivityInterface {
+        serde_json::from_value(serde_json::json!({
+            "timestamp": timestamp,
+            "type": "TRADE",
+            "asset": "A",
+            "side": side,
+            "size": size,
+            "usdcSize": size * price,
+            "price": price,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn grid_expands_to_every_combination() {
+        let settings = BacktestSettings::default();
+        let mut grid = SweepGrid::from_config(&base(), &settings);
+        assert_eq!(grid.combinations().len(), 1);
+
+        grid.strategies = vec![CopyStrategy::PERCENTAGE, CopyStrategy::ADAPTIVE];
+        grid.copy_sizes = vec![5.0, 10.0, 20.0];
+        grid.aggregation_windows = vec![None, Some(60)];
+        grid.tier_specs = vec![String::new(), "1-10:2,10+:1".to_string()];
+        let combinations = grid.combinations();
+        assert_eq!(combinations.len(), 24);
+
+        let adaptive = combinations
+            .iter()
+            .find(|p| p.strategy == CopyStrategy::ADAPTIVE && !p.tiered_multipliers.is_empty())
+            .unwrap()
+            .apply(&base());
+        assert_eq!(adaptive.adaptive_threshold, Some(500.0));
+        assert_eq!(adaptive.tiered_multipliers.len(), 2);
+
+        grid.tier_specs = vec!["10-1:2".to_string()];
+        assert!(grid.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn sweep_ranks_by_average_roi() {
+        // A winning round trip: larger copies earn more
+        let histories = vec![TraderHistory {
+            trader: "0xabc".to_string(),
+            trades: vec![trade(1, "BUY", 200.0, 0.5), trade(2, "SELL", 200.0, 0.75)],
+            resolutions: HashMap::new(),
+        }];
+        let settings = BacktestSettings { slippage_bps: 0.0, ..Default::default() };
+        let mut grid = SweepGrid::from_config(&base(), &settings);
+        grid.copy_sizes = vec![5.0, 20.0, 10.0];
+
+        let results = run_sweep(histories, &grid, &base(), &settings).await.unwrap();
+        let ranked: Vec<(usize, f64)> = results.iter().map(|r| (r.rank, r.parameters.copy_size)).collect();
+        assert_eq!(ranked, vec![(1, 20.0), (2, 10.0), (3, 5.0)]);
+        // 20% of $100 = $20 → 40 tokens sold at 0.75 = $30
+        assert!((results[0].avg_roi_percent - 1.0).abs() < 1e-9);
+
+        let markdown = to_markdown(&results);
+        assert!(markdown.lines().nth(2).unwrap().starts_with("| 1 | PERCENTAGE 20, max $100, min $1 | +1.00%"));
+        let csv = to_csv(&results);
+        assert_eq!(csv.lines().count(), 4);
+        assert!(csv.lines().nth(1).unwrap().starts_with("1,PERCENTAGE,20,100,1,,\"\",1.0000"));
+    }
+}
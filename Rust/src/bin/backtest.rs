@@ -0,0 +1,173 @@
+// Backtest a trader's history against the configured copy strategy
+//
+// Usage: cargo run --bin backtest -- <TRADER_ADDRESS> [options]
+//   --source db|api            Trades journaled by the bot (default) or the data API
+//   --days N                   Only replay the last N days
+//   --balance USD              Starting balance (default 1000)
+//   --slippage-bps N           Price penalty per fill (default 50)
+//   --fee-bps N                Fee per fill (default 0)
+//   --aggregation-window SECS  Combine small BUYs like TRADE_AGGREGATION_ENABLED
+//   --no-resolve               Value open positions at their last price instead of looking up resolutions
+//   --output FILE              Write the full report (with equity curve) as JSON
+//   --equity-csv FILE          Write the equity curve as CSV
+//   --config FILE, --set KEY=VALUE  Config layers, as for the bot
+//
+// The copy strategy is read from the same settings as the bot (COPY_STRATEGY, COPY_SIZE, ...), through
+// .env, bot.toml and --set.
+
+use std::collections::HashMap;
+use std::process;
+use polymarket_copy_trading_bot::backtest::history::{fetch_resolutions, fetch_trades_from_api, load_trades_from_db};
+use polymarket_copy_trading_bot::backtest::{run_backtest, BacktestReport, BacktestSettings};
+use polymarket_copy_trading_bot::config::copy_strategy::CopyStrategyConfig;
+use polymarket_copy_trading_bot::config::env::{load_env, load_layers};
+use polymarket_copy_trading_bot::storage::init_storage;
+use polymarket_copy_trading_bot::utils::logger::Logger;
+
+struct Args {
+    trader: String,
+    from_api: bool,
+    days: Option<i64>,
+    settings: BacktestSettings,
+    resolve: bool,
+    output: Option<String>,
+    equity_csv: Option<String>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = std::env::args().skip(1);
+    let mut parsed = Args {
+        trader: String::new(),
+        from_api: false,
+        days: None,
+        settings: BacktestSettings::default(),
+        resolve: true,
+        output: None,
+        equity_csv: None,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("{} requires a value", name));
+        let number = |name: &str, value: String| value.parse::<f64>().map_err(|_| format!("Invalid {}: {}", name, value));
+        match arg.as_str() {
+            "--source" => match value("--source")?.as_str() {
+                "db" => parsed.from_api = false,
+                "api" => parsed.from_api = true,
+                other => return Err(format!("Unknown source: {} (expected db or api)", other)),
+            },
+            "--days" => parsed.days = Some(number("--days", value("--days")?)? as i64),
+            "--balance" => parsed.settings.starting_balance = number("--balance", value("--balance")?)?,
+            "--slippage-bps" => parsed.settings.slippage_bps = number("--slippage-bps", value("--slippage-bps")?)?,
+            "--fee-bps" => parsed.settings.fee_bps = number("--fee-bps", value("--fee-bps")?)?,
+            "--aggregation-window" => {
+                parsed.settings.aggregation_window_seconds =
+                    Some(number("--aggregation-window", value("--aggregation-window")?)? as u64)
+            }
+            "--no-resolve" => parsed.resolve = false,
+            "--output" => parsed.output = Some(value("--output")?),
+            "--equity-csv" => parsed.equity_csv = Some(value("--equity-csv")?),
+            // Config layer flags, already read by load_layers
+            "--config" | "--set" => {
+                value(arg.as_str())?;
+            }
+            "--paper" => {}
+            other if other.starts_with("--") => return Err(format!("Unknown option: {}", other)),
+            other => parsed.trader = other.to_lowercase(),
+        }
+    }
+
+    if !parsed.trader.starts_with("0x") || parsed.trader.len() != 42 {
+        return Err("Usage: backtest <TRADER_ADDRESS> [--source db|api] [--days N] [--balance USD] \
+             [--slippage-bps N] [--fee-bps N] [--aggregation-window SECS] [--no-resolve] \
+             [--output FILE] [--equity-csv FILE] [--config FILE] [--set KEY=VALUE]"
+            .to_string());
+    }
+    Ok(parsed)
+}
+
+fn print_report(trader: &str, report: &BacktestReport) {
+    Logger::header(&format!("📈 BACKTEST RESULTS: {}", trader));
+    println!("   Trader trades:     {}", report.trader_trades);
+    println!("   Copied / skipped:  {} / {}", report.copied_trades, report.skipped_trades);
+    println!("   Starting balance:  ${:.2}", report.starting_balance);
+    println!("   Final equity:      ${:.2} (cash ${:.2})", report.final_equity, report.final_balance);
+    println!("   ROI:               {:+.2}%", report.roi_percent);
+    println!("   Max drawdown:      {:.2}%", report.max_drawdown_percent);
+    println!(
+        "   Win rate:          {:.1}% of {} closed position(s), {} still open",
+        report.win_rate_percent, report.closed_positions, report.open_positions
+    );
+    println!("   Volume / fees:     ${:.2} / ${:.2}", report.total_volume, report.total_fees);
+    Logger::separator();
+}
+
+fn write_equity_csv(path: &str, report: &BacktestReport) -> std::io::Result<()> {
+    let mut csv = String::from("timestamp,equity\n");
+    for point in &report.equity_curve {
+        csv.push_str(&format!("{},{:.6}\n", point.timestamp, point.equity));
+    }
+    std::fs::write(path, csv)
+}
+
+async fn run(args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    let since = args.days.map(|days| chrono::Utc::now().timestamp() - days * 86_400);
+
+    let trades = if args.from_api {
+        Logger::info(&format!("Fetching trade history for {} from the data API...", args.trader));
+        fetch_trades_from_api(&args.trader, since).await?
+    } else {
+        // Stored history lives in the bot's storage, so the bot's env must be complete
+        load_env()?;
+        init_storage().await?;
+        Logger::info(&format!("Loading stored trade history for {}...", args.trader));
+        load_trades_from_db(&args.trader, since).await?
+    };
+    if trades.is_empty() {
+        Logger::warning("No trades found - nothing to replay");
+        return Ok(());
+    }
+    Logger::info(&format!("Replaying {} trades", trades.len()));
+
+    let resolutions = if args.resolve {
+        Logger::info("Looking up market resolutions...");
+        fetch_resolutions(&trades).await?
+    } else {
+        HashMap::new()
+    };
+
+    let config = CopyStrategyConfig::from_env();
+    let report = run_backtest(&trades, &config, &args.settings, &resolutions);
+    print_report(&args.trader, &report);
+
+    if let Some(path) = &args.output {
+        std::fs::write(path, serde_json::to_string_pretty(&report)?)?;
+        Logger::success(&format!("Report written to {}", path));
+    }
+    if let Some(path) = &args.equity_csv {
+        write_equity_csv(path, &report)?;
+        Logger::success(&format!("Equity curve written to {}", path));
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() {
+    // The strategy under test comes from the same .env, bot.toml and --set layers as the bot's
+    if let Err(e) = load_layers() {
+        eprintln!("{}", e);
+        process::exit(2);
+    }
+
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}", message);
+            process::exit(2);
+        }
+    };
+
+    if let Err(e) = run(args).await {
+        Logger::error(&format!("Backtest failed: {}", e));
+        process::exit(1);
+    }
+}
@@ -0,0 +1,70 @@
+// Inspect or clear the kill switch
+//
+// Usage: cargo run --bin kill-switch -- [status|resume] [--paper]
+//   status   Show whether new BUYs are halted, and the equity baselines (default)
+//   resume   Clear the halt; a running bot picks it up at its next check
+//
+// The state file is KILL_SWITCH_FILE, read through the same configuration layers as the bot.
+
+use std::process;
+use polymarket_copy_trading_bot::config::env::{load_env, ENV};
+use polymarket_copy_trading_bot::services::kill_switch::KillSwitchState;
+use polymarket_copy_trading_bot::utils::errors::AppResult;
+use polymarket_copy_trading_bot::utils::logger::Logger;
+
+fn run(command: &str) -> AppResult<()> {
+    let path = &ENV().risk.kill_switch_file;
+    let mut state = KillSwitchState::load(path)?;
+    match command {
+        "resume" => match state.resume() {
+            Some(halt) => {
+                state.save(path)?;
+                Logger::success(&format!("Kill switch cleared ({}: {})", halt.trigger, halt.reason));
+                Logger::info("Equity baselines restart from the next check");
+            }
+            None => Logger::info("New BUYs are not halted - nothing to resume"),
+        },
+        _ => {
+            match &state.halt {
+                Some(halt) => Logger::warning(&format!("🛑 New BUYs halted ({}): {}", halt.trigger, halt.reason)),
+                None => Logger::success("New BUYs are allowed"),
+            }
+            Logger::info(&format!(
+                "High-water mark ${:.2}, equity at the start of {} ${:.2} ({})",
+                state.high_water_mark,
+                if state.day.is_empty() { "the day" } else { &state.day },
+                state.day_start_equity,
+                path
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    // Configuration flags (--config, --set, --paper) are handled by load_env
+    let mut command = "status".to_string();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" | "--set" => {
+                args.next();
+            }
+            _ if arg.starts_with("--") => {}
+            _ => command = arg,
+        }
+    }
+    if command != "status" && command != "resume" {
+        eprintln!("Unknown command: {} (use status or resume)", command);
+        process::exit(2);
+    }
+
+    if let Err(e) = load_env() {
+        eprintln!("Failed to load environment variables: {}", e);
+        process::exit(1);
+    }
+    if let Err(e) = run(&command) {
+        Logger::error(&e.to_string());
+        process::exit(1);
+    }
+}
@@ -0,0 +1,226 @@
+// Sweep copy strategy settings over several traders' history and rank the combinations
+//
+// Usage: cargo run --bin sweep -- --traders <ADDR1,ADDR2,...> [grid] [options]
+// Grid (comma-separated values, each defaults to the current env value):
+//   --strategies PERCENTAGE,FIXED,ADAPTIVE
+//   --copy-sizes 5,10,20
+//   --max-order-sizes 50,100
+//   --min-order-sizes 1,2
+//   --aggregation-windows off,60,300
+//   --tiers "1-10:2.0,10+:1.0;none"   Alternatives separated by ';', "none" for no tiers
+// Options:
+//   --source db|api, --days N, --balance USD, --slippage-bps N, --fee-bps N, --no-resolve
+//   --format md|csv|json (default md), --output FILE, --top N
+//   --config FILE, --set KEY=VALUE   Config layers the defaults are read from, as for the bot
+
+use std::process;
+use futures::future::join_all;
+use polymarket_copy_trading_bot::backtest::history::{fetch_resolutions, fetch_trades_from_api, load_trades_from_db};
+use polymarket_copy_trading_bot::backtest::sweep::{run_sweep, to_csv, to_markdown, SweepGrid, TraderHistory};
+use polymarket_copy_trading_bot::backtest::BacktestSettings;
+use polymarket_copy_trading_bot::config::copy_strategy::{CopyStrategy, CopyStrategyConfig};
+use polymarket_copy_trading_bot::config::env::{load_env, load_layers};
+use polymarket_copy_trading_bot::storage::init_storage;
+use polymarket_copy_trading_bot::utils::errors::AppResult;
+use polymarket_copy_trading_bot::utils::logger::Logger;
+
+#[derive(PartialEq)]
+enum Format {
+    Markdown,
+    Csv,
+    Json,
+}
+
+struct Args {
+    traders: Vec<String>,
+    from_api: bool,
+    days: Option<i64>,
+    settings: BacktestSettings,
+    grid: SweepGrid,
+    resolve: bool,
+    format: Format,
+    output: Option<String>,
+    top: Option<usize>,
+}
+
+fn parse_list<T>(name: &str, value: &str, parse: impl Fn(&str) -> Option<T>) -> Result<Vec<T>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| parse(item).ok_or_else(|| format!("Invalid {} value: {}", name, item)))
+        .collect()
+}
+
+fn parse_args() -> Result<Args, String> {
+    let settings = BacktestSettings::default();
+    let mut parsed = Args {
+        traders: Vec::new(),
+        from_api: false,
+        days: None,
+        grid: SweepGrid::from_config(&CopyStrategyConfig::from_env(), &settings),
+        settings,
+        resolve: true,
+        format: Format::Markdown,
+        output: None,
+        top: None,
+    };
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} requires a value", arg));
+        let number = |name: &str, value: String| value.parse::<f64>().map_err(|_| format!("Invalid {}: {}", name, value));
+        match arg.as_str() {
+            "--traders" => {
+                parsed.traders = parse_list("--traders", &value()?, |address| {
+                    let address = address.to_lowercase();
+                    (address.starts_with("0x") && address.len() == 42).then_some(address)
+                })?
+            }
+            "--source" => match value()?.as_str() {
+                "db" => parsed.from_api = false,
+                "api" => parsed.from_api = true,
+                other => return Err(format!("Unknown source: {} (expected db or api)", other)),
+            },
+            "--days" => parsed.days = Some(number("--days", value()?)? as i64),
+            "--balance" => parsed.settings.starting_balance = number("--balance", value()?)?,
+            "--slippage-bps" => parsed.settings.slippage_bps = number("--slippage-bps", value()?)?,
+            "--fee-bps" => parsed.settings.fee_bps = number("--fee-bps", value()?)?,
+            "--no-resolve" => parsed.resolve = false,
+            "--strategies" => {
+                parsed.grid.strategies = parse_list("--strategies", &value()?, |strategy| {
+                    match strategy.to_uppercase().as_str() {
+                        "PERCENTAGE" | "FIXED" | "ADAPTIVE" => Some(CopyStrategy::from_str(strategy)),
+                        _ => None,
+                    }
+                })?
+            }
+            "--copy-sizes" => parsed.grid.copy_sizes = parse_list("--copy-sizes", &value()?, |v| v.parse().ok())?,
+            "--max-order-sizes" => {
+                parsed.grid.max_order_sizes = parse_list("--max-order-sizes", &value()?, |v| v.parse().ok())?
+            }
+            "--min-order-sizes" => {
+                parsed.grid.min_order_sizes = parse_list("--min-order-sizes", &value()?, |v| v.parse().ok())?
+            }
+            "--aggregation-windows" => {
+                parsed.grid.aggregation_windows = parse_list("--aggregation-windows", &value()?, |v| match v {
+                    "off" | "none" | "0" => Some(None),
+                    _ => v.parse().ok().map(Some),
+                })?
+            }
+            "--tiers" => {
+                parsed.grid.tier_specs = value()?
+                    .split(';')
+                    .map(str::trim)
+                    .map(|spec| if spec == "none" { String::new() } else { spec.to_string() })
+                    .collect()
+            }
+            "--format" => {
+                parsed.format = match value()?.as_str() {
+                    "md" | "markdown" => Format::Markdown,
+                    "csv" => Format::Csv,
+                    "json" => Format::Json,
+                    other => return Err(format!("Unknown format: {} (expected md, csv or json)", other)),
+                }
+            }
+            "--output" => parsed.output = Some(value()?),
+            "--top" => parsed.top = Some(number("--top", value()?)? as usize),
+            // Config layer flags, already read by load_layers
+            "--config" | "--set" => {
+                value()?;
+            }
+            "--paper" => {}
+            other => return Err(format!("Unknown option: {}", other)),
+        }
+    }
+
+    if parsed.traders.is_empty() {
+        return Err("Usage: sweep --traders <ADDR1,ADDR2,...> [--strategies ..] [--copy-sizes ..] \
+             [--max-order-sizes ..] [--min-order-sizes ..] [--aggregation-windows ..] [--tiers ..] \
+             [--source db|api] [--days N] [--format md|csv|json] [--output FILE] [--top N] \
+             [--config FILE] [--set KEY=VALUE]"
+            .to_string());
+    }
+    Ok(parsed)
+}
+
+async fn load_history(trader: String, from_api: bool, since: Option<i64>, resolve: bool) -> AppResult<TraderHistory> {
+    let trades = if from_api {
+        fetch_trades_from_api(&trader, since).await?
+    } else {
+        load_trades_from_db(&trader, since).await?
+    };
+    let resolutions = if resolve {
+        fetch_resolutions(&trades).await?
+    } else {
+        Default::default()
+    };
+    Logger::info(&format!("{}: {} trades loaded", trader, trades.len()));
+    Ok(TraderHistory { trader, trades, resolutions })
+}
+
+async fn run(args: Args) -> Result<(), Box<dyn std::error::Error>> {
+    let since = args.days.map(|days| chrono::Utc::now().timestamp() - days * 86_400);
+    if !args.from_api {
+        // Stored history lives in the bot's storage, so the bot's env must be complete
+        load_env()?;
+        init_storage().await?;
+    }
+
+    Logger::info(&format!("Loading history for {} trader(s)...", args.traders.len()));
+    let histories = join_all(
+        args.traders
+            .iter()
+            .map(|trader| load_history(trader.clone(), args.from_api, since, args.resolve)),
+    )
+    .await
+    .into_iter()
+    .collect::<AppResult<Vec<_>>>()?;
+
+    let combinations = args.grid.combinations().len();
+    Logger::info(&format!(
+        "Running {} combination(s) × {} trader(s)...",
+        combinations,
+        histories.len()
+    ));
+    let mut results = run_sweep(histories, &args.grid, &CopyStrategyConfig::from_env(), &args.settings).await?;
+    if let Some(top) = args.top {
+        results.truncate(top);
+    }
+
+    let rendered = match args.format {
+        Format::Markdown => to_markdown(&results),
+        Format::Csv => to_csv(&results),
+        Format::Json => serde_json::to_string_pretty(&results)?,
+    };
+    match &args.output {
+        Some(path) => {
+            std::fs::write(path, rendered)?;
+            Logger::success(&format!("Ranked results written to {}", path));
+        }
+        None => println!("\n{}", rendered),
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() {
+    // The strategy under test comes from the same .env, bot.toml and --set layers as the bot's
+    if let Err(e) = load_layers() {
+        eprintln!("{}", e);
+        process::exit(2);
+    }
+
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}", message);
+            process::exit(2);
+        }
+    };
+
+    if let Err(e) = run(args).await {
+        Logger::error(&format!("Sweep failed: {}", e));
+        process::exit(1);
+    }
+}
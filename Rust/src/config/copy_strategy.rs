@@ -1,7 +1,9 @@
 // Copy strategy configuration
 
+use crate::utils::errors::AppError;
 use serde::{Deserialize, Serialize};
-use std::env;
+use crate::config::env::var;
+use crate::services::risk_manager::RiskLimit;
 
 /// Order size calculation result
 #[derive(Debug, Clone)]
@@ -14,10 +16,13 @@ pub struct OrderSizeCalculation {
     pub capped_by_max: bool,
     pub reduced_by_balance: bool,
     pub below_minimum: bool,
+    /// Exposure caps that trimmed or rejected the order, in the order they applied
+    pub risk_limits: Vec<RiskLimit>,
     pub reasoning: String,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(clippy::upper_case_acronyms)] // Variants match the COPY_STRATEGY env values
 pub enum CopyStrategy {
     PERCENTAGE,
     FIXED,
@@ -25,6 +30,7 @@ pub enum CopyStrategy {
 }
 
 impl CopyStrategy {
+    #[allow(clippy::should_implement_trait)] // Infallible: unknown values fall back to PERCENTAGE
     pub fn from_str(s: &str) -> Self {
         match s.to_uppercase().as_str() {
             "PERCENTAGE" => CopyStrategy::PERCENTAGE,
@@ -35,11 +41,45 @@ impl CopyStrategy {
     }
 }
 
+/// Multiplier applied to trader orders within a USD size range
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MultiplierTier {
+    pub min: f64,         // Minimum trade size in USD (inclusive)
+    pub max: Option<f64>, // Maximum trade size in USD (exclusive), None = infinity
+    pub multiplier: f64,
+}
+
+#[derive(Debug, Clone)]
 pub struct CopyStrategyConfig {
     pub strategy: CopyStrategy,
     pub copy_size: f64,
     pub max_order_size_usd: f64,
     pub min_order_size_usd: f64,
+    // Adaptive strategy parameters (only used if strategy = ADAPTIVE)
+    pub adaptive_min_percent: Option<f64>, // Minimum percentage for large orders
+    pub adaptive_max_percent: Option<f64>, // Maximum percentage for small orders
+    pub adaptive_threshold: Option<f64>,   // Threshold in USD to trigger adaptation
+    // Multipliers keyed on trader order size (TIERED_MULTIPLIERS), sorted by min
+    pub tiered_multipliers: Vec<MultiplierTier>,
+    // Single multiplier (TRADE_MULTIPLIER), ignored if tiered_multipliers is set
+    pub trade_multiplier: Option<f64>,
+}
+
+/// What `from_env` reads with none of the strategy variables set
+impl Default for CopyStrategyConfig {
+    fn default() -> Self {
+        Self {
+            strategy: CopyStrategy::PERCENTAGE,
+            copy_size: 10.0,
+            max_order_size_usd: 100.0,
+            min_order_size_usd: 1.0,
+            adaptive_min_percent: None,
+            adaptive_max_percent: None,
+            adaptive_threshold: None,
+            tiered_multipliers: Vec::new(),
+            trade_multiplier: None,
+        }
+    }
 }
 
 impl CopyStrategyConfig {
@@ -47,31 +87,63 @@ impl CopyStrategyConfig {
         use crate::config::env::get_env_or_default;
         
         // Support legacy COPY_PERCENTAGE + TRADE_MULTIPLIER for backward compatibility
-        let has_legacy_config = env::var("COPY_PERCENTAGE").is_ok() && env::var("COPY_STRATEGY").is_err();
+        let has_legacy_config = var("COPY_PERCENTAGE").is_ok() && var("COPY_STRATEGY").is_err();
         
         if has_legacy_config {
             eprintln!("⚠️  Using legacy COPY_PERCENTAGE configuration. Consider migrating to COPY_STRATEGY.");
             let copy_percentage: f64 = get_env_or_default("COPY_PERCENTAGE", "10.0").parse().unwrap_or(10.0);
             let trade_multiplier: f64 = get_env_or_default("TRADE_MULTIPLIER", "1.0").parse().unwrap_or(1.0);
-            let effective_percentage = copy_percentage * trade_multiplier;
+            let tiered_multipliers = tiered_multipliers_from_env();
+            // TIERED_MULTIPLIERS overrides TRADE_MULTIPLIER
+            let effective_percentage = if tiered_multipliers.is_empty() {
+                copy_percentage * trade_multiplier
+            } else {
+                copy_percentage
+            };
             
             return Self {
                 strategy: CopyStrategy::PERCENTAGE,
                 copy_size: effective_percentage,
                 max_order_size_usd: get_env_or_default("MAX_ORDER_SIZE_USD", "100.0").parse().unwrap_or(100.0),
                 min_order_size_usd: get_env_or_default("MIN_ORDER_SIZE_USD", "1.0").parse().unwrap_or(1.0),
+                adaptive_min_percent: None,
+                adaptive_max_percent: None,
+                adaptive_threshold: None,
+                // TRADE_MULTIPLIER is already folded into copy_size above
+                tiered_multipliers,
+                trade_multiplier: None,
             };
         }
         
         // Parse new copy strategy configuration
         let strategy_str = get_env_or_default("COPY_STRATEGY", "PERCENTAGE");
         let strategy = CopyStrategy::from_str(&strategy_str);
+        let copy_size: f64 = get_env_or_default("COPY_SIZE", "10.0").parse().unwrap_or(10.0);
+        
+        // Add adaptive strategy parameters if applicable
+        let (adaptive_min_percent, adaptive_max_percent, adaptive_threshold) = if strategy == CopyStrategy::ADAPTIVE {
+            (
+                Some(var("ADAPTIVE_MIN_PERCENT").ok().and_then(|v| v.parse().ok()).unwrap_or(copy_size)),
+                Some(var("ADAPTIVE_MAX_PERCENT").ok().and_then(|v| v.parse().ok()).unwrap_or(copy_size)),
+                Some(get_env_or_default("ADAPTIVE_THRESHOLD_USD", "500.0").parse().unwrap_or(500.0)),
+            )
+        } else {
+            (None, None, None)
+        };
         
         Self {
             strategy,
-            copy_size: get_env_or_default("COPY_SIZE", "10.0").parse().unwrap_or(10.0),
+            copy_size,
             max_order_size_usd: get_env_or_default("MAX_ORDER_SIZE_USD", "100.0").parse().unwrap_or(100.0),
             min_order_size_usd: get_env_or_default("MIN_ORDER_SIZE_USD", "1.0").parse().unwrap_or(1.0),
+            adaptive_min_percent,
+            adaptive_max_percent,
+            adaptive_threshold,
+            tiered_multipliers: tiered_multipliers_from_env(),
+            trade_multiplier: var("TRADE_MULTIPLIER")
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .filter(|m| *m != 1.0),
         }
     }
     
@@ -108,7 +180,7 @@ impl CopyStrategyConfig {
             }
         };
         
-        // Apply multiplier (for now, just 1.0 - can be extended with tiered multipliers)
+        // Apply tiered or single multiplier based on trader's order size
         let multiplier = get_trade_multiplier(self, trader_order_size);
         let mut final_amount = base_amount * multiplier;
         
@@ -153,20 +225,318 @@ impl CopyStrategyConfig {
             capped_by_max,
             reduced_by_balance,
             below_minimum,
+            risk_limits: Vec::new(),
             reasoning,
         }
     }
     
     /// Calculate adaptive percentage based on trader's order size
-    fn calculate_adaptive_percent(&self, _trader_order_size: f64) -> f64 {
-        // For now, return copy_size (ADAPTIVE strategy needs more config)
-        // TODO: Implement full adaptive logic with min/max percent and threshold
-        self.copy_size
+    ///
+    /// - Small orders (< threshold): scale up towards `adaptive_max_percent` (reached at $0)
+    /// - Large orders (>= threshold): scale down towards `adaptive_min_percent` (reached at 2x threshold)
+    /// - At the threshold itself: `copy_size`
+    fn calculate_adaptive_percent(&self, trader_order_size: f64) -> f64 {
+        let min_percent = self.adaptive_min_percent.unwrap_or(self.copy_size);
+        let max_percent = self.adaptive_max_percent.unwrap_or(self.copy_size);
+        let threshold = self.adaptive_threshold.unwrap_or(500.0);
+        
+        if trader_order_size >= threshold {
+            // Large order: scale down to min_percent
+            let factor = (trader_order_size / threshold - 1.0).min(1.0);
+            lerp(self.copy_size, min_percent, factor)
+        } else {
+            // Small order: scale up to max_percent
+            let factor = trader_order_size / threshold;
+            lerp(max_percent, self.copy_size, factor)
+        }
     }
 }
 
-/// Get trade multiplier (placeholder - can be extended with tiered multipliers)
-pub fn get_trade_multiplier(_config: &CopyStrategyConfig, _trader_order_size: f64) -> f64 {
-    // TODO: Implement tiered multipliers
-    1.0
+/// Linear interpolation between two values (t is clamped to [0, 1])
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t.clamp(0.0, 1.0)
+}
+
+/// TIERED_MULTIPLIERS from the environment (validated in `config::env`)
+fn tiered_multipliers_from_env() -> Vec<MultiplierTier> {
+    var("TIERED_MULTIPLIERS")
+        .ok()
+        .and_then(|spec| parse_tiered_multipliers(&spec).ok())
+        .unwrap_or_default()
+}
+
+/// Parse a tier spec such as `1-10:2.0,10-100:1.0,100-500:0.5,500+:0.2`
+///
+/// Each tier is `min-max:multiplier` (max exclusive) or `min+:multiplier` for an open upper bound.
+/// Tiers are returned sorted by `min`; overlapping tiers and an open tier that is not last are rejected.
+pub fn parse_tiered_multipliers(spec: &str) -> Result<Vec<MultiplierTier>, AppError> {
+    let invalid = |message: String| AppError::ConfigurationError(message);
+    let mut tiers = Vec::new();
+    
+    for tier_def in spec.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        let (range, multiplier_str) = match tier_def.split(':').collect::<Vec<_>>()[..] {
+            [range, multiplier] => (range.trim(), multiplier.trim()),
+            _ => {
+                return Err(invalid(format!(
+                    "Invalid tier format: \"{}\". Expected \"min-max:multiplier\" or \"min+:multiplier\"",
+                    tier_def
+                )))
+            }
+        };
+        
+        let multiplier = match multiplier_str.parse::<f64>() {
+            Ok(m) if m.is_finite() && m >= 0.0 => m,
+            _ => return Err(invalid(format!("Invalid multiplier in tier \"{}\": {}", tier_def, multiplier_str))),
+        };
+        
+        let parse_bound = |value: &str| value.trim().parse::<f64>().ok().filter(|v| v.is_finite() && *v >= 0.0);
+        
+        if let Some(min_str) = range.strip_suffix('+') {
+            // Infinite upper bound: "500+"
+            let min = parse_bound(min_str)
+                .ok_or_else(|| invalid(format!("Invalid minimum value in tier \"{}\": {}", tier_def, range)))?;
+            tiers.push(MultiplierTier { min, max: None, multiplier });
+        } else if let Some((min_str, max_str)) = range.split_once('-') {
+            // Bounded range: "100-500"
+            let min = parse_bound(min_str)
+                .ok_or_else(|| invalid(format!("Invalid minimum value in tier \"{}\": {}", tier_def, min_str)))?;
+            let max = parse_bound(max_str).filter(|max| *max > min).ok_or_else(|| {
+                invalid(format!(
+                    "Invalid maximum value in tier \"{}\": {} (must be > {})",
+                    tier_def, max_str, min
+                ))
+            })?;
+            tiers.push(MultiplierTier { min, max: Some(max), multiplier });
+        } else {
+            return Err(invalid(format!(
+                "Invalid range format in tier \"{}\". Use \"min-max\" or \"min+\"",
+                tier_def
+            )));
+        }
+    }
+    
+    // Sort tiers by min value
+    tiers.sort_by(|a, b| a.min.total_cmp(&b.min));
+    
+    // Validate no overlaps
+    for pair in tiers.windows(2) {
+        let (current, next) = (pair[0], pair[1]);
+        match current.max {
+            None => {
+                return Err(invalid(format!(
+                    "Tier with infinite upper bound must be last: {}+",
+                    current.min
+                )))
+            }
+            Some(max) if max > next.min => {
+                let next_max = next.max.map(|m| m.to_string()).unwrap_or_else(|| "∞".to_string());
+                return Err(invalid(format!(
+                    "Overlapping tiers: [{}-{}] and [{}-{}]",
+                    current.min, max, next.min, next_max
+                )));
+            }
+            Some(_) => {}
+        }
+    }
+    
+    Ok(tiers)
+}
+
+/// Get the multiplier for a trader order of the given USD size
+///
+/// Orders below the first tier use the first tier; orders falling in a gap use the tier below it.
+pub fn get_trade_multiplier(config: &CopyStrategyConfig, trader_order_size: f64) -> f64 {
+    // Use tiered multipliers if configured
+    if let Some(first) = config.tiered_multipliers.first() {
+        return config
+            .tiered_multipliers
+            .iter()
+            .rev()
+            .find(|tier| trader_order_size >= tier.min)
+            .unwrap_or(first)
+            .multiplier;
+    }
+    
+    // Fall back to single multiplier if configured, default: no multiplier
+    config.trade_multiplier.unwrap_or(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn adaptive_config() -> CopyStrategyConfig {
+        CopyStrategyConfig {
+            strategy: CopyStrategy::ADAPTIVE,
+            max_order_size_usd: 1_000.0,
+            adaptive_min_percent: Some(5.0),
+            adaptive_max_percent: Some(20.0),
+            adaptive_threshold: Some(500.0),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn lerp_clamps_factor() {
+        let cases = [
+            (0.0, 10.0, 0.0, 0.0),
+            (0.0, 10.0, 0.5, 5.0),
+            (0.0, 10.0, 1.0, 10.0),
+            (0.0, 10.0, -1.0, 0.0),
+            (0.0, 10.0, 2.0, 10.0),
+            (20.0, 10.0, 0.25, 17.5),
+        ];
+        for (a, b, t, expected) in cases {
+            assert_eq!(lerp(a, b, t), expected, "lerp({}, {}, {})", a, b, t);
+        }
+    }
+
+    #[test]
+    fn adaptive_percent_follows_interpolation_curve() {
+        let config = adaptive_config();
+        // (trader order size, expected percent)
+        let cases = [
+            (0.0, 20.0),     // $0 → max percent
+            (125.0, 17.5),   // quarter of threshold
+            (250.0, 15.0),   // half of threshold
+            (500.0, 10.0),   // threshold → copy size
+            (750.0, 7.5),    // 1.5x threshold
+            (1_000.0, 5.0),  // 2x threshold → min percent
+            (10_000.0, 5.0), // beyond 2x stays at min percent
+        ];
+        for (order_size, expected) in cases {
+            let percent = config.calculate_adaptive_percent(order_size);
+            assert!(
+                (percent - expected).abs() < 1e-9,
+                "order ${}: expected {}%, got {}%",
+                order_size,
+                expected,
+                percent
+            );
+        }
+    }
+
+    #[test]
+    fn adaptive_percent_defaults_to_copy_size() {
+        let config = CopyStrategyConfig {
+            adaptive_min_percent: None,
+            adaptive_max_percent: None,
+            adaptive_threshold: None,
+            ..adaptive_config()
+        };
+        for order_size in [0.0, 100.0, 500.0, 5_000.0] {
+            assert_eq!(config.calculate_adaptive_percent(order_size), 10.0);
+        }
+    }
+
+    #[test]
+    fn adaptive_strategy_sizes_orders() {
+        let config = adaptive_config();
+        let cases = [
+            (100.0, 18.0),   // 18% of $100
+            (500.0, 50.0),   // 10% of $500
+            (1_000.0, 50.0), // 5% of $1000
+        ];
+        for (order_size, expected) in cases {
+            let result = config.calculate_order_size(order_size, 10_000.0, 0.0);
+            assert!((result.final_amount - expected).abs() < 1e-9, "order ${}", order_size);
+            assert_eq!(result.strategy, CopyStrategy::ADAPTIVE);
+        }
+    }
+
+    fn tier(min: f64, max: Option<f64>, multiplier: f64) -> MultiplierTier {
+        MultiplierTier { min, max, multiplier }
+    }
+
+    #[test]
+    fn parses_tier_spec() {
+        let tiers = parse_tiered_multipliers("1-10:2.0, 10-100:1.0,100-500:0.5,500+:0.2").unwrap();
+        assert_eq!(
+            tiers,
+            vec![
+                tier(1.0, Some(10.0), 2.0),
+                tier(10.0, Some(100.0), 1.0),
+                tier(100.0, Some(500.0), 0.5),
+                tier(500.0, None, 0.2),
+            ]
+        );
+        // Order in the spec does not matter
+        let shuffled = parse_tiered_multipliers("500+:0.2,1-10:2.0,100-500:0.5,10-100:1.0").unwrap();
+        assert_eq!(shuffled, tiers);
+        assert!(parse_tiered_multipliers("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_tier_specs() {
+        let cases = [
+            ("1-10", "Invalid tier format"),
+            ("1-10:2:3", "Invalid tier format"),
+            ("1-10:abc", "Invalid multiplier"),
+            ("1-10:-1", "Invalid multiplier"),
+            ("x+:1.0", "Invalid minimum value"),
+            ("a-10:1.0", "Invalid minimum value"),
+            ("10-5:1.0", "Invalid maximum value"),
+            ("10-10:1.0", "Invalid maximum value"),
+            ("10:1.0", "Invalid range format"),
+            ("1-10:2.0,5-20:1.0", "Overlapping tiers"),
+            ("100+:0.5,500+:0.2", "infinite upper bound must be last"),
+        ];
+        for (spec, expected) in cases {
+            match parse_tiered_multipliers(spec) {
+                Err(AppError::ConfigurationError(message)) => {
+                    assert!(message.contains(expected), "{}: {}", spec, message)
+                }
+                other => panic!("{}: expected error, got {:?}", spec, other),
+            }
+        }
+    }
+
+    #[test]
+    fn trade_multiplier_follows_tiers() {
+        let config = CopyStrategyConfig {
+            strategy: CopyStrategy::PERCENTAGE,
+            tiered_multipliers: parse_tiered_multipliers("1-10:2.0,10-100:1.0,100-500:0.5,600+:0.2").unwrap(),
+            ..adaptive_config()
+        };
+        let cases = [
+            (0.5, 2.0),      // below the first tier
+            (1.0, 2.0),
+            (9.99, 2.0),
+            (10.0, 1.0),     // max is exclusive
+            (250.0, 0.5),
+            (550.0, 0.5),    // gap between 500 and 600
+            (600.0, 0.2),
+            (1_000_000.0, 0.2),
+        ];
+        for (order_size, expected) in cases {
+            assert_eq!(get_trade_multiplier(&config, order_size), expected, "order ${}", order_size);
+        }
+    }
+
+    #[test]
+    fn single_multiplier_applies_without_tiers() {
+        let mut config = CopyStrategyConfig {
+            strategy: CopyStrategy::PERCENTAGE,
+            ..adaptive_config()
+        };
+        assert_eq!(get_trade_multiplier(&config, 50.0), 1.0);
+        config.trade_multiplier = Some(1.5);
+        assert_eq!(get_trade_multiplier(&config, 50.0), 1.5);
+    }
+
+    #[test]
+    fn tiered_multiplier_scales_order_size() {
+        let config = CopyStrategyConfig {
+            strategy: CopyStrategy::PERCENTAGE,
+            tiered_multipliers: parse_tiered_multipliers("1-10:2.0,10-100:1.0,100+:0.5").unwrap(),
+            ..adaptive_config()
+        };
+        // 10% of $5 = $0.50 → 2x = $1.00
+        let small = config.calculate_order_size(5.0, 1_000.0, 0.0);
+        assert!((small.final_amount - 1.0).abs() < 1e-9);
+        assert!(small.reasoning.contains("2x multiplier"));
+        // 10% of $400 = $40 → 0.5x = $20
+        let large = config.calculate_order_size(400.0, 1_000.0, 0.0);
+        assert!((large.final_amount - 20.0).abs() < 1e-9);
+    }
 }
@@ -0,0 +1,209 @@
+// MongoDB connection
+// One pooled client per process; database name and pool options come from MONGO_URI
+
+use std::collections::BTreeSet;
+use std::sync::OnceLock;
+use std::time::Duration;
+use mongodb::bson::{doc, Document};
+use mongodb::error::{Error, ErrorKind, WriteFailure};
+use mongodb::options::{ClientOptions, IndexOptions};
+use mongodb::{Client, Database, IndexModel};
+use crate::config::env::ENV;
+use crate::utils::constants::{DB_CONSTANTS, DB_FIELDS};
+use crate::utils::errors::{AppError, AppResult};
+use crate::utils::logger::Logger;
+
+static CLIENT: OnceLock<Client> = OnceLock::new();
+static DATABASE: OnceLock<Database> = OnceLock::new();
+
+/// Fill in defaults for options the URI leaves unset
+fn apply_defaults(options: &mut ClientOptions) {
+    options.app_name.get_or_insert_with(|| DB_CONSTANTS::APP_NAME.to_string());
+    options
+        .server_selection_timeout
+        .get_or_insert(Duration::from_millis(DB_CONSTANTS::SERVER_SELECTION_TIMEOUT_MS));
+    options.max_pool_size.get_or_insert(DB_CONSTANTS::MAX_POOL_SIZE);
+}
+
+/// Database named in the URI path, or the bot's default
+fn database_name(options: &ClientOptions) -> &str {
+    options
+        .default_database
+        .as_deref()
+        .filter(|name| !name.is_empty())
+        .unwrap_or(DB_CONSTANTS::DEFAULT_DATABASE)
+}
+
+/// Connect to MongoDB, verify the server is reachable and create the indexes the bot relies on
+pub async fn connect_db() -> AppResult<()> {
+    if DATABASE.get().is_some() {
+        return Ok(());
+    }
+
+    let mut options = ClientOptions::parse(&ENV().mongo_uri)
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Invalid MONGO_URI: {}", e)))?;
+    apply_defaults(&mut options);
+    let name = database_name(&options).to_string();
+    let timeout = options.server_selection_timeout;
+
+    let client = Client::with_options(options)
+        .map_err(|e| AppError::DatabaseError(format!("Failed to create MongoDB client: {}", e)))?;
+    let database = client.database(&name);
+
+    // The driver connects lazily, so ping now to surface bad hosts/credentials at startup
+    database.run_command(doc! { "ping": 1 }).await.map_err(|e| {
+        AppError::DatabaseError(format!(
+            "Cannot reach MongoDB database '{}' (timeout {:?}): {}",
+            name,
+            timeout.unwrap_or_default(),
+            e
+        ))
+    })?;
+
+    ensure_indexes(&database, &ENV().user_addresses).await?;
+
+    let _ = CLIENT.set(client);
+    let _ = DATABASE.set(database);
+    Logger::success(&format!("MongoDB connected (database: {})", name));
+    Ok(())
+}
+
+/// Shut down the pool, waiting for in-flight operations to finish
+pub async fn close_db() -> AppResult<()> {
+    if let Some(client) = CLIENT.get() {
+        client.clone().shutdown().await;
+        Logger::info("MongoDB connection closed");
+    }
+    Ok(())
+}
+
+/// Database handle (panics if `connect_db` has not completed)
+pub fn get_database() -> &'static Database {
+    DATABASE.get().expect("Database not connected - call connect_db() first")
+}
+
+/// Whether a driver error is a unique index violation
+fn is_duplicate_key_error(error: &Error) -> bool {
+    match error.kind.as_ref() {
+        ErrorKind::Command(command) => command.code == DB_CONSTANTS::DUPLICATE_KEY_CODE,
+        ErrorKind::Write(WriteFailure::WriteError(write)) => write.code == DB_CONSTANTS::DUPLICATE_KEY_CODE,
+        _ => false,
+    }
+}
+
+/// Per-trader collections to index: existing ones plus those of the tracked addresses
+fn collections_to_index(existing: &[String], addresses: &[String], prefix: &str) -> BTreeSet<String> {
+    existing
+        .iter()
+        .filter(|name| name.starts_with(prefix))
+        .cloned()
+        .chain(addresses.iter().map(|address| format!("{}{}", prefix, address)))
+        .collect()
+}
+
+async fn create_index(database: &Database, collection: &str, keys: Document, options: IndexOptions) -> AppResult<()> {
+    let model = IndexModel::builder().keys(keys).options(options).build();
+    database
+        .collection::<Document>(collection)
+        .create_index(model)
+        .await
+        .map_err(|e| {
+            let hint = if is_duplicate_key_error(&e) {
+                " - the collection already holds duplicates, remove them before restarting"
+            } else {
+                ""
+            };
+            AppError::DatabaseError(format!("Failed to create index on {}: {}{}", collection, e, hint))
+        })?;
+    Ok(())
+}
+
+async fn drop_index_if_present(database: &Database, collection: &str, name: &str) -> AppResult<()> {
+    let collection = database.collection::<Document>(collection);
+    // A collection that does not exist yet has no indexes
+    let names = collection.list_index_names().await.unwrap_or_default();
+    if names.iter().any(|existing| existing == name) {
+        collection.drop_index(name).await.map_err(|e| {
+            AppError::DatabaseError(format!("Failed to drop index {} on {}: {}", name, collection.name(), e))
+        })?;
+    }
+    Ok(())
+}
+
+/// Unique indexes that let inserts and upserts deduplicate on the server, plus the ledger's lookup indexes
+///
+/// Creating an index that already exists is a no-op, so this runs on every startup.
+async fn ensure_indexes(database: &Database, addresses: &[String]) -> AppResult<()> {
+    let existing = database
+        .list_collection_names()
+        .await
+        .map_err(|e| AppError::DatabaseError(format!("Failed to list collections: {}", e)))?;
+
+    for collection in collections_to_index(&existing, addresses, DB_CONSTANTS::ACTIVITY_COLLECTION_PREFIX) {
+        // Trades used to be unique per transaction, which dropped all but one asset of a transaction
+        drop_index_if_present(database, &collection, "transactionHash_unique").await?;
+        // Only non-empty hashes are unique; documents without one don't collide
+        let options = IndexOptions::builder()
+            .name("transactionHash_asset_side_unique".to_string())
+            .unique(true)
+            .partial_filter_expression(doc! { DB_FIELDS::TRANSACTION_HASH: { "$gt": "" } })
+            .build();
+        let keys = doc! { DB_FIELDS::TRANSACTION_HASH: 1, DB_FIELDS::ASSET: 1, DB_FIELDS::SIDE: 1 };
+        create_index(database, &collection, keys, options).await?;
+    }
+
+    for collection in collections_to_index(&existing, addresses, DB_CONSTANTS::POSITION_COLLECTION_PREFIX) {
+        let options = IndexOptions::builder()
+            .name("asset_conditionId_unique".to_string())
+            .unique(true)
+            .build();
+        let keys = doc! { DB_FIELDS::ASSET: 1, DB_FIELDS::CONDITION_ID: 1 };
+        create_index(database, &collection, keys, options).await?;
+    }
+
+    // Ledger lookups: by copied trade, and fills by time for PnL
+    let by_source = doc! { DB_FIELDS::TRADER_ADDRESS: 1, DB_FIELDS::SOURCE_TRANSACTION_HASH: 1 };
+    for collection in [DB_CONSTANTS::MY_ORDERS_COLLECTION, DB_CONSTANTS::MY_FILLS_COLLECTION] {
+        create_index(database, collection, by_source.clone(), IndexOptions::default()).await?;
+    }
+    let by_time = doc! { DB_FIELDS::FILLED_AT: 1 };
+    create_index(database, DB_CONSTANTS::MY_FILLS_COLLECTION, by_time, IndexOptions::default()).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reads_database_and_pool_options_from_uri() {
+        let mut options = ClientOptions::parse("mongodb://localhost:27017/copybot?maxPoolSize=5&appName=custom")
+            .await
+            .unwrap();
+        apply_defaults(&mut options);
+        assert_eq!(database_name(&options), "copybot");
+        assert_eq!(options.max_pool_size, Some(5));
+        assert_eq!(options.app_name.as_deref(), Some("custom"));
+
+        let mut options = ClientOptions::parse("mongodb://localhost:27017/").await.unwrap();
+        apply_defaults(&mut options);
+        assert_eq!(database_name(&options), DB_CONSTANTS::DEFAULT_DATABASE);
+        assert_eq!(options.max_pool_size, Some(DB_CONSTANTS::MAX_POOL_SIZE));
+    }
+
+    #[test]
+    fn indexes_existing_and_tracked_collections_once() {
+        let existing = vec![
+            "user_activities_0xaaa".to_string(),
+            "user_positions_0xaaa".to_string(),
+            "paper_wallet".to_string(),
+        ];
+        let addresses = vec!["0xaaa".to_string(), "0xbbb".to_string()];
+        let collections = collections_to_index(&existing, &addresses, DB_CONSTANTS::ACTIVITY_COLLECTION_PREFIX);
+        assert_eq!(
+            collections.into_iter().collect::<Vec<_>>(),
+            vec!["user_activities_0xaaa".to_string(), "user_activities_0xbbb".to_string()]
+        );
+    }
+}
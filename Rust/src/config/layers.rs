@@ -0,0 +1,383 @@
+// Layered configuration sources
+// bot.toml holds the full typed schema; environment variables and CLI flags override it key by key
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+use serde::Deserialize;
+use toml::Spanned;
+use crate::config::trader_config::TraderOverrides;
+use crate::utils::errors::AppError;
+
+/// Config file read when neither `--config` nor BOT_CONFIG names one
+pub const DEFAULT_CONFIG_FILE: &str = "bot.toml";
+
+/// Where the effective value of a setting comes from
+#[derive(Debug, Clone, PartialEq)]
+pub enum Origin {
+    /// `field` (`section.name`) on `line` of `path`
+    File { path: String, line: usize, field: String },
+    Env,
+    Cli,
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Origin::File { path, line, field } => write!(f, "{}:{} ({})", path, line, field),
+            Origin::Env => write!(f, "environment"),
+            Origin::Cli => write!(f, "command line"),
+        }
+    }
+}
+
+/// Declares a bot.toml section: each field is optional, keeps its position in the file and maps
+/// to the environment variable of the same setting
+macro_rules! config_section {
+    ($name:ident { $($field:ident: $ty:ty => $key:literal,)* }) => {
+        #[derive(Debug, Default, Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct $name {
+            $($field: Option<Spanned<$ty>>,)*
+        }
+
+        impl $name {
+            const KEYS: &'static [(&'static str, &'static str)] = &[$((stringify!($field), $key),)*];
+
+            /// `(environment key, field name, value, byte offset)` of every field that is set
+            fn values(&self) -> Vec<(&'static str, &'static str, String, usize)> {
+                let mut values = Vec::new();
+                $(
+                    if let Some(value) = &self.$field {
+                        values.push(($key, stringify!($field), value.get_ref().to_string(), value.span().start));
+                    }
+                )*
+                values
+            }
+        }
+    };
+}
+
+config_section!(WalletSection {
+    proxy_wallet: String => "PROXY_WALLET",
+    private_key: String => "PRIVATE_KEY",
+});
+
+config_section!(EndpointsSection {
+    clob_http_url: String => "CLOB_HTTP_URL",
+    clob_ws_url: String => "CLOB_WS_URL",
+    rpc_url: String => "RPC_URL",
+    usdc_contract_address: String => "USDC_CONTRACT_ADDRESS",
+});
+
+config_section!(MonitorSection {
+    fetch_interval: u64 => "FETCH_INTERVAL",
+    too_old_timestamp: i64 => "TOO_OLD_TIMESTAMP",
+    clob_ws_enabled: bool => "CLOB_WS_ENABLED",
+    onchain_monitor_enabled: bool => "ONCHAIN_MONITOR_ENABLED",
+    onchain_confirmations: u64 => "ONCHAIN_CONFIRMATIONS",
+});
+
+config_section!(StorageSection {
+    backend: String => "STORAGE_BACKEND",
+    mongo_uri: String => "MONGO_URI",
+    sqlite_path: String => "SQLITE_PATH",
+});
+
+config_section!(StrategySection {
+    strategy: String => "COPY_STRATEGY",
+    copy_size: f64 => "COPY_SIZE",
+    max_order_size_usd: f64 => "MAX_ORDER_SIZE_USD",
+    min_order_size_usd: f64 => "MIN_ORDER_SIZE_USD",
+    adaptive_min_percent: f64 => "ADAPTIVE_MIN_PERCENT",
+    adaptive_max_percent: f64 => "ADAPTIVE_MAX_PERCENT",
+    adaptive_threshold_usd: f64 => "ADAPTIVE_THRESHOLD_USD",
+    tiered_multipliers: String => "TIERED_MULTIPLIERS",
+    trade_multiplier: f64 => "TRADE_MULTIPLIER",
+});
+
+config_section!(RiskSection {
+    max_market_exposure_usd: f64 => "MAX_MARKET_EXPOSURE_USD",
+    max_event_exposure_usd: f64 => "MAX_EVENT_EXPOSURE_USD",
+    max_trader_exposure_usd: f64 => "MAX_TRADER_EXPOSURE_USD",
+    max_portfolio_exposure_percent: f64 => "MAX_PORTFOLIO_EXPOSURE_PERCENT",
+    max_daily_loss_usd: f64 => "MAX_DAILY_LOSS_USD",
+    max_drawdown_percent: f64 => "MAX_DRAWDOWN_PERCENT",
+    flatten_on_halt: bool => "FLATTEN_ON_HALT",
+    kill_switch_file: String => "KILL_SWITCH_FILE",
+});
+
+config_section!(ExitsSection {
+    stop_loss_percent: f64 => "STOP_LOSS_PERCENT",
+    take_profit_percent: f64 => "TAKE_PROFIT_PERCENT",
+    trailing_stop_percent: f64 => "TRAILING_STOP_PERCENT",
+});
+
+config_section!(ExecutionSection {
+    retry_limit: u32 => "RETRY_LIMIT",
+    request_timeout_ms: u64 => "REQUEST_TIMEOUT_MS",
+    network_retry_limit: u32 => "NETWORK_RETRY_LIMIT",
+    trade_aggregation_enabled: bool => "TRADE_AGGREGATION_ENABLED",
+    trade_aggregation_window_seconds: u64 => "TRADE_AGGREGATION_WINDOW_SECONDS",
+    clob_creds_file: String => "CLOB_CREDS_FILE",
+    max_price_drift: String => "MAX_PRICE_DRIFT",
+    price_drift_action: String => "PRICE_DRIFT_ACTION",
+    price_drift_limit_expiry_seconds: u64 => "PRICE_DRIFT_LIMIT_EXPIRY_SECONDS",
+    max_slippage_bps: f64 => "MAX_SLIPPAGE_BPS",
+});
+
+config_section!(PaperSection {
+    enabled: bool => "DRY_RUN",
+    starting_balance: f64 => "PAPER_STARTING_BALANCE",
+});
+
+/// bot.toml
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BotConfigFile {
+    #[serde(default)]
+    wallet: WalletSection,
+    #[serde(default)]
+    endpoints: EndpointsSection,
+    #[serde(default)]
+    monitor: MonitorSection,
+    #[serde(default)]
+    storage: StorageSection,
+    #[serde(default)]
+    strategy: StrategySection,
+    #[serde(default)]
+    risk: RiskSection,
+    #[serde(default)]
+    exits: ExitsSection,
+    #[serde(default)]
+    execution: ExecutionSection,
+    #[serde(default)]
+    paper: PaperSection,
+    /// Followed traders, each with optional overrides of `[strategy]` and `[exits]`
+    #[serde(default)]
+    traders: BTreeMap<String, Spanned<TraderOverrides>>,
+}
+
+/// Every `(section, field, environment key)` bot.toml knows
+fn file_keys() -> impl Iterator<Item = (&'static str, &'static str, &'static str)> {
+    let sections: [(&'static str, &'static [(&'static str, &'static str)]); 9] = [
+        ("wallet", WalletSection::KEYS),
+        ("endpoints", EndpointsSection::KEYS),
+        ("monitor", MonitorSection::KEYS),
+        ("storage", StorageSection::KEYS),
+        ("strategy", StrategySection::KEYS),
+        ("risk", RiskSection::KEYS),
+        ("exits", ExitsSection::KEYS),
+        ("execution", ExecutionSection::KEYS),
+        ("paper", PaperSection::KEYS),
+    ];
+    sections
+        .into_iter()
+        .flat_map(|(section, keys)| keys.iter().map(move |(field, key)| (section, *field, *key)))
+}
+
+/// Environment key of a setting given as `KEY` or `section.field`
+fn resolve_key(name: &str) -> Option<&'static str> {
+    file_keys()
+        .find(|(section, field, key)| *key == name || format!("{}.{}", section, field) == name)
+        .map(|(_, _, key)| key)
+}
+
+/// 1-based line of a byte offset
+fn line_of(contents: &str, offset: usize) -> usize {
+    contents[..offset.min(contents.len())].matches('\n').count() + 1
+}
+
+/// A setting as given in bot.toml
+#[derive(Debug, Clone)]
+struct FileValue {
+    value: String,
+    origin: Origin,
+}
+
+/// Trader table from bot.toml
+#[derive(Debug, Clone)]
+pub struct FileTrader {
+    pub address: String,
+    pub origin: Origin,
+    pub overrides: TraderOverrides,
+}
+
+/// Settings from bot.toml, the environment and the command line; later layers win
+#[derive(Debug, Default)]
+pub struct ConfigLayers {
+    file: HashMap<&'static str, FileValue>,
+    cli: HashMap<&'static str, String>,
+    traders: Vec<FileTrader>,
+}
+
+impl ConfigLayers {
+    /// Parse bot.toml contents; `path` is only used in messages
+    pub fn from_toml(path: &str, contents: &str) -> Result<Self, AppError> {
+        let file: BotConfigFile = toml::from_str(contents)
+            .map_err(|e| AppError::ConfigurationError(format!("Invalid {}: {}", path, e)))?;
+
+        let sections = [
+            ("wallet", file.wallet.values()),
+            ("endpoints", file.endpoints.values()),
+            ("monitor", file.monitor.values()),
+            ("storage", file.storage.values()),
+            ("strategy", file.strategy.values()),
+            ("risk", file.risk.values()),
+            ("exits", file.exits.values()),
+            ("execution", file.execution.values()),
+            ("paper", file.paper.values()),
+        ];
+        let origin = |field: String, offset: usize| Origin::File {
+            path: path.to_string(),
+            line: line_of(contents, offset),
+            field,
+        };
+
+        let mut layers = Self::default();
+        for (section, values) in sections {
+            for (key, field, value, offset) in values {
+                let origin = origin(format!("{}.{}", section, field), offset);
+                layers.file.insert(key, FileValue { value, origin });
+            }
+        }
+        for (address, table) in file.traders {
+            layers.traders.push(FileTrader {
+                origin: origin(format!("traders.\"{}\"", address), table.span().start),
+                address: address.trim().to_lowercase(),
+                overrides: table.into_inner(),
+            });
+        }
+        // The trader tables double as USER_ADDRESSES
+        if let Some(first) = layers.traders.first() {
+            let addresses: Vec<&str> = layers.traders.iter().map(|trader| trader.address.as_str()).collect();
+            let value = FileValue { value: addresses.join(","), origin: first.origin.clone() };
+            layers.file.insert("USER_ADDRESSES", value);
+        }
+        Ok(layers)
+    }
+
+    /// Load the config file and command-line overrides
+    ///
+    /// Flags: `--config <path>`, `--set <KEY|section.field>=<value>` (repeatable) and `--paper`.
+    /// Other arguments are left alone. The default bot.toml is optional; a file named explicitly
+    /// through `--config` or BOT_CONFIG must exist.
+    pub fn load(args: &[String]) -> Result<Self, AppError> {
+        let mut config_path = std::env::var("BOT_CONFIG").ok();
+        let mut cli = HashMap::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--config" => {
+                    let path = args.next().ok_or_else(|| {
+                        AppError::ConfigurationError("--config needs a file path".to_string())
+                    })?;
+                    config_path = Some(path.clone());
+                }
+                "--set" => {
+                    let assignment = args.next().map(String::as_str).unwrap_or_default();
+                    let (name, value) = assignment.split_once('=').ok_or_else(|| {
+                        AppError::ConfigurationError(format!("--set expects KEY=VALUE, got \"{}\"", assignment))
+                    })?;
+                    let key = resolve_key(name.trim()).ok_or_else(|| {
+                        AppError::ConfigurationError(format!("--set: unknown setting \"{}\"", name))
+                    })?;
+                    cli.insert(key, value.trim().to_string());
+                }
+                "--paper" => {
+                    cli.insert("DRY_RUN", "true".to_string());
+                }
+                _ => {}
+            }
+        }
+
+        let mut layers = match config_path {
+            Some(path) => Self::read(&path)?,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => Self::read(DEFAULT_CONFIG_FILE)?,
+            None => Self::default(),
+        };
+        layers.cli = cli;
+        Ok(layers)
+    }
+
+    fn read(path: &str) -> Result<Self, AppError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| AppError::ConfigurationError(format!("Cannot read config file {}: {}", path, e)))?;
+        Self::from_toml(path, &contents)
+    }
+
+    /// Effective value and origin of a setting: command line, then environment, then file
+    ///
+    /// Empty environment variables (as left by a copied env.example) do not hide the file value.
+    pub fn lookup(&self, key: &str) -> Option<(String, Origin)> {
+        if let Some(value) = self.cli.get(key) {
+            return Some((value.clone(), Origin::Cli));
+        }
+        if let Ok(value) = std::env::var(key).map(|value| value.trim().to_string()) {
+            if !value.is_empty() {
+                return Some((value, Origin::Env));
+            }
+        }
+        self.file.get(key).map(|setting| (setting.value.clone(), setting.origin.clone()))
+    }
+
+    /// Trader tables from the config file, in address order
+    pub fn traders(&self) -> &[FileTrader] {
+        &self.traders
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = r#"
+[storage]
+backend = "sqlite"
+
+[strategy]
+strategy = "ADAPTIVE"
+copy_size = 7.5
+
+[traders."0x2222222222222222222222222222222222222222"]
+
+[traders."0x1111111111111111111111111111111111111111"]
+copy_size = 1.0
+"#;
+
+    #[test]
+    fn file_settings_map_to_environment_keys_with_their_line() {
+        let layers = ConfigLayers::from_toml("bot.toml", FILE).unwrap();
+        let (value, origin) = layers.lookup("COPY_SIZE").unwrap();
+        assert_eq!(value, "7.5");
+        assert_eq!(origin.to_string(), "bot.toml:7 (strategy.copy_size)");
+        assert_eq!(layers.lookup("STORAGE_BACKEND").unwrap().0, "sqlite");
+
+        let (addresses, origin) = layers.lookup("USER_ADDRESSES").unwrap();
+        assert_eq!(
+            addresses,
+            "0x1111111111111111111111111111111111111111,0x2222222222222222222222222222222222222222"
+        );
+        // Trader tables point at their header
+        assert!(matches!(origin, Origin::File { line: 11, .. }));
+        assert_eq!(layers.traders()[0].overrides.copy_size, Some(1.0));
+    }
+
+    #[test]
+    fn command_line_overrides_the_file() {
+        let mut layers = ConfigLayers::from_toml("bot.toml", FILE).unwrap();
+        layers.cli.insert(resolve_key("strategy.copy_size").unwrap(), "3".to_string());
+        layers.cli.insert(resolve_key("COPY_STRATEGY").unwrap(), "FIXED".to_string());
+        assert_eq!(layers.lookup("COPY_SIZE"), Some(("3".to_string(), Origin::Cli)));
+        assert_eq!(layers.lookup("COPY_STRATEGY").unwrap().0, "FIXED");
+        assert_eq!(resolve_key("strategy.unknown"), None);
+    }
+
+    #[test]
+    fn unknown_fields_and_wrong_types_point_at_the_line() {
+        for contents in ["[strategy]\ncopy_sise = 5", "[monitor]\nfetch_interval = \"fast\"", "[risky]\nx = 1"] {
+            let error = ConfigLayers::from_toml("bot.toml", contents).unwrap_err().to_string();
+            assert!(error.contains("line"), "{}", error);
+        }
+    }
+}
@@ -1,3 +1,5 @@
 pub mod copy_strategy;
 pub mod db;
 pub mod env;
+pub mod layers;
+pub mod trader_config;
@@ -0,0 +1,334 @@
+// Per-trader copy configuration
+// Overrides of the global copy strategy, loaded from TRADER_CONFIG_FILE and keyed by trader address
+
+use std::collections::HashMap;
+use std::path::Path;
+use serde::Deserialize;
+use crate::config::copy_strategy::{parse_tiered_multipliers, CopyStrategy, CopyStrategyConfig};
+use crate::interfaces::user::UserActivityInterface;
+use crate::services::exit_manager::ExitThresholds;
+use crate::utils::constants::DB_FIELDS;
+use crate::utils::errors::AppError;
+
+/// Overrides for one trader; unset fields fall back to the global configuration
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TraderOverrides {
+    pub strategy: Option<CopyStrategy>,
+    pub copy_size: Option<f64>,
+    pub max_order_size_usd: Option<f64>,
+    pub min_order_size_usd: Option<f64>,
+    pub adaptive_min_percent: Option<f64>,
+    pub adaptive_max_percent: Option<f64>,
+    pub adaptive_threshold_usd: Option<f64>,
+    /// Same format as TIERED_MULTIPLIERS
+    pub tiered_multipliers: Option<String>,
+    /// Replaces the global tiers too, unless `tiered_multipliers` is also set
+    pub trade_multiplier: Option<f64>,
+    /// Sides to copy (BUY, SELL)
+    pub sides: Option<Vec<String>>,
+    /// Market slugs, event slugs or condition IDs to copy
+    pub markets: Option<Vec<String>>,
+    pub stop_loss_percent: Option<f64>,
+    pub take_profit_percent: Option<f64>,
+    pub trailing_stop_percent: Option<f64>,
+}
+
+/// Layout of the trader config file: one `[traders."0x…"]` table per trader
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TraderConfigFile {
+    #[serde(default)]
+    traders: HashMap<String, TraderOverrides>,
+}
+
+/// Effective copy configuration of one trader
+#[derive(Debug, Clone)]
+pub struct TraderConfig {
+    pub copy_strategy: CopyStrategyConfig,
+    /// Sides copied; None copies both
+    pub sides: Option<Vec<String>>,
+    /// Markets copied (slug, event slug or condition ID); None copies every market
+    pub markets: Option<Vec<String>>,
+    /// Exits of the positions copied from this trader
+    pub exits: ExitThresholds,
+}
+
+fn positive(value: f64) -> bool {
+    value.is_finite() && value > 0.0
+}
+
+impl TraderConfig {
+    fn global(copy_strategy: CopyStrategyConfig, exits: ExitThresholds) -> Self {
+        Self { copy_strategy, sides: None, markets: None, exits }
+    }
+
+    /// Apply `overrides` on top of the global configuration
+    fn resolve(global: &TraderConfig, overrides: &TraderOverrides) -> Result<Self, String> {
+        let mut config = global.copy_strategy.clone();
+        if let Some(strategy) = overrides.strategy {
+            config.strategy = strategy;
+        }
+        if let Some(copy_size) = overrides.copy_size {
+            if !positive(copy_size) {
+                return Err(format!("copy_size must be positive, got {}", copy_size));
+            }
+            config.copy_size = copy_size;
+        }
+        if let Some(max) = overrides.max_order_size_usd {
+            config.max_order_size_usd = max;
+        }
+        if let Some(min) = overrides.min_order_size_usd {
+            config.min_order_size_usd = min;
+        }
+        if config.min_order_size_usd < 0.0 || config.max_order_size_usd < config.min_order_size_usd {
+            return Err(format!(
+                "order size limits must satisfy 0 <= min ({}) <= max ({})",
+                config.min_order_size_usd, config.max_order_size_usd
+            ));
+        }
+
+        for (name, percent) in [
+            ("adaptive_min_percent", overrides.adaptive_min_percent),
+            ("adaptive_max_percent", overrides.adaptive_max_percent),
+        ] {
+            if percent.is_some_and(|percent| !positive(percent) || percent > 100.0) {
+                return Err(format!("{} must be between 0 and 100", name));
+            }
+        }
+        config.adaptive_min_percent = overrides.adaptive_min_percent.or(config.adaptive_min_percent);
+        config.adaptive_max_percent = overrides.adaptive_max_percent.or(config.adaptive_max_percent);
+        if let (Some(min), Some(max)) = (config.adaptive_min_percent, config.adaptive_max_percent) {
+            if min > max {
+                return Err(format!("adaptive_min_percent ({}) cannot exceed adaptive_max_percent ({})", min, max));
+            }
+        }
+        if let Some(threshold) = overrides.adaptive_threshold_usd {
+            if !positive(threshold) {
+                return Err(format!("adaptive_threshold_usd must be positive, got {}", threshold));
+            }
+            config.adaptive_threshold = Some(threshold);
+        }
+
+        if let Some(multiplier) = overrides.trade_multiplier {
+            if !positive(multiplier) {
+                return Err(format!("trade_multiplier must be positive, got {}", multiplier));
+            }
+            config.trade_multiplier = Some(multiplier).filter(|m| *m != 1.0);
+            config.tiered_multipliers.clear();
+        }
+        if let Some(spec) = &overrides.tiered_multipliers {
+            config.tiered_multipliers = parse_tiered_multipliers(spec).map_err(|e| e.to_string())?;
+        }
+
+        let sides = match &overrides.sides {
+            Some(sides) => {
+                let sides: Vec<String> = sides.iter().map(|side| side.trim().to_uppercase()).collect();
+                let known = [DB_FIELDS::SIDE_BUY, DB_FIELDS::SIDE_SELL];
+                if let Some(side) = sides.iter().find(|side| !known.contains(&side.as_str())) {
+                    return Err(format!("unknown side {} (use BUY or SELL)", side));
+                }
+                Some(sides)
+            }
+            None => None,
+        };
+        let markets = overrides
+            .markets
+            .as_ref()
+            .map(|markets| markets.iter().map(|market| market.trim().to_lowercase()).collect());
+
+        for (name, percent) in [
+            ("stop_loss_percent", overrides.stop_loss_percent),
+            ("take_profit_percent", overrides.take_profit_percent),
+            ("trailing_stop_percent", overrides.trailing_stop_percent),
+        ] {
+            if percent.is_some_and(|percent| !positive(percent)) {
+                return Err(format!("{} must be positive", name));
+            }
+        }
+        for (name, percent) in [
+            ("stop_loss_percent", overrides.stop_loss_percent),
+            ("trailing_stop_percent", overrides.trailing_stop_percent),
+        ] {
+            if percent.is_some_and(|percent| percent >= 100.0) {
+                return Err(format!("{} must be below 100", name));
+            }
+        }
+        let exits = ExitThresholds {
+            stop_loss_percent: overrides.stop_loss_percent.or(global.exits.stop_loss_percent),
+            take_profit_percent: overrides.take_profit_percent.or(global.exits.take_profit_percent),
+            trailing_stop_percent: overrides.trailing_stop_percent.or(global.exits.trailing_stop_percent),
+        };
+
+        Ok(Self { copy_strategy: config, sides, markets, exits })
+    }
+
+    /// Why `trade` is not copied for this trader, if its side or market is filtered out
+    pub fn rejection(&self, trade: &UserActivityInterface) -> Option<String> {
+        if let Some(sides) = &self.sides {
+            if !trade.side.is_empty() && !sides.iter().any(|side| side.eq_ignore_ascii_case(&trade.side)) {
+                return Some(format!("{} trades are not copied for this trader", trade.side));
+            }
+        }
+        if let Some(markets) = &self.markets {
+            let allowed = [&trade.slug, &trade.event_slug, &trade.condition_id]
+                .iter()
+                .any(|id| !id.is_empty() && markets.contains(&id.to_lowercase()));
+            if !allowed {
+                return Some(format!("market {} is not copied for this trader", trade.slug));
+            }
+        }
+        None
+    }
+}
+
+/// `[traders."0x…"]` tables of a trader config file
+pub fn parse_trader_overrides(contents: &str) -> Result<Vec<(String, TraderOverrides)>, AppError> {
+    let file: TraderConfigFile = toml::from_str(contents)
+        .map_err(|e| AppError::ConfigurationError(format!("Invalid trader config: {}", e)))?;
+    Ok(file.traders.into_iter().collect())
+}
+
+/// Trader overrides from `path`; a missing file means none unless `required`
+pub fn load_trader_overrides(path: &str, required: bool) -> Result<Vec<(String, TraderOverrides)>, AppError> {
+    if !required && !Path::new(path).exists() {
+        return Ok(Vec::new());
+    }
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| AppError::ConfigurationError(format!("Cannot read trader config {}: {}", path, e)))?;
+    parse_trader_overrides(&contents)
+}
+
+/// Copy configuration of every followed trader
+#[derive(Debug, Clone)]
+pub struct TraderConfigs {
+    global: TraderConfig,
+    /// Keyed by lowercase trader address
+    traders: HashMap<String, TraderConfig>,
+}
+
+impl TraderConfigs {
+    /// Every trader copied with the global configuration
+    pub fn new(global: CopyStrategyConfig, exits: ExitThresholds) -> Self {
+        Self { global: TraderConfig::global(global, exits), traders: HashMap::new() }
+    }
+
+    /// Apply a trader's overrides on top of the global configuration
+    pub fn insert(&mut self, address: &str, overrides: &TraderOverrides) -> Result<(), String> {
+        let config = TraderConfig::resolve(&self.global, overrides)?;
+        self.traders.insert(address.trim().to_lowercase(), config);
+        Ok(())
+    }
+
+    /// Overrides from a TOML document with `[traders."0x…"]` tables
+    pub fn from_toml(global: CopyStrategyConfig, exits: ExitThresholds, contents: &str) -> Result<Self, AppError> {
+        let mut configs = Self::new(global, exits);
+        for (address, overrides) in parse_trader_overrides(contents)? {
+            configs
+                .insert(&address, &overrides)
+                .map_err(|e| AppError::ConfigurationError(format!("Invalid trader config for {}: {}", address, e)))?;
+        }
+        Ok(configs)
+    }
+
+    /// Whether the trader has its own configuration
+    pub fn contains(&self, address: &str) -> bool {
+        self.traders.contains_key(&address.to_lowercase())
+    }
+
+    /// Effective configuration for a trader, the global one unless overridden
+    pub fn for_trader(&self, address: &str) -> &TraderConfig {
+        self.traders.get(&address.to_lowercase()).unwrap_or(&self.global)
+    }
+
+    /// Whether any trader's positions have exit thresholds
+    pub fn has_exits(&self) -> bool {
+        self.global.exits.is_enabled() || self.traders.values().any(|config| config.exits.is_enabled())
+    }
+
+    /// Addresses with their own configuration
+    pub fn overridden(&self) -> impl Iterator<Item = &String> {
+        self.traders.keys()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHALE: &str = "0x1111111111111111111111111111111111111111";
+    const SHARP: &str = "0x2222222222222222222222222222222222222222";
+
+    fn global() -> CopyStrategyConfig {
+        CopyStrategyConfig {
+            tiered_multipliers: parse_tiered_multipliers("0-100:2.0,100+:1.0").unwrap(),
+            ..Default::default()
+        }
+    }
+
+    fn trade(side: &str, slug: &str) -> UserActivityInterface {
+        serde_json::from_value(serde_json::json!({ "timestamp": 1, "side": side, "slug": slug, "conditionId": "0xc" })).unwrap()
+    }
+
+    #[test]
+    fn overrides_fall_back_to_the_global_config() {
+        let exits = ExitThresholds { stop_loss_percent: Some(30.0), ..Default::default() };
+        let configs = TraderConfigs::from_toml(
+            global(),
+            exits,
+            &format!(
+                r#"
+                [traders."{}"]
+                copy_size = 1.0
+                max_order_size_usd = 500
+                trade_multiplier = 0.5
+
+                [traders."{}"]
+                strategy = "FIXED"
+                copy_size = 5.0
+                sides = ["buy"]
+                markets = ["Will-It-Rain"]
+                stop_loss_percent = 15
+                take_profit_percent = 40
+                "#,
+                WHALE.to_uppercase().replace("0X", "0x"),
+                SHARP
+            ),
+        )
+        .unwrap();
+
+        let whale = &configs.for_trader(WHALE).copy_strategy;
+        assert_eq!((whale.strategy, whale.copy_size, whale.max_order_size_usd), (CopyStrategy::PERCENTAGE, 1.0, 500.0));
+        assert_eq!((whale.trade_multiplier, whale.tiered_multipliers.len()), (Some(0.5), 0));
+
+        let sharp = configs.for_trader(SHARP);
+        assert_eq!((sharp.copy_strategy.strategy, sharp.copy_strategy.min_order_size_usd), (CopyStrategy::FIXED, 1.0));
+        assert_eq!(sharp.copy_strategy.tiered_multipliers.len(), 2);
+        assert_eq!(sharp.rejection(&trade("BUY", "will-it-rain")), None);
+        assert!(sharp.rejection(&trade("SELL", "will-it-rain")).is_some());
+        assert!(sharp.rejection(&trade("BUY", "other-market")).is_some());
+        assert_eq!((sharp.exits.stop_loss_percent, sharp.exits.take_profit_percent), (Some(15.0), Some(40.0)));
+        assert_eq!(configs.for_trader(WHALE).exits, exits);
+        assert!(configs.has_exits());
+
+        let unknown = configs.for_trader("0x3333333333333333333333333333333333333333");
+        assert_eq!(unknown.copy_strategy.copy_size, 10.0);
+        assert_eq!(unknown.rejection(&trade("SELL", "anything")), None);
+    }
+
+    #[test]
+    fn invalid_overrides_are_rejected() {
+        for table in [
+            "copy_size = 0",
+            "min_order_size_usd = 200",
+            "sides = [\"HOLD\"]",
+            "tiered_multipliers = \"10-1:2\"",
+            "strategy = \"MARTINGALE\"",
+            "copy_sise = 5",
+            "trailing_stop_percent = 100",
+        ] {
+            let contents = format!("[traders.\"{}\"]\n{}", WHALE, table);
+            assert!(TraderConfigs::from_toml(global(), ExitThresholds::default(), &contents).is_err(), "{} should be rejected", table);
+        }
+    }
+}
@@ -0,0 +1,108 @@
+// Execution state of a journaled trade
+// Every trade moves Pending → Claimed → Submitted → one of the final statuses
+
+use serde::{Deserialize, Serialize};
+
+/// Where the executor is with a followed trader's trade
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ExecutionStatus {
+    /// Journaled, not picked up yet
+    #[default]
+    Pending,
+    /// Picked up by the executor, no order accepted yet
+    Claimed,
+    /// At least one order was accepted by the CLOB
+    Submitted,
+    /// Done with only part of the copy filled
+    PartiallyFilled,
+    /// Done with the whole copy filled
+    Filled,
+    /// Not copied on purpose
+    Skipped { reason: String },
+    /// Not copied because orders failed
+    Failed { error: String },
+}
+
+impl ExecutionStatus {
+    pub const PENDING: &'static str = "PENDING";
+    pub const CLAIMED: &'static str = "CLAIMED";
+    pub const SUBMITTED: &'static str = "SUBMITTED";
+    pub const PARTIALLY_FILLED: &'static str = "PARTIALLY_FILLED";
+    pub const FILLED: &'static str = "FILLED";
+    pub const SKIPPED: &'static str = "SKIPPED";
+    pub const FAILED: &'static str = "FAILED";
+
+    /// Name stored in the `status` field
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => Self::PENDING,
+            Self::Claimed => Self::CLAIMED,
+            Self::Submitted => Self::SUBMITTED,
+            Self::PartiallyFilled => Self::PARTIALLY_FILLED,
+            Self::Filled => Self::FILLED,
+            Self::Skipped { .. } => Self::SKIPPED,
+            Self::Failed { .. } => Self::FAILED,
+        }
+    }
+
+    /// The executor is done with the trade
+    pub fn is_final(&self) -> bool {
+        !matches!(self, Self::Pending | Self::Claimed | Self::Submitted)
+    }
+
+    /// Picked up but not finished; a crash in this state needs recovery
+    pub fn is_in_flight(&self) -> bool {
+        matches!(self, Self::Claimed | Self::Submitted)
+    }
+}
+
+/// Execution status plus the bookkeeping needed to reconcile it after a crash
+///
+/// Timestamps are unix milliseconds.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionState {
+    #[serde(flatten)]
+    pub status: ExecutionStatus,
+    /// Orders posted for this trade, rejected ones included
+    #[serde(default)]
+    pub attempts: u32,
+    /// Orders accepted by the CLOB
+    #[serde(rename = "orderIds", default)]
+    pub order_ids: Vec<String>,
+    #[serde(rename = "updatedAt", default)]
+    pub updated_at: i64,
+    #[serde(rename = "claimedAt", default, skip_serializing_if = "Option::is_none")]
+    pub claimed_at: Option<i64>,
+    #[serde(rename = "submittedAt", default, skip_serializing_if = "Option::is_none")]
+    pub submitted_at: Option<i64>,
+    #[serde(rename = "completedAt", default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<i64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_is_stored_flat_next_to_its_details() {
+        let state = ExecutionState {
+            status: ExecutionStatus::Skipped { reason: "not a trade".to_string() },
+            attempts: 2,
+            updated_at: 1_000,
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&state).unwrap();
+        assert_eq!(json["status"], "SKIPPED");
+        assert_eq!(json["reason"], "not a trade");
+        assert!(json.get("claimedAt").is_none());
+
+        let document = mongodb::bson::to_document(&state).unwrap();
+        assert_eq!(document.get_str("status"), Ok(state.status.as_str()));
+        assert_eq!(mongodb::bson::from_document::<ExecutionState>(document).unwrap(), state);
+
+        for status in [ExecutionStatus::PartiallyFilled, ExecutionStatus::Failed { error: "x".to_string() }] {
+            assert_eq!(serde_json::to_value(&status).unwrap()["status"], status.as_str());
+        }
+    }
+}
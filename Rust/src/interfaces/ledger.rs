@@ -0,0 +1,86 @@
+// Own-order and fill ledger
+// Every order we post and every fill it gets, linked back to the trader activity that caused it
+
+use serde::{Deserialize, Serialize};
+
+/// An order we posted to copy a trader's trade, accepted or not
+///
+/// Timestamps are unix milliseconds.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderRecord {
+    /// CLOB order ID (`paper-…` when paper trading); empty if the order was rejected
+    #[serde(default)]
+    pub order_id: String,
+    /// Followed trader whose activity was copied
+    pub trader_address: String,
+    /// `transactionHash` of the copied activity
+    pub source_transaction_hash: String,
+    /// When the trader's trade happened (unix seconds, as reported by the data API)
+    #[serde(default)]
+    pub source_timestamp: i64,
+    pub asset: String,
+    #[serde(default)]
+    pub condition_id: String,
+    #[serde(default)]
+    pub slug: String,
+    /// BUY or SELL
+    pub side: String,
+    /// FOK for market orders, GTD for the limit orders posted for drifted copies
+    pub order_type: String,
+    /// Worst acceptable price
+    pub price: f64,
+    /// USDC to spend (BUY) or tokens to sell (SELL)
+    pub amount: f64,
+    pub fee_rate_bps: f64,
+    pub accepted: bool,
+    /// CLOB status of an accepted order (e.g. `matched`)
+    #[serde(default)]
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub paper: bool,
+    /// Set on exits the bot placed on its own (e.g. `KILL_SWITCH`) rather than to copy a trade
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trigger: Option<String>,
+    pub submitted_at: i64,
+    pub responded_at: i64,
+    /// From the trader's trade to our submission
+    pub latency_ms: i64,
+}
+
+/// Tokens matched by one of our orders
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FillRecord {
+    pub order_id: String,
+    pub trader_address: String,
+    pub source_transaction_hash: String,
+    pub asset: String,
+    #[serde(default)]
+    pub condition_id: String,
+    /// Event of the market; all outcomes of an event share it
+    #[serde(default)]
+    pub event_slug: String,
+    pub side: String,
+    /// Average fill price (USDC per token)
+    pub price: f64,
+    /// Tokens bought or sold
+    pub size: f64,
+    /// USDC paid (BUY) or received (SELL), before fees
+    pub usdc: f64,
+    /// Fee in USDC
+    pub fee: f64,
+    /// Settlement transactions reported by the CLOB
+    #[serde(default)]
+    pub transaction_hashes: Vec<String>,
+    #[serde(default)]
+    pub paper: bool,
+    /// Same as the order's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trigger: Option<String>,
+    pub filled_at: i64,
+    /// From the trader's trade to our fill
+    pub latency_ms: i64,
+}
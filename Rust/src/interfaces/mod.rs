@@ -1 +1,3 @@
+pub mod execution;
+pub mod ledger;
 pub mod user;
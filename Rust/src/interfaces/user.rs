@@ -2,6 +2,7 @@
 
 use mongodb::bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
+use crate::interfaces::execution::ExecutionState;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(dead_code)] // Reserved for future use
@@ -17,7 +18,7 @@ pub type TradeSide = String; // "BUY" | "SELL"
 pub type ActivityType = String; // "TRADE" | "REDEEM" | "MERGE"
 
 /// User activity interface representing a trade or activity
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct UserActivityInterface {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
     pub _id: Option<ObjectId>,
@@ -62,10 +63,9 @@ pub struct UserActivityInterface {
     pub profile_image: String,
     #[serde(rename = "profileImageOptimized", default)]
     pub profile_image_optimized: String,
+    /// Copy progress; absent on data API responses, which start out pending
     #[serde(default)]
-    pub bot: bool,
-    #[serde(rename = "botExcutedTime", default)]
-    pub bot_excuted_time: i64,
+    pub execution: ExecutionState,
     /// Tracks actual tokens we bought for this trade
     #[serde(rename = "myBoughtSize", default)]
     pub my_bought_size: Option<f64>,
@@ -130,20 +130,46 @@ pub struct UserPositionInterface {
 
 /// Order book entry interface
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[allow(dead_code)] // Reserved for CLOB client implementation
 pub struct OrderBookEntry {
     pub price: String,
     pub size: String,
 }
 
+impl OrderBookEntry {
+    pub fn price(&self) -> f64 {
+        self.price.parse().unwrap_or(0.0)
+    }
+
+    pub fn size(&self) -> f64 {
+        self.size.parse().unwrap_or(0.0)
+    }
+}
+
 /// Order book interface
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[allow(dead_code)] // Reserved for CLOB client implementation
 pub struct OrderBook {
+    #[serde(default)]
     pub bids: Vec<OrderBookEntry>,
+    #[serde(default)]
     pub asks: Vec<OrderBookEntry>,
 }
 
+impl OrderBook {
+    /// Lowest ask (the API does not guarantee level ordering)
+    pub fn best_ask(&self) -> Option<&OrderBookEntry> {
+        self.asks
+            .iter()
+            .min_by(|a, b| a.price().partial_cmp(&b.price()).unwrap_or(std::cmp::Ordering::Equal))
+    }
+
+    /// Highest bid (the API does not guarantee level ordering)
+    pub fn best_bid(&self) -> Option<&OrderBookEntry> {
+        self.bids
+            .iter()
+            .max_by(|a, b| a.price().partial_cmp(&b.price()).unwrap_or(std::cmp::Ordering::Equal))
+    }
+}
+
 /// Position summary for display
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(dead_code)] // Reserved for future display features
@@ -155,3 +181,21 @@ pub struct PositionSummary {
     pub avg_price: f64,
     pub cur_price: f64,
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) fn level(price: &str, size: &str) -> OrderBookEntry {
+        OrderBookEntry { price: price.to_string(), size: size.to_string() }
+    }
+
+    /// Order book shared by the fill and slippage tests
+    pub(crate) fn book() -> OrderBook {
+        // Unsorted on purpose, like the API may return it
+        OrderBook {
+            bids: vec![level("0.48", "100"), level("0.50", "10"), level("0.45", "1000")],
+            asks: vec![level("0.55", "100"), level("0.52", "10"), level("0.60", "1000")],
+        }
+    }
+}
@@ -0,0 +1,10 @@
+// Polymarket Copy Trading Bot - Rust Version
+// Shared library behind the bot and the offline tools in src/bin
+
+pub mod backtest;
+pub mod config;
+pub mod interfaces;
+pub mod models;
+pub mod services;
+pub mod storage;
+pub mod utils;
@@ -4,17 +4,14 @@
 use std::process;
 use tokio::sync::RwLock;
 
-mod config;
-mod interfaces;
-mod models;
-mod services;
-mod utils;
+use polymarket_copy_trading_bot::{config, services, storage, utils};
 
-use config::db::connect_db;
 use config::env::{load_env, ENV};
 use services::create_clob_client::create_clob_client;
 use services::trade_executor::trade_executor;
 use services::trade_monitor::trade_monitor;
+use services::trade_pipeline;
+use storage::{close_storage, init_storage};
 use utils::errors::normalize_error;
 use utils::health_check::perform_health_check;
 use utils::logger::Logger;
@@ -47,8 +44,8 @@ async fn graceful_shutdown(signal: &str) {
     Logger::info("Waiting for services to finish current operations...");
     tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
     
-    // Close database connection
-    if let Err(e) = config::db::close_db().await {
+    // Close storage
+    if let Err(e) = close_storage().await {
         Logger::error(&format!("Error closing database: {}", e));
     }
     
@@ -66,10 +63,18 @@ async fn main_async() -> Result<(), Box<dyn std::error::Error>> {
     println!("   Read the guide: GETTING_STARTED.md");
     println!("   Run health check: cargo run --bin health-check\n");
     
-    // Connect to MongoDB
-    connect_db().await?;
+    // Open the trade journal (MongoDB unless STORAGE_BACKEND says otherwise)
+    init_storage().await?;
+    if ENV().dry_run {
+        // The paper wallet is kept in MongoDB even when the journal is not
+        config::db::connect_db().await?;
+    }
     
     Logger::startup(&ENV().user_addresses, &ENV().proxy_wallet);
+    let overridden = ENV().trader_configs.overridden().count();
+    if overridden > 0 {
+        Logger::info(&format!("Per-trader copy configuration loaded for {} trader(s)", overridden));
+    }
     
     // Perform initial health check
     Logger::info("Performing initial health check...");
@@ -80,10 +85,24 @@ async fn main_async() -> Result<(), Box<dyn std::error::Error>> {
         Logger::warning("Health check failed, but continuing startup...");
     }
     
+    if ENV().dry_run {
+        let paper_balance = services::paper_wallet::init_paper_wallet().await?;
+        Logger::warning(&format!(
+            "📝 PAPER TRADING MODE: orders are simulated against the live order book (paper balance: ${:.2})",
+            paper_balance
+        ));
+    }
+    
     Logger::info("Initializing CLOB client...");
     let clob_client = create_clob_client().await?;
     Logger::success("CLOB client ready");
     
+    // A kill switch halt stays in force across restarts until explicitly resumed
+    services::kill_switch::init_kill_switch()?;
+    
+    // Settle trades a previous run left mid-execution before any new trade is copied
+    services::execution_recovery::recover_interrupted_trades(&clob_client).await;
+    
     Logger::separator();
     Logger::info("Starting trade monitor...");
     
@@ -92,16 +111,19 @@ async fn main_async() -> Result<(), Box<dyn std::error::Error>> {
     // We call init() separately to ensure it completes first, matching PythonVersion behavior
     services::trade_monitor::init().await?;
     
+    // Detected trades flow from the monitor straight to the executor; storage only journals them
+    let (trade_sender, trade_receiver) = trade_pipeline::channel();
+    
     // Now start the monitor (it will skip init() since it's already done)
     let monitor_future = async {
-        let _ = trade_monitor().await;
+        let _ = trade_monitor(trade_sender).await;
     };
     
     Logger::info("Starting trade executor...");
     
     // Start trade executor (it will log "ready" after monitor has logged "Monitoring")
     let executor_future = async move {
-        let _ = trade_executor(clob_client).await;
+        let _ = trade_executor(clob_client, trade_receiver).await;
     };
     
     // Wait for both services or shutdown signal
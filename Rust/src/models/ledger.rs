@@ -0,0 +1,17 @@
+// MongoDB models for the own-order and fill ledger
+// Shared by all followed traders; each record carries the trader address and source transaction
+
+use mongodb::bson::Document;
+use mongodb::Collection;
+use crate::config::db::get_database;
+use crate::utils::constants::DB_CONSTANTS;
+
+/// Every order we posted, accepted or rejected
+pub fn get_my_order_collection() -> Collection<Document> {
+    get_database().collection(DB_CONSTANTS::MY_ORDERS_COLLECTION)
+}
+
+/// Every fill of our orders
+pub fn get_my_fill_collection() -> Collection<Document> {
+    get_database().collection(DB_CONSTANTS::MY_FILLS_COLLECTION)
+}
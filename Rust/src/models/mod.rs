@@ -1 +1,3 @@
+pub mod ledger;
+pub mod paper_trading;
 pub mod user_history;
@@ -0,0 +1,22 @@
+// MongoDB models for paper trading
+// Simulated wallet state lives in paper_* collections, apart from the real trading history
+
+use mongodb::bson::Document;
+use mongodb::Collection;
+use crate::config::db::get_database;
+use crate::utils::constants::DB_CONSTANTS;
+
+/// Simulated USDC balance (a single document)
+pub fn get_paper_wallet_collection() -> Collection<Document> {
+    get_database().collection(DB_CONSTANTS::PAPER_WALLET_COLLECTION)
+}
+
+/// Simulated positions, one document per outcome token
+pub fn get_paper_position_collection() -> Collection<Document> {
+    get_database().collection(DB_CONSTANTS::PAPER_POSITIONS_COLLECTION)
+}
+
+/// Every simulated fill, in execution order
+pub fn get_paper_fill_collection() -> Collection<Document> {
+    get_database().collection(DB_CONSTANTS::PAPER_FILLS_COLLECTION)
+}
@@ -4,17 +4,19 @@
 use mongodb::bson::{doc, oid::ObjectId};
 use serde::{Deserialize, Serialize};
 use crate::config::db::get_database;
+use crate::interfaces::execution::ExecutionState;
 use crate::interfaces::user::{UserActivityInterface, UserPositionInterface};
+use crate::utils::constants::DB_CONSTANTS;
 
 /// Get the activity collection for a specific wallet address
 pub fn get_user_activity_collection(wallet_address: &str) -> mongodb::Collection<mongodb::bson::Document> {
-    let collection_name = format!("user_activities_{}", wallet_address);
+    let collection_name = format!("{}{}", DB_CONSTANTS::ACTIVITY_COLLECTION_PREFIX, wallet_address);
     get_database().collection(&collection_name)
 }
 
 /// Get the position collection for a specific wallet address
 pub fn get_user_position_collection(wallet_address: &str) -> mongodb::Collection<mongodb::bson::Document> {
-    let collection_name = format!("user_positions_{}", wallet_address);
+    let collection_name = format!("{}{}", DB_CONSTANTS::POSITION_COLLECTION_PREFIX, wallet_address);
     get_database().collection(&collection_name)
 }
 
@@ -55,9 +57,7 @@ pub struct UserActivity {
     #[serde(rename = "profileImageOptimized")]
     pub profile_image_optimized: Option<String>,
     #[serde(default)]
-    pub bot: bool,
-    #[serde(rename = "botExcutedTime")]
-    pub bot_executed_time: Option<i64>,
+    pub execution: ExecutionState,
     #[serde(rename = "myBoughtSize")]
     pub my_bought_size: Option<f64>, // Tracks actual tokens we bought
 }
@@ -87,8 +87,7 @@ impl From<UserActivityInterface> for UserActivity {
             bio: Some(activity.bio),
             profile_image: Some(activity.profile_image),
             profile_image_optimized: Some(activity.profile_image_optimized),
-            bot: activity.bot,
-            bot_executed_time: Some(activity.bot_excuted_time),
+            execution: activity.execution,
             my_bought_size: activity.my_bought_size,
         }
     }
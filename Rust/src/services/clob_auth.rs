@@ -0,0 +1,273 @@
+// CLOB authentication
+// L1: EIP-712 `ClobAuth` signature proving wallet ownership (used to create/derive API keys)
+// L2: HMAC-SHA256 `POLY_*` headers signed with the API secret (used on every trading request)
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+use base64::alphabet;
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig};
+use base64::engine::DecodePaddingMode;
+use base64::Engine;
+use ethers::abi::{encode, Token};
+use ethers::signers::LocalWallet;
+use ethers::types::{Address, H256, U256};
+use ethers::utils::{keccak256, to_checksum};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use crate::utils::errors::{AppError, AppResult};
+
+/// Message every L1 signature attests to
+const CLOB_AUTH_MESSAGE: &str = "This message attests that I control the given wallet";
+const CLOB_AUTH_DOMAIN_NAME: &str = "ClobAuthDomain";
+const CLOB_AUTH_DOMAIN_VERSION: &str = "1";
+const CLOB_AUTH_TYPE: &str = "ClobAuth(address address,string timestamp,uint256 nonce,string message)";
+const CLOB_AUTH_DOMAIN_TYPE: &str = "EIP712Domain(string name,string version,uint256 chainId)";
+
+pub const POLY_ADDRESS: &str = "POLY_ADDRESS";
+pub const POLY_SIGNATURE: &str = "POLY_SIGNATURE";
+pub const POLY_TIMESTAMP: &str = "POLY_TIMESTAMP";
+pub const POLY_NONCE: &str = "POLY_NONCE";
+pub const POLY_API_KEY: &str = "POLY_API_KEY";
+pub const POLY_PASSPHRASE: &str = "POLY_PASSPHRASE";
+
+/// Secrets are URL-safe base64 and may or may not be padded
+const SECRET_ENGINE: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::URL_SAFE,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// L2 API credentials
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiCreds {
+    #[serde(rename = "apiKey")]
+    pub api_key: String,
+    pub secret: String,
+    pub passphrase: String,
+}
+
+impl ApiCreds {
+    pub fn is_valid(&self) -> bool {
+        !self.api_key.is_empty() && !self.secret.is_empty() && !self.passphrase.is_empty()
+    }
+}
+
+/// Current unix time in seconds, as sent in POLY_TIMESTAMP
+pub fn current_timestamp() -> String {
+    chrono::Utc::now().timestamp().to_string()
+}
+
+/// EIP-712 digest of the `ClobAuth` struct
+pub fn clob_auth_digest(address: Address, timestamp: &str, nonce: u64, chain_id: u64) -> [u8; 32] {
+    let domain_separator = keccak256(encode(&[
+        Token::FixedBytes(keccak256(CLOB_AUTH_DOMAIN_TYPE).to_vec()),
+        Token::FixedBytes(keccak256(CLOB_AUTH_DOMAIN_NAME).to_vec()),
+        Token::FixedBytes(keccak256(CLOB_AUTH_DOMAIN_VERSION).to_vec()),
+        Token::Uint(U256::from(chain_id)),
+    ]));
+    let struct_hash = keccak256(encode(&[
+        Token::FixedBytes(keccak256(CLOB_AUTH_TYPE).to_vec()),
+        Token::Address(address),
+        Token::FixedBytes(keccak256(timestamp).to_vec()),
+        Token::Uint(U256::from(nonce)),
+        Token::FixedBytes(keccak256(CLOB_AUTH_MESSAGE).to_vec()),
+    ]));
+
+    let mut payload = Vec::with_capacity(66);
+    payload.extend_from_slice(&[0x19, 0x01]);
+    payload.extend_from_slice(&domain_separator);
+    payload.extend_from_slice(&struct_hash);
+    keccak256(payload)
+}
+
+/// L1 headers for `/auth/*` endpoints
+pub fn l1_headers(wallet: &LocalWallet, chain_id: u64, nonce: u64, timestamp: &str) -> AppResult<Vec<(&'static str, String)>> {
+    use ethers::signers::Signer;
+
+    let address = wallet.address();
+    let digest = clob_auth_digest(address, timestamp, nonce, chain_id);
+    let signature = wallet
+        .sign_hash(H256::from(digest))
+        .map_err(|e| AppError::TradingError(format!("Failed to sign ClobAuth message: {}", e)))?;
+
+    Ok(vec![
+        (POLY_ADDRESS, to_checksum(&address, None)),
+        (POLY_SIGNATURE, format!("0x{}", signature)),
+        (POLY_TIMESTAMP, timestamp.to_string()),
+        (POLY_NONCE, nonce.to_string()),
+    ])
+}
+
+/// HMAC-SHA256 over `timestamp + method + path + body`, URL-safe base64 encoded
+pub fn build_hmac_signature(secret: &str, timestamp: &str, method: &str, request_path: &str, body: Option<&str>) -> AppResult<String> {
+    let key = SECRET_ENGINE
+        .decode(secret.trim())
+        .map_err(|e| AppError::ConfigurationError(format!("Invalid CLOB API secret: {}", e)))?;
+    let mut mac = Hmac::<Sha256>::new_from_slice(&key)
+        .map_err(|e| AppError::ConfigurationError(format!("Invalid CLOB API secret: {}", e)))?;
+
+    mac.update(timestamp.as_bytes());
+    mac.update(method.as_bytes());
+    mac.update(request_path.as_bytes());
+    if let Some(body) = body {
+        mac.update(body.as_bytes());
+    }
+    Ok(base64::engine::general_purpose::URL_SAFE.encode(mac.finalize().into_bytes()))
+}
+
+/// L2 headers for authenticated trading endpoints
+pub fn l2_headers(
+    signer: Address,
+    creds: &ApiCreds,
+    method: &str,
+    request_path: &str,
+    body: Option<&str>,
+    timestamp: &str,
+) -> AppResult<Vec<(&'static str, String)>> {
+    let signature = build_hmac_signature(&creds.secret, timestamp, method, request_path, body)?;
+    Ok(vec![
+        (POLY_ADDRESS, to_checksum(&signer, None)),
+        (POLY_SIGNATURE, signature),
+        (POLY_TIMESTAMP, timestamp.to_string()),
+        (POLY_API_KEY, creds.api_key.clone()),
+        (POLY_PASSPHRASE, creds.passphrase.clone()),
+    ])
+}
+
+/// Read cached credentials for `signer` from the credentials file
+/// The file maps checksummed signer addresses to credentials so several wallets can share it
+pub fn load_cached_creds(path: &Path, signer: Address) -> Option<ApiCreds> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    let cache: HashMap<String, ApiCreds> = serde_json::from_str(&contents).ok()?;
+    cache
+        .get(&to_checksum(&signer, None))
+        .filter(|creds| creds.is_valid())
+        .cloned()
+}
+
+/// Persist credentials for `signer`, keeping entries for other wallets
+pub fn save_cached_creds(path: &Path, signer: Address, creds: &ApiCreds) -> AppResult<()> {
+    let mut cache: HashMap<String, ApiCreds> = std::fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default();
+    cache.insert(to_checksum(&signer, None), creds.clone());
+
+    let contents = serde_json::to_string_pretty(&cache)
+        .map_err(|e| AppError::Unknown(format!("Failed to serialize API credentials: {}", e)))?;
+    let write_error =
+        |e: std::io::Error| AppError::ConfigurationError(format!("Failed to write {}: {}", path.display(), e));
+
+    // The file holds an API secret - keep it private to the bot's user from the moment it exists
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path).map_err(write_error)?;
+    // `mode` only applies to new files; tighten one left readable by an older version before writing
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600)).map_err(write_error)?;
+    }
+    file.write_all(contents.as_bytes()).map_err(write_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use ethers::types::transaction::eip712::{Eip712, TypedData};
+
+    const TEST_PRIVATE_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    const TEST_ADDRESS: &str = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+
+    #[test]
+    fn clob_auth_digest_matches_typed_data_encoding() {
+        let typed: TypedData = serde_json::from_value(serde_json::json!({
+            "types": {
+                "EIP712Domain": [
+                    {"name": "name", "type": "string"},
+                    {"name": "version", "type": "string"},
+                    {"name": "chainId", "type": "uint256"}
+                ],
+                "ClobAuth": [
+                    {"name": "address", "type": "address"},
+                    {"name": "timestamp", "type": "string"},
+                    {"name": "nonce", "type": "uint256"},
+                    {"name": "message", "type": "string"}
+                ]
+            },
+            "primaryType": "ClobAuth",
+            "domain": {"name": "ClobAuthDomain", "version": "1", "chainId": 137},
+            "message": {
+                "address": TEST_ADDRESS,
+                "timestamp": "1700000000",
+                "nonce": 0,
+                "message": CLOB_AUTH_MESSAGE
+            }
+        }))
+        .unwrap();
+
+        let address = Address::from_str(TEST_ADDRESS).unwrap();
+        assert_eq!(clob_auth_digest(address, "1700000000", 0, 137), typed.encode_eip712().unwrap());
+    }
+
+    #[test]
+    fn l1_headers_carry_recoverable_signature() {
+        let wallet = LocalWallet::from_str(TEST_PRIVATE_KEY).unwrap();
+        let headers: HashMap<_, _> = l1_headers(&wallet, 137, 0, "1700000000").unwrap().into_iter().collect();
+        assert_eq!(headers[POLY_ADDRESS], TEST_ADDRESS);
+        assert_eq!(headers[POLY_NONCE], "0");
+
+        let signature = ethers::types::Signature::from_str(&headers[POLY_SIGNATURE]).unwrap();
+        let digest = clob_auth_digest(Address::from_str(TEST_ADDRESS).unwrap(), "1700000000", 0, 137);
+        assert_eq!(signature.recover(H256::from(digest)).unwrap(), Address::from_str(TEST_ADDRESS).unwrap());
+    }
+
+    #[test]
+    fn hmac_signature_vectors() {
+        // Expected values computed independently with Python's hmac/base64 modules
+        let secret = "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=";
+        let cases = [
+            ("1000000", "GET", "/data/orders", None, "1T6JvlIKwKjAC4ZRdyg9-9os_Govl3xuB_mRMbpT6tA="),
+            ("1700000000", "POST", "/order", Some(r#"{"hash":"0x123"}"#), "7VNJy5Y3mGqy4VkHzKJd45AiIVnlkVJtycPXGW0DG3Y="),
+            ("1700000000", "DELETE", "/order", Some(r#"{"orderID":"0xabc"}"#), "R6Zjd0XmcT575-SA5KWD9BUc-qkFqvudAxkHd3R1vpw="),
+        ];
+        for (timestamp, method, path, body, expected) in cases {
+            assert_eq!(build_hmac_signature(secret, timestamp, method, path, body).unwrap(), expected, "{} {}", method, path);
+        }
+        // URL-safe and unpadded secrets decode to the same key
+        assert_eq!(
+            build_hmac_signature("AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8", "1000000", "GET", "/data/orders", None).unwrap(),
+            "1T6JvlIKwKjAC4ZRdyg9-9os_Govl3xuB_mRMbpT6tA="
+        );
+    }
+
+    #[test]
+    fn credentials_cache_round_trip() {
+        let path = std::env::temp_dir().join(format!("clob-creds-{}.json", std::process::id()));
+        let signer = Address::from_str(TEST_ADDRESS).unwrap();
+        let other = Address::from_low_u64_be(1);
+        let creds = ApiCreds {
+            api_key: "key".to_string(),
+            secret: "c2VjcmV0".to_string(),
+            passphrase: "pass".to_string(),
+        };
+
+        assert_eq!(load_cached_creds(&path, signer), None);
+        save_cached_creds(&path, signer, &creds).unwrap();
+        save_cached_creds(&path, other, &ApiCreds { api_key: "other".to_string(), ..creds.clone() }).unwrap();
+        assert_eq!(load_cached_creds(&path, signer), Some(creds));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+}
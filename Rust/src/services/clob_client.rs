@@ -0,0 +1,1245 @@
+// Polymarket CLOB client
+// Native Rust replacement for @polymarket/clob-client: builds CTF Exchange orders,
+// signs them per EIP-712 and posts them to CLOB_HTTP_URL
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+use ethers::abi::{encode, Token};
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::{Address, H256, U256};
+use ethers::utils::{keccak256, to_checksum};
+use reqwest::{Client, Method};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use tokio::sync::RwLock;
+use crate::interfaces::user::OrderBook;
+use crate::services::clob_auth::{self, ApiCreds};
+use crate::utils::constants::CLOB_CONSTANTS;
+use crate::utils::errors::{AppError, AppResult};
+
+/// EIP-712 type string of a CTF Exchange order
+const ORDER_TYPE: &str = "Order(uint256 salt,address maker,address signer,address taker,uint256 tokenId,uint256 makerAmount,uint256 takerAmount,uint256 expiration,uint256 nonce,uint256 feeRateBps,uint8 side,uint8 signatureType)";
+
+/// EIP-712 domain type string used by both exchanges
+const DOMAIN_TYPE: &str = "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+
+/// Order side
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+impl Side {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Side::Buy => "BUY",
+            Side::Sell => "SELL",
+        }
+    }
+
+    /// On-chain encoding of the side (BUY = 0, SELL = 1)
+    fn as_u8(self) -> u8 {
+        match self {
+            Side::Buy => 0,
+            Side::Sell => 1,
+        }
+    }
+}
+
+/// Time-in-force of a posted order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+#[allow(dead_code)] // GTC/FAK are part of the CLOB API surface
+pub enum OrderType {
+    /// Good till cancelled
+    Gtc,
+    /// Fill or kill
+    Fok,
+    /// Good till date (requires expiration)
+    Gtd,
+    /// Fill and kill (partial fills allowed, rest cancelled)
+    Fak,
+}
+
+impl OrderType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OrderType::Gtc => "GTC",
+            OrderType::Fok => "FOK",
+            OrderType::Gtd => "GTD",
+            OrderType::Fak => "FAK",
+        }
+    }
+}
+
+/// How the maker's signature must be verified by the exchange
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(dead_code)] // PolyProxy is part of the CTF Exchange spec
+pub enum SignatureType {
+    /// Plain EOA: maker == signer
+    Eoa = 0,
+    /// Polymarket email/magic proxy wallet
+    PolyProxy = 1,
+    /// Gnosis Safe owned by the signer
+    PolyGnosisSafe = 2,
+}
+
+/// Decimal precision used when converting prices/sizes to raw amounts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RoundConfig {
+    price: u32,
+    size: u32,
+    amount: u32,
+}
+
+/// Derive rounding precision from market tick size (matches clob-client ROUNDING_CONFIG)
+fn round_config(tick_size: f64) -> RoundConfig {
+    let price = (-tick_size.log10()).round().max(1.0) as u32;
+    RoundConfig { price, size: 2, amount: price + 2 }
+}
+
+fn round_down(value: Decimal, decimals: u32) -> Decimal {
+    value.round_dp_with_strategy(decimals, RoundingStrategy::ToZero)
+}
+
+fn round_up(value: Decimal, decimals: u32) -> Decimal {
+    value.round_dp_with_strategy(decimals, RoundingStrategy::AwayFromZero)
+}
+
+fn decimal_places(value: Decimal) -> u32 {
+    value.normalize().scale()
+}
+
+fn to_decimal(value: f64) -> AppResult<Decimal> {
+    Decimal::from_f64(value)
+        .ok_or_else(|| AppError::ValidationError(format!("Invalid numeric value: {}", value)))
+}
+
+/// Convert a human amount into 6-decimal raw units
+fn to_units(value: Decimal) -> AppResult<U256> {
+    let scaled = (value * Decimal::from(10u64.pow(CLOB_CONSTANTS::COLLATERAL_DECIMALS))).trunc();
+    scaled
+        .to_u128()
+        .map(U256::from)
+        .ok_or_else(|| AppError::ValidationError(format!("Amount out of range: {}", value)))
+}
+
+/// Round the taker amount the same way clob-client does before converting to units
+fn round_taker_amount(raw_taker: Decimal, config: RoundConfig) -> Decimal {
+    let mut raw_taker = raw_taker;
+    if decimal_places(raw_taker) > config.amount {
+        raw_taker = round_up(raw_taker, config.amount + 4);
+        if decimal_places(raw_taker) > config.amount {
+            raw_taker = round_down(raw_taker, config.amount);
+        }
+    }
+    raw_taker
+}
+
+/// Raw (maker, taker) amounts for a market order
+/// BUY: `amount` is USDC to spend, SELL: `amount` is tokens to sell
+fn market_order_amounts(side: Side, amount: f64, price: f64, config: RoundConfig) -> AppResult<(U256, U256)> {
+    let raw_price = round_down(to_decimal(price)?, config.price);
+    if raw_price.is_zero() {
+        return Err(AppError::ValidationError(format!("Invalid order price: {}", price)));
+    }
+    let raw_maker = round_down(to_decimal(amount)?, config.size);
+    let raw_taker = match side {
+        Side::Buy => raw_maker / raw_price,
+        Side::Sell => raw_maker * raw_price,
+    };
+    let raw_taker = round_taker_amount(raw_taker, config);
+    Ok((to_units(raw_maker)?, to_units(raw_taker)?))
+}
+
+/// Raw (maker, taker) amounts for a limit order of `size` tokens at `price`
+fn limit_order_amounts(side: Side, size: f64, price: f64, config: RoundConfig) -> AppResult<(U256, U256)> {
+    let raw_price = round_down(to_decimal(price)?, config.price);
+    let raw_size = round_down(to_decimal(size)?, config.size);
+    match side {
+        Side::Buy => {
+            let raw_maker = round_taker_amount(raw_size * raw_price, config);
+            Ok((to_units(raw_maker)?, to_units(raw_size)?))
+        }
+        Side::Sell => {
+            let raw_taker = round_taker_amount(raw_size * raw_price, config);
+            Ok((to_units(raw_size)?, to_units(raw_taker)?))
+        }
+    }
+}
+
+/// Arguments for a market order (FOK/FAK against the book)
+#[derive(Debug, Clone)]
+pub struct MarketOrderArgs {
+    pub token_id: String,
+    /// USDC to spend for BUY, tokens to sell for SELL
+    pub amount: f64,
+    /// Worst acceptable price
+    pub price: f64,
+    pub side: Side,
+}
+
+/// Arguments for a limit order
+#[derive(Debug, Clone)]
+pub struct OrderArgs {
+    pub token_id: String,
+    pub price: f64,
+    /// Size in outcome tokens
+    pub size: f64,
+    pub side: Side,
+    /// Unix seconds, 0 = no expiration (required for GTD)
+    pub expiration: u64,
+}
+
+/// Unsigned CTF Exchange order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Order {
+    pub salt: u64,
+    pub maker: Address,
+    pub signer: Address,
+    pub taker: Address,
+    pub token_id: U256,
+    pub maker_amount: U256,
+    pub taker_amount: U256,
+    pub expiration: U256,
+    pub nonce: U256,
+    pub fee_rate_bps: U256,
+    pub side: Side,
+    pub signature_type: SignatureType,
+    /// Selects the NegRisk exchange as verifying contract
+    pub neg_risk: bool,
+}
+
+impl Order {
+    /// EIP-712 struct hash of the order
+    pub fn struct_hash(&self) -> [u8; 32] {
+        keccak256(encode(&[
+            Token::FixedBytes(keccak256(ORDER_TYPE).to_vec()),
+            Token::Uint(U256::from(self.salt)),
+            Token::Address(self.maker),
+            Token::Address(self.signer),
+            Token::Address(self.taker),
+            Token::Uint(self.token_id),
+            Token::Uint(self.maker_amount),
+            Token::Uint(self.taker_amount),
+            Token::Uint(self.expiration),
+            Token::Uint(self.nonce),
+            Token::Uint(self.fee_rate_bps),
+            Token::Uint(U256::from(self.side.as_u8())),
+            Token::Uint(U256::from(self.signature_type as u8)),
+        ]))
+    }
+
+    /// Final EIP-712 digest (`\x19\x01 ‖ domainSeparator ‖ structHash`) that gets signed
+    pub fn digest(&self, chain_id: u64) -> [u8; 32] {
+        let mut payload = Vec::with_capacity(66);
+        payload.extend_from_slice(&[0x19, 0x01]);
+        payload.extend_from_slice(&domain_separator(chain_id, exchange_address(self.neg_risk)));
+        payload.extend_from_slice(&self.struct_hash());
+        keccak256(payload)
+    }
+}
+
+/// Verifying contract for the order's market type
+fn exchange_address(neg_risk: bool) -> Address {
+    let address = if neg_risk {
+        CLOB_CONSTANTS::NEG_RISK_EXCHANGE_ADDRESS
+    } else {
+        CLOB_CONSTANTS::EXCHANGE_ADDRESS
+    };
+    Address::from_str(address).expect("exchange address constant is valid")
+}
+
+/// EIP-712 domain separator of the CTF Exchange
+fn domain_separator(chain_id: u64, verifying_contract: Address) -> [u8; 32] {
+    keccak256(encode(&[
+        Token::FixedBytes(keccak256(DOMAIN_TYPE).to_vec()),
+        Token::FixedBytes(keccak256(CLOB_CONSTANTS::EXCHANGE_DOMAIN_NAME).to_vec()),
+        Token::FixedBytes(keccak256(CLOB_CONSTANTS::EXCHANGE_DOMAIN_VERSION).to_vec()),
+        Token::Uint(U256::from(chain_id)),
+        Token::Address(verifying_contract),
+    ]))
+}
+
+/// Order plus its 65-byte `r ‖ s ‖ v` signature
+#[derive(Debug, Clone)]
+pub struct SignedOrder {
+    pub order: Order,
+    pub signature: String,
+}
+
+impl SignedOrder {
+    /// JSON representation expected by `POST /order` (matches clob-client orderToJson)
+    pub fn to_json(&self) -> serde_json::Value {
+        let order = &self.order;
+        serde_json::json!({
+            "salt": order.salt,
+            "maker": to_checksum(&order.maker, None),
+            "signer": to_checksum(&order.signer, None),
+            "taker": to_checksum(&order.taker, None),
+            "tokenId": order.token_id.to_string(),
+            "makerAmount": order.maker_amount.to_string(),
+            "takerAmount": order.taker_amount.to_string(),
+            "expiration": order.expiration.to_string(),
+            "nonce": order.nonce.to_string(),
+            "feeRateBps": order.fee_rate_bps.to_string(),
+            "side": order.side,
+            "signatureType": order.signature_type as u8,
+            "signature": self.signature,
+        })
+    }
+}
+
+/// Sign an order with the given wallet
+pub fn sign_order(wallet: &LocalWallet, order: Order, chain_id: u64) -> AppResult<SignedOrder> {
+    let digest = order.digest(chain_id);
+    let signature = wallet
+        .sign_hash(H256::from(digest))
+        .map_err(|e| AppError::TradingError(format!("Failed to sign order: {}", e)))?;
+    Ok(SignedOrder {
+        order,
+        signature: format!("0x{}", signature),
+    })
+}
+
+/// Accept both JSON strings and numbers for amount fields
+fn de_string_or_number<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(match value {
+        serde_json::Value::String(s) => s,
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    })
+}
+
+/// Response of `POST /order`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OrderResponse {
+    #[serde(default)]
+    pub success: bool,
+    #[serde(rename = "errorMsg", default)]
+    pub error_msg: String,
+    #[serde(rename = "orderID", default)]
+    pub order_id: String,
+    #[serde(default)]
+    pub status: String,
+    #[serde(rename = "makingAmount", default, deserialize_with = "de_string_or_number")]
+    pub making_amount: String,
+    #[serde(rename = "takingAmount", default, deserialize_with = "de_string_or_number")]
+    pub taking_amount: String,
+    #[serde(rename = "transactionsHashes", default)]
+    pub transactions_hashes: Vec<String>,
+}
+
+impl OrderResponse {
+    /// Build a failed response from an error body (`{"error": ...}` or similar)
+    fn from_error_body(body: &str, status: reqwest::StatusCode) -> Self {
+        let message = serde_json::from_str::<serde_json::Value>(body)
+            .ok()
+            .and_then(|value| {
+                ["error", "errorMsg", "message"]
+                    .iter()
+                    .find_map(|key| value.get(*key).and_then(|v| v.as_str()).map(str::to_string))
+            })
+            .unwrap_or_else(|| format!("HTTP {}: {}", status, body.trim()));
+        OrderResponse {
+            success: false,
+            error_msg: message,
+            ..Default::default()
+        }
+    }
+
+    /// Matched `(making, taking)` amounts, if the CLOB reported them
+    ///
+    /// For a BUY making is USDC spent and taking is tokens received; for a SELL it is the reverse.
+    pub fn filled_amounts(&self) -> Option<(f64, f64)> {
+        let making = self.making_amount.parse::<f64>().ok()?;
+        let taking = self.taking_amount.parse::<f64>().ok()?;
+        if making > 0.0 && taking > 0.0 {
+            Some((making, taking))
+        } else {
+            None
+        }
+    }
+
+    /// Error message if the order was rejected
+    pub fn error_message(&self) -> Option<&str> {
+        if self.error_msg.is_empty() {
+            None
+        } else {
+            Some(self.error_msg.as_str())
+        }
+    }
+}
+
+/// One of our orders, as reported by `GET /data/order/{id}`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OpenOrder {
+    #[serde(default)]
+    pub id: String,
+    /// MATCHED, LIVE, CANCELED, ...
+    #[serde(default)]
+    pub status: String,
+    #[serde(default)]
+    pub side: String,
+    #[serde(default)]
+    pub asset_id: String,
+    #[serde(default, deserialize_with = "de_string_or_number")]
+    pub original_size: String,
+    #[serde(default, deserialize_with = "de_string_or_number")]
+    pub size_matched: String,
+    #[serde(default, deserialize_with = "de_string_or_number")]
+    pub price: String,
+}
+
+impl OpenOrder {
+    /// Tokens matched so far
+    pub fn matched_size(&self) -> f64 {
+        self.size_matched.parse().unwrap_or(0.0)
+    }
+}
+
+/// One of our fills, as reported by `GET /data/trades`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ClobTrade {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub taker_order_id: String,
+    #[serde(default)]
+    pub asset_id: String,
+    /// Side of the taker order
+    #[serde(default)]
+    pub side: String,
+    #[serde(default, deserialize_with = "de_string_or_number")]
+    pub size: String,
+    #[serde(default, deserialize_with = "de_string_or_number")]
+    pub price: String,
+    /// MATCHED, MINED, CONFIRMED, RETRYING or FAILED
+    #[serde(default)]
+    pub status: String,
+    /// Whether we were the TAKER or a MAKER in this match
+    #[serde(default)]
+    pub trader_side: String,
+    /// Fee rate charged to the taker order
+    #[serde(default, deserialize_with = "de_string_or_number")]
+    pub fee_rate_bps: String,
+    #[serde(default)]
+    pub maker_orders: Vec<MakerOrderFill>,
+}
+
+/// A maker order's part in a match
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MakerOrderFill {
+    #[serde(default)]
+    pub order_id: String,
+    #[serde(default, deserialize_with = "de_string_or_number")]
+    pub matched_amount: String,
+    #[serde(default, deserialize_with = "de_string_or_number")]
+    pub price: String,
+    #[serde(default, deserialize_with = "de_string_or_number")]
+    pub fee_rate_bps: String,
+}
+
+/// Fee in USDC on `tokens` matched at `price`
+///
+/// The exchange charges `rate * min(price, 1 - price)` per token: in tokens on a BUY and in USDC on
+/// a SELL, which comes to the same USDC value.
+pub fn match_fee(fee_rate_bps: f64, price: f64, tokens: f64) -> f64 {
+    fee_rate_bps / 10_000.0 * price.min(1.0 - price).max(0.0) * tokens
+}
+
+impl ClobTrade {
+    /// Tokens matched
+    pub fn size(&self) -> f64 {
+        self.size.parse().unwrap_or(0.0)
+    }
+    /// USDC per token
+    pub fn price(&self) -> f64 {
+        self.price.parse().unwrap_or(0.0)
+    }
+
+    /// Tokens `order_id` matched here, as taker or maker, and the fee charged on them
+    pub fn order_fill(&self, order_id: &str) -> Option<(f64, f64)> {
+        let parse = |value: &str| value.parse::<f64>().unwrap_or(0.0);
+        if self.taker_order_id == order_id {
+            let fee = match_fee(parse(&self.fee_rate_bps), self.price(), self.size());
+            return Some((self.size(), fee));
+        }
+        self.maker_orders.iter().filter(|maker| maker.order_id == order_id).fold(None, |total, maker| {
+            let (tokens, fee) = total.unwrap_or((0.0, 0.0));
+            let matched = parse(&maker.matched_amount);
+            Some((tokens + matched, fee + match_fee(parse(&maker.fee_rate_bps), parse(&maker.price), matched)))
+        })
+    }
+}
+
+/// Tokens `order_id` matched across `trades` and the fees charged on them, None if it is in none of them
+pub fn order_fills(trades: &[ClobTrade], order_id: &str) -> Option<(f64, f64)> {
+    trades
+        .iter()
+        .filter_map(|trade| trade.order_fill(order_id))
+        .reduce(|(tokens, fee), (more_tokens, more_fee)| (tokens + more_tokens, fee + more_fee))
+}
+
+#[derive(Deserialize)]
+struct TradesPage {
+    #[serde(default)]
+    data: Vec<ClobTrade>,
+    #[serde(default)]
+    next_cursor: String,
+}
+
+#[derive(Deserialize)]
+struct TickSizeResponse {
+    minimum_tick_size: f64,
+}
+
+#[derive(Deserialize)]
+struct NegRiskResponse {
+    neg_risk: bool,
+}
+
+#[derive(Deserialize)]
+struct FeeRateResponse {
+    #[serde(default)]
+    base_fee: f64,
+}
+
+/// Polymarket CLOB client
+pub struct ClobClient {
+    host: String,
+    chain_id: u64,
+    wallet: LocalWallet,
+    signature_type: SignatureType,
+    funder: Address,
+    http: Client,
+    creds: Option<ApiCreds>,
+    tick_sizes: RwLock<HashMap<String, f64>>,
+    neg_risk: RwLock<HashMap<String, bool>>,
+    fee_rates: RwLock<HashMap<String, u64>>,
+}
+
+impl ClobClient {
+    /// Create a client for `host`
+    /// `funder` is the wallet holding the funds (proxy/Safe); defaults to the signer's address
+    pub fn new(
+        host: &str,
+        chain_id: u64,
+        private_key: &str,
+        signature_type: SignatureType,
+        funder: Option<&str>,
+        request_timeout_ms: u64,
+    ) -> AppResult<Self> {
+        let wallet = LocalWallet::from_str(private_key.trim())
+            .map_err(|e| AppError::ConfigurationError(format!("Invalid PRIVATE_KEY: {}", e)))?
+            .with_chain_id(chain_id);
+        let funder = match funder {
+            Some(address) => Address::from_str(address)
+                .map_err(|e| AppError::ConfigurationError(format!("Invalid funder address {}: {}", address, e)))?,
+            None => wallet.address(),
+        };
+        let http = Client::builder()
+            .timeout(std::time::Duration::from_millis(request_timeout_ms))
+            .build()
+            .map_err(|e| AppError::NetworkError(format!("Failed to build HTTP client: {}", e)))?;
+
+        Ok(Self {
+            host: host.trim_end_matches('/').to_string(),
+            chain_id,
+            wallet,
+            signature_type,
+            funder,
+            http,
+            creds: None,
+            tick_sizes: RwLock::new(HashMap::new()),
+            neg_risk: RwLock::new(HashMap::new()),
+            fee_rates: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Address of the signing EOA
+    #[allow(dead_code)] // Used by API key derivation and diagnostics
+    pub fn signer_address(&self) -> Address {
+        self.wallet.address()
+    }
+
+    /// GET a JSON resource from the CLOB
+    async fn get_json<T: DeserializeOwned>(&self, path: &str, query: &[(&str, &str)]) -> AppResult<T> {
+        let url = format!("{}{}", self.host, path);
+        let response = self
+            .http
+            .get(&url)
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| AppError::NetworkError(format!("GET {} failed: {}", path, e)))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(AppError::NetworkError(format!("GET {} returned HTTP {}", path, status)));
+        }
+        response
+            .json::<T>()
+            .await
+            .map_err(|e| AppError::NetworkError(format!("Failed to parse {} response: {}", path, e)))
+    }
+
+    /// Current order book of a token
+    pub async fn get_order_book(&self, token_id: &str) -> AppResult<OrderBook> {
+        self.get_json(CLOB_CONSTANTS::BOOK_ENDPOINT, &[("token_id", token_id)]).await
+    }
+
+    /// Minimum tick size of a token (cached)
+    pub async fn get_tick_size(&self, token_id: &str) -> AppResult<f64> {
+        if let Some(tick_size) = self.tick_sizes.read().await.get(token_id) {
+            return Ok(*tick_size);
+        }
+        let response: TickSizeResponse = self
+            .get_json(CLOB_CONSTANTS::TICK_SIZE_ENDPOINT, &[("token_id", token_id)])
+            .await?;
+        let tick_size = if response.minimum_tick_size > 0.0 {
+            response.minimum_tick_size
+        } else {
+            CLOB_CONSTANTS::DEFAULT_TICK_SIZE
+        };
+        self.tick_sizes.write().await.insert(token_id.to_string(), tick_size);
+        Ok(tick_size)
+    }
+
+    /// Whether a token trades on the NegRisk exchange (cached)
+    pub async fn get_neg_risk(&self, token_id: &str) -> AppResult<bool> {
+        if let Some(neg_risk) = self.neg_risk.read().await.get(token_id) {
+            return Ok(*neg_risk);
+        }
+        let response: NegRiskResponse = self
+            .get_json(CLOB_CONSTANTS::NEG_RISK_ENDPOINT, &[("token_id", token_id)])
+            .await?;
+        self.neg_risk.write().await.insert(token_id.to_string(), response.neg_risk);
+        Ok(response.neg_risk)
+    }
+
+    /// Fee rate in basis points the CLOB expects orders on a token to be signed with (cached)
+    pub async fn get_fee_rate_bps(&self, token_id: &str) -> AppResult<u64> {
+        if let Some(fee_rate_bps) = self.fee_rates.read().await.get(token_id) {
+            return Ok(*fee_rate_bps);
+        }
+        let response: FeeRateResponse = self
+            .get_json(CLOB_CONSTANTS::FEE_RATE_ENDPOINT, &[("token_id", token_id)])
+            .await?;
+        let fee_rate_bps = response.base_fee.max(0.0).round() as u64;
+        self.fee_rates.write().await.insert(token_id.to_string(), fee_rate_bps);
+        Ok(fee_rate_bps)
+    }
+
+    /// Assemble an unsigned order for this client's maker/signer
+    fn build_order(
+        &self,
+        token_id: &str,
+        amounts: (U256, U256),
+        side: Side,
+        expiration: u64,
+        (neg_risk, fee_rate_bps): (bool, u64),
+    ) -> AppResult<Order> {
+        let token_id = U256::from_dec_str(token_id)
+            .map_err(|e| AppError::ValidationError(format!("Invalid token ID {}: {}", token_id, e)))?;
+        // Same salt scheme as clob-client: round(random() * now_ms)
+        let now_ms = chrono::Utc::now().timestamp_millis() as f64;
+        let salt = (ethers::core::rand::random::<f64>() * now_ms).round() as u64;
+
+        Ok(Order {
+            salt,
+            maker: self.funder,
+            signer: self.wallet.address(),
+            taker: Address::zero(),
+            token_id,
+            maker_amount: amounts.0,
+            taker_amount: amounts.1,
+            expiration: U256::from(expiration),
+            nonce: U256::zero(),
+            fee_rate_bps: U256::from(fee_rate_bps),
+            side,
+            signature_type: self.signature_type,
+            neg_risk,
+        })
+    }
+
+    /// Create and sign a market order
+    pub async fn create_market_order(&self, args: &MarketOrderArgs) -> AppResult<SignedOrder> {
+        let tick_size = self.get_tick_size(&args.token_id).await?;
+        let neg_risk = self.get_neg_risk(&args.token_id).await?;
+        let fee_rate_bps = self.get_fee_rate_bps(&args.token_id).await?;
+        let amounts = market_order_amounts(args.side, args.amount, args.price, round_config(tick_size))?;
+        let order = self.build_order(&args.token_id, amounts, args.side, 0, (neg_risk, fee_rate_bps))?;
+        sign_order(&self.wallet, order, self.chain_id)
+    }
+
+    /// Create and sign a limit order
+    pub async fn create_order(&self, args: &OrderArgs) -> AppResult<SignedOrder> {
+        let tick_size = self.get_tick_size(&args.token_id).await?;
+        let neg_risk = self.get_neg_risk(&args.token_id).await?;
+        let fee_rate_bps = self.get_fee_rate_bps(&args.token_id).await?;
+        let amounts = limit_order_amounts(args.side, args.size, args.price, round_config(tick_size))?;
+        let order = self.build_order(&args.token_id, amounts, args.side, args.expiration, (neg_risk, fee_rate_bps))?;
+        sign_order(&self.wallet, order, self.chain_id)
+    }
+
+    /// Use existing L2 credentials for authenticated requests
+    #[allow(dead_code)] // Credentials are normally derived via create_or_derive_api_creds
+    pub fn set_api_creds(&mut self, creds: ApiCreds) {
+        self.creds = Some(creds);
+    }
+
+    /// Send an L1-authenticated request to an `/auth/*` endpoint and parse the returned credentials
+    async fn l1_request(&self, method: Method, path: &str, nonce: u64) -> AppResult<ApiCreds> {
+        let timestamp = clob_auth::current_timestamp();
+        let headers = clob_auth::l1_headers(&self.wallet, self.chain_id, nonce, &timestamp)?;
+        let mut request = self.http.request(method.clone(), format!("{}{}", self.host, path));
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| AppError::NetworkError(format!("{} {} failed: {}", method, path, e)))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(AppError::NetworkError(format!("{} {} returned HTTP {}: {}", method, path, status, body.trim())));
+        }
+        let creds: ApiCreds = response
+            .json()
+            .await
+            .map_err(|e| AppError::NetworkError(format!("Failed to parse {} response: {}", path, e)))?;
+        if !creds.is_valid() {
+            return Err(AppError::NetworkError(format!("{} returned incomplete API credentials", path)));
+        }
+        Ok(creds)
+    }
+
+    /// Mint a new API key for this wallet
+    pub async fn create_api_key(&self, nonce: u64) -> AppResult<ApiCreds> {
+        self.l1_request(Method::POST, CLOB_CONSTANTS::CREATE_API_KEY_ENDPOINT, nonce).await
+    }
+
+    /// Fetch the existing API key for this wallet and nonce
+    pub async fn derive_api_key(&self, nonce: u64) -> AppResult<ApiCreds> {
+        self.l1_request(Method::GET, CLOB_CONSTANTS::DERIVE_API_KEY_ENDPOINT, nonce).await
+    }
+
+    /// Load credentials from `cache_path`, else derive the existing key, else create one
+    /// Derive comes first so restarts never mint a new key; fresh credentials are written back to the cache
+    pub async fn create_or_derive_api_creds(&mut self, cache_path: &Path) -> AppResult<()> {
+        let signer = self.wallet.address();
+        if let Some(creds) = clob_auth::load_cached_creds(cache_path, signer) {
+            self.creds = Some(creds);
+            return Ok(());
+        }
+
+        let creds = match self.derive_api_key(0).await {
+            Ok(creds) => creds,
+            Err(derive_error) => self.create_api_key(0).await.map_err(|create_error| {
+                AppError::NetworkError(format!(
+                    "Could not derive ({}) or create ({}) CLOB API key",
+                    derive_error, create_error
+                ))
+            })?,
+        };
+        clob_auth::save_cached_creds(cache_path, signer, &creds)?;
+        self.creds = Some(creds);
+        Ok(())
+    }
+
+    /// Send an L2-authenticated request, returning the status and raw body
+    /// Only `path` is signed; `query` is appended to the URL
+    async fn l2_request(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, &str)],
+        body: Option<String>,
+    ) -> AppResult<(reqwest::StatusCode, String)> {
+        let creds = self.creds.as_ref().ok_or_else(|| {
+            AppError::ConfigurationError("CLOB API credentials not initialized".to_string())
+        })?;
+        let timestamp = clob_auth::current_timestamp();
+        let headers = clob_auth::l2_headers(
+            self.wallet.address(),
+            creds,
+            method.as_str(),
+            path,
+            body.as_deref(),
+            &timestamp,
+        )?;
+
+        let mut request = self.http.request(method.clone(), format!("{}{}", self.host, path)).query(query);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        // The body must be sent byte-for-byte as it was signed
+        if let Some(body) = body {
+            request = request
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| AppError::NetworkError(format!("{} {} failed: {}", method, path, e)))?;
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| AppError::NetworkError(format!("Failed to read {} response: {}", path, e)))?;
+        Ok((status, text))
+    }
+
+    /// GET an L2-authenticated JSON resource; `None` if the CLOB has nothing under `path`
+    async fn l2_get_json<T: DeserializeOwned>(&self, path: &str, query: &[(&str, &str)]) -> AppResult<Option<T>> {
+        let (status, text) = self.l2_request(Method::GET, path, query, None).await?;
+        if status == reqwest::StatusCode::NOT_FOUND || text.trim().is_empty() || text.trim() == "null" {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Err(AppError::NetworkError(format!("GET {} returned HTTP {} {}", path, status, text.trim())));
+        }
+        serde_json::from_str(&text)
+            .map(Some)
+            .map_err(|e| AppError::NetworkError(format!("Failed to parse {} response: {}", path, e)))
+    }
+
+    /// One of our orders by ID; `None` if the CLOB does not know it
+    pub async fn get_order(&self, order_id: &str) -> AppResult<Option<OpenOrder>> {
+        let path = format!("{}{}", CLOB_CONSTANTS::ORDER_ENDPOINT, order_id);
+        self.l2_get_json(&path, &[]).await
+    }
+
+    /// Our fills of a token matched at or after `after` (unix seconds), across all pages
+    pub async fn get_trades(&self, asset_id: &str, after: i64) -> AppResult<Vec<ClobTrade>> {
+        let maker = to_checksum(&self.funder, None);
+        let after = after.to_string();
+        let mut cursor = String::new();
+        let mut trades = Vec::new();
+        loop {
+            let mut query = vec![("maker_address", maker.as_str()), ("asset_id", asset_id), ("after", after.as_str())];
+            if !cursor.is_empty() {
+                query.push(("next_cursor", cursor.as_str()));
+            }
+            let Some(page) = self.l2_get_json::<TradesPage>(CLOB_CONSTANTS::TRADES_ENDPOINT, &query).await? else {
+                break;
+            };
+            trades.extend(page.data);
+            if page.next_cursor.is_empty() || page.next_cursor == CLOB_CONSTANTS::END_CURSOR || page.next_cursor == cursor {
+                break;
+            }
+            cursor = page.next_cursor;
+        }
+        Ok(trades)
+    }
+
+    /// Submit a signed order
+    /// Rejections are returned as `success: false` with the CLOB's message, not as `Err`
+    pub async fn post_order(&self, order: &SignedOrder, order_type: OrderType) -> AppResult<OrderResponse> {
+        let owner = self.creds.as_ref().map(|creds| creds.api_key.clone()).unwrap_or_default();
+        let body = serde_json::json!({
+            "order": order.to_json(),
+            "owner": owner,
+            "orderType": order_type,
+        });
+        let (status, text) = self
+            .l2_request(Method::POST, CLOB_CONSTANTS::POST_ORDER_ENDPOINT, &[], Some(body.to_string()))
+            .await?;
+
+        if !status.is_success() {
+            return Ok(OrderResponse::from_error_body(&text, status));
+        }
+        serde_json::from_str::<OrderResponse>(&text)
+            .map_err(|e| AppError::NetworkError(format!("Failed to parse /order response: {}", e)))
+    }
+
+    /// Cancel a resting order
+    pub async fn cancel_order(&self, order_id: &str) -> AppResult<()> {
+        let body = serde_json::json!({ "orderID": order_id });
+        let (status, text) = self
+            .l2_request(Method::DELETE, CLOB_CONSTANTS::POST_ORDER_ENDPOINT, &[], Some(body.to_string()))
+            .await?;
+        if !status.is_success() {
+            return Err(AppError::TradingError(format!("Cancel {} failed: HTTP {} {}", order_id, status, text.trim())));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::transaction::eip712::{Eip712, TypedData};
+    use ethers::types::Signature;
+
+    // Hardhat/anvil account #0 - never holds real funds
+    const TEST_PRIVATE_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    const TEST_ADDRESS: &str = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+    const TEST_TOKEN_ID: &str =
+        "71321045679252212594626385532706912750332728571942532289631379312455583992563";
+    // Signatures of `golden_order(false)` / `golden_order(true)` signed with TEST_PRIVATE_KEY by
+    // clob-client's order builder: `cd TypeScript && npm ci && npm run order-vectors`
+    const GOLDEN_SIGNATURE: &str = "0xce4174c43467456e7257a8d272fe37cf2986433eb6f248a26899fcd9146ada645b759d125eeec684849b28977c352677cf91b326368bf09bafc25aae177ee1e21b";
+    const GOLDEN_SIGNATURE_NEG_RISK: &str = "0xdd7885b3e5a4ed8ad6761ffa5c1e97a261bdbca331ddc78570624ab44fa35300157bc98ee3d48f5775a57cc441daba1aba61f4230ed40bba9c0ae6b9d0f6fb4d1c";
+
+    fn golden_order(neg_risk: bool) -> Order {
+        Order {
+            salt: 479249096354,
+            maker: Address::from_str(TEST_ADDRESS).unwrap(),
+            signer: Address::from_str(TEST_ADDRESS).unwrap(),
+            taker: Address::zero(),
+            token_id: U256::from_dec_str(TEST_TOKEN_ID).unwrap(),
+            maker_amount: U256::from(10_000_000u64),
+            taker_amount: U256::from(20_000_000u64),
+            expiration: U256::zero(),
+            nonce: U256::zero(),
+            fee_rate_bps: U256::zero(),
+            side: Side::Buy,
+            signature_type: SignatureType::Eoa,
+            neg_risk,
+        }
+    }
+
+    /// Same order expressed as eth_signTypedData_v4 JSON (the clob-client input format)
+    fn typed_data(order: &Order) -> TypedData {
+        let verifying_contract = if order.neg_risk {
+            CLOB_CONSTANTS::NEG_RISK_EXCHANGE_ADDRESS
+        } else {
+            CLOB_CONSTANTS::EXCHANGE_ADDRESS
+        };
+        serde_json::from_value(serde_json::json!({
+            "types": {
+                "EIP712Domain": [
+                    {"name": "name", "type": "string"},
+                    {"name": "version", "type": "string"},
+                    {"name": "chainId", "type": "uint256"},
+                    {"name": "verifyingContract", "type": "address"}
+                ],
+                "Order": [
+                    {"name": "salt", "type": "uint256"},
+                    {"name": "maker", "type": "address"},
+                    {"name": "signer", "type": "address"},
+                    {"name": "taker", "type": "address"},
+                    {"name": "tokenId", "type": "uint256"},
+                    {"name": "makerAmount", "type": "uint256"},
+                    {"name": "takerAmount", "type": "uint256"},
+                    {"name": "expiration", "type": "uint256"},
+                    {"name": "nonce", "type": "uint256"},
+                    {"name": "feeRateBps", "type": "uint256"},
+                    {"name": "side", "type": "uint8"},
+                    {"name": "signatureType", "type": "uint8"}
+                ]
+            },
+            "primaryType": "Order",
+            "domain": {
+                "name": "Polymarket CTF Exchange",
+                "version": "1",
+                "chainId": 137,
+                "verifyingContract": verifying_contract
+            },
+            "message": {
+                "salt": order.salt.to_string(),
+                "maker": to_checksum(&order.maker, None),
+                "signer": to_checksum(&order.signer, None),
+                "taker": to_checksum(&order.taker, None),
+                "tokenId": order.token_id.to_string(),
+                "makerAmount": order.maker_amount.to_string(),
+                "takerAmount": order.taker_amount.to_string(),
+                "expiration": order.expiration.to_string(),
+                "nonce": order.nonce.to_string(),
+                "feeRateBps": order.fee_rate_bps.to_string(),
+                "side": order.side.as_u8(),
+                "signatureType": order.signature_type as u8
+            }
+        }))
+        .unwrap()
+    }
+
+    fn test_creds() -> ApiCreds {
+        ApiCreds {
+            api_key: "test-key".to_string(),
+            secret: "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=".to_string(),
+            passphrase: "test-pass".to_string(),
+        }
+    }
+
+    fn test_client(host: &str) -> ClobClient {
+        let mut client =
+            ClobClient::new(host, CLOB_CONSTANTS::CHAIN_ID, TEST_PRIVATE_KEY, SignatureType::Eoa, None, 5000).unwrap();
+        client.set_api_creds(test_creds());
+        client
+    }
+
+    #[test]
+    fn digest_matches_typed_data_encoding() {
+        for neg_risk in [false, true] {
+            let order = golden_order(neg_risk);
+            let expected = typed_data(&order).encode_eip712().unwrap();
+            assert_eq!(order.digest(CLOB_CONSTANTS::CHAIN_ID), expected, "neg_risk={}", neg_risk);
+        }
+    }
+
+    #[test]
+    fn golden_signature_vectors() {
+        // Expected values come from `ExchangeOrderBuilder.buildSignedOrder` (what clob-client's
+        // `createOrder` signs with), not from this client; see TypeScript/src/scripts/orderSignatureVectors.ts
+        let cases = [
+            (false, GOLDEN_SIGNATURE),
+            (true, GOLDEN_SIGNATURE_NEG_RISK),
+        ];
+        let wallet = LocalWallet::from_str(TEST_PRIVATE_KEY).unwrap();
+        for (neg_risk, expected) in cases {
+            let signed = sign_order(&wallet, golden_order(neg_risk), CLOB_CONSTANTS::CHAIN_ID).unwrap();
+            assert_eq!(signed.signature, expected, "neg_risk={}", neg_risk);
+
+            let signature = Signature::from_str(&signed.signature).unwrap();
+            let recovered = signature.recover(H256::from(signed.order.digest(CLOB_CONSTANTS::CHAIN_ID))).unwrap();
+            assert_eq!(recovered, Address::from_str(TEST_ADDRESS).unwrap());
+        }
+    }
+
+
+    #[test]
+    fn market_order_amounts_follow_clob_rounding() {
+        let cases = [
+            // (side, amount, price, tick, maker, taker)
+            (Side::Buy, 10.0, 0.5, 0.01, 10_000_000u64, 20_000_000u64),
+            (Side::Buy, 1.234, 0.33, 0.01, 1_230_000, 3_727_200),
+            (Side::Sell, 15.5, 0.47, 0.01, 15_500_000, 7_285_000),
+            (Side::Buy, 5.0, 0.123, 0.001, 5_000_000, 40_650_400),
+            (Side::Sell, 3.333, 0.6, 0.1, 3_330_000, 1_998_000),
+        ];
+        for (side, amount, price, tick, maker, taker) in cases {
+            let (maker_amount, taker_amount) =
+                market_order_amounts(side, amount, price, round_config(tick)).unwrap();
+            assert_eq!(maker_amount, U256::from(maker), "{:?} {} @ {}", side, amount, price);
+            assert_eq!(taker_amount, U256::from(taker), "{:?} {} @ {}", side, amount, price);
+        }
+    }
+
+    #[test]
+    fn limit_order_amounts_follow_clob_rounding() {
+        let (maker, taker) = limit_order_amounts(Side::Buy, 100.0, 0.56, round_config(0.01)).unwrap();
+        assert_eq!((maker, taker), (U256::from(56_000_000u64), U256::from(100_000_000u64)));
+        let (maker, taker) = limit_order_amounts(Side::Sell, 100.0, 0.56, round_config(0.01)).unwrap();
+        assert_eq!((maker, taker), (U256::from(100_000_000u64), U256::from(56_000_000u64)));
+    }
+
+    #[tokio::test]
+    async fn creates_and_posts_market_order_against_mock_clob() {
+        let mut server = mockito::Server::new_async().await;
+        let tick = server
+            .mock("GET", "/tick-size")
+            .match_query(mockito::Matcher::UrlEncoded("token_id".into(), TEST_TOKEN_ID.into()))
+            .with_body(r#"{"minimum_tick_size":0.01}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let neg_risk = server
+            .mock("GET", "/neg-risk")
+            .match_query(mockito::Matcher::UrlEncoded("token_id".into(), TEST_TOKEN_ID.into()))
+            .with_body(r#"{"neg_risk":true}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let fee_rate = server
+            .mock("GET", "/fee-rate")
+            .match_query(mockito::Matcher::UrlEncoded("token_id".into(), TEST_TOKEN_ID.into()))
+            .with_body(r#"{"base_fee":100}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let post = server
+            .mock("POST", "/order")
+            .match_header("POLY_ADDRESS", TEST_ADDRESS)
+            .match_header("POLY_API_KEY", "test-key")
+            .match_header("POLY_PASSPHRASE", "test-pass")
+            .match_header("POLY_SIGNATURE", mockito::Matcher::Any)
+            .match_header("POLY_TIMESTAMP", mockito::Matcher::Any)
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "orderType": "FOK",
+                "owner": "test-key",
+                "order": {
+                    "maker": TEST_ADDRESS,
+                    "signer": TEST_ADDRESS,
+                    "taker": CLOB_CONSTANTS::ZERO_ADDRESS,
+                    "tokenId": TEST_TOKEN_ID,
+                    "makerAmount": "10000000",
+                    "takerAmount": "20000000",
+                    "side": "BUY",
+                    "signatureType": 0,
+                    "feeRateBps": "100"
+                }
+            })))
+            .with_body(r#"{"success":true,"errorMsg":"","orderID":"0xabc","status":"matched","makingAmount":"10","takingAmount":"20","transactionsHashes":["0xdef"]}"#)
+            .create_async()
+            .await;
+
+        let client = test_client(&format!("{}/", server.url()));
+        let args = MarketOrderArgs {
+            token_id: TEST_TOKEN_ID.to_string(),
+            amount: 10.0,
+            price: 0.5,
+            side: Side::Buy,
+        };
+        let signed = client.create_market_order(&args).await.unwrap();
+        assert!(signed.order.neg_risk);
+        assert_eq!(signed.order.fee_rate_bps, U256::from(100));
+        // Second order hits the tick-size/neg-risk/fee-rate caches
+        client.create_market_order(&args).await.unwrap();
+
+        let response = client.post_order(&signed, OrderType::Fok).await.unwrap();
+        assert!(response.success);
+        assert_eq!(response.order_id, "0xabc");
+        assert_eq!(response.filled_amounts(), Some((10.0, 20.0)));
+        assert_eq!(response.error_message(), None);
+
+        tick.assert_async().await;
+        neg_risk.assert_async().await;
+        fee_rate.assert_async().await;
+        post.assert_async().await;
+    }
+
+    #[test]
+    fn fees_come_from_the_matches_of_an_order() {
+        let trades: Vec<ClobTrade> = serde_json::from_value(serde_json::json!([
+            {
+                "id": "t1", "taker_order_id": "0xours", "side": "BUY", "size": "100", "price": "0.4",
+                "fee_rate_bps": "200", "trader_side": "TAKER",
+                "maker_orders": [{ "order_id": "0xm", "matched_amount": "100", "price": "0.4", "fee_rate_bps": "0" }]
+            },
+            {
+                "id": "t2", "taker_order_id": "0xother", "side": "SELL", "size": "50", "price": "0.7",
+                "fee_rate_bps": "0", "trader_side": "MAKER",
+                "maker_orders": [{ "order_id": "0xours", "matched_amount": "20", "price": "0.7", "fee_rate_bps": 100 }]
+            }
+        ]))
+        .unwrap();
+        // Taker: 2% of min(0.4, 0.6) on 100 tokens; maker: 1% of min(0.7, 0.3) on 20 tokens
+        let (tokens, fee) = order_fills(&trades, "0xours").unwrap();
+        assert_eq!(tokens, 120.0);
+        assert!((fee - (0.8 + 0.06)).abs() < 1e-9, "{}", fee);
+        assert_eq!(order_fills(&trades, "0xm"), Some((100.0, 0.0)));
+        assert_eq!(order_fills(&trades, "0xunknown"), None);
+    }
+
+    #[tokio::test]
+    async fn fetches_order_book_and_picks_best_levels() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/book")
+            .match_query(mockito::Matcher::UrlEncoded("token_id".into(), TEST_TOKEN_ID.into()))
+            .with_body(
+                r#"{"market":"0x1","asset_id":"1","hash":"h","timestamp":"0",
+                    "bids":[{"price":"0.48","size":"10"},{"price":"0.5","size":"3"}],
+                    "asks":[{"price":"0.55","size":"7"},{"price":"0.52","size":"4"}]}"#,
+            )
+            .create_async()
+            .await;
+
+        let client = test_client(&server.url());
+        let book = client.get_order_book(TEST_TOKEN_ID).await.unwrap();
+        let ask = book.best_ask().unwrap();
+        assert_eq!((ask.price(), ask.size()), (0.52, 4.0));
+        let bid = book.best_bid().unwrap();
+        assert_eq!((bid.price(), bid.size()), (0.5, 3.0));
+    }
+
+    #[tokio::test]
+    async fn rejected_order_surfaces_clob_error_message() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/order")
+            .with_status(400)
+            .with_body(r#"{"error":"not enough balance / allowance"}"#)
+            .create_async()
+            .await;
+
+        let client = test_client(&server.url());
+        let wallet = LocalWallet::from_str(TEST_PRIVATE_KEY).unwrap();
+        let signed = sign_order(&wallet, golden_order(false), CLOB_CONSTANTS::CHAIN_ID).unwrap();
+        let response = client.post_order(&signed, OrderType::Fok).await.unwrap();
+        assert!(!response.success);
+        assert_eq!(response.error_message(), Some("not enough balance / allowance"));
+    }
+
+    #[tokio::test]
+    async fn derives_api_key_once_and_caches_it() {
+        let mut server = mockito::Server::new_async().await;
+        let derive = server
+            .mock("GET", "/auth/derive-api-key")
+            .match_header("POLY_ADDRESS", TEST_ADDRESS)
+            .match_header("POLY_NONCE", "0")
+            .match_header("POLY_SIGNATURE", mockito::Matcher::Regex("^0x[0-9a-f]{130}$".to_string()))
+            .with_body(r#"{"apiKey":"derived-key","secret":"c2VjcmV0","passphrase":"derived-pass"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let create = server.mock("POST", "/auth/api-key").expect(0).create_async().await;
+
+        let cache_path = std::env::temp_dir().join(format!("clob-client-creds-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&cache_path);
+        for _ in 0..2 {
+            let mut client =
+                ClobClient::new(&server.url(), CLOB_CONSTANTS::CHAIN_ID, TEST_PRIVATE_KEY, SignatureType::Eoa, None, 5000)
+                    .unwrap();
+            client.create_or_derive_api_creds(&cache_path).await.unwrap();
+            assert_eq!(client.creds.as_ref().unwrap().api_key, "derived-key");
+        }
+        let _ = std::fs::remove_file(&cache_path);
+
+        derive.assert_async().await;
+        create.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn creates_api_key_when_none_can_be_derived() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/auth/derive-api-key")
+            .with_status(400)
+            .with_body(r#"{"error":"Could not derive api key!"}"#)
+            .create_async()
+            .await;
+        let create = server
+            .mock("POST", "/auth/api-key")
+            .with_body(r#"{"apiKey":"new-key","secret":"c2VjcmV0","passphrase":"new-pass"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let cache_path = std::env::temp_dir().join(format!("clob-client-create-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&cache_path);
+        let mut client =
+            ClobClient::new(&server.url(), CLOB_CONSTANTS::CHAIN_ID, TEST_PRIVATE_KEY, SignatureType::Eoa, None, 5000).unwrap();
+        client.create_or_derive_api_creds(&cache_path).await.unwrap();
+        assert_eq!(client.creds.as_ref().unwrap().api_key, "new-key");
+        let _ = std::fs::remove_file(&cache_path);
+
+        create.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn authenticated_requests_require_credentials() {
+        let client =
+            ClobClient::new("http://127.0.0.1:1", CLOB_CONSTANTS::CHAIN_ID, TEST_PRIVATE_KEY, SignatureType::Eoa, None, 5000).unwrap();
+        let wallet = LocalWallet::from_str(TEST_PRIVATE_KEY).unwrap();
+        let signed = sign_order(&wallet, golden_order(false), CLOB_CONSTANTS::CHAIN_ID).unwrap();
+        assert!(matches!(
+            client.post_order(&signed, OrderType::Fok).await,
+            Err(AppError::ConfigurationError(_))
+        ));
+    }
+}
@@ -0,0 +1,466 @@
+// CLOB WebSocket subscriber
+// Streams market channel trade prints for watched assets with heartbeats, reconnect backoff and resubscription
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+use tokio::sync::{mpsc, Notify, RwLock};
+use tokio::time::{interval, sleep, timeout, Duration, Instant};
+use tokio_tungstenite::tungstenite::Message;
+use crate::utils::constants::WS_CONSTANTS;
+use crate::utils::errors::AppError;
+use crate::utils::logger::Logger;
+
+/// Full URL of the market channel, e.g. `wss://ws-subscriptions-clob.polymarket.com/ws/market`
+///
+/// The user channel only reports orders and trades of the API key owner, so followed wallets can
+/// only be observed through market channel trade prints. Those carry no wallet or transaction hash:
+/// they tell us a watched asset traded, not who traded it.
+pub fn market_channel_url(ws_url: &str) -> String {
+    format!("{}/{}", ws_url.trim_end_matches('/'), WS_CONSTANTS::MARKET_CHANNEL)
+}
+
+/// Initial market channel subscription
+pub fn market_subscription_message(asset_ids: &[String]) -> String {
+    serde_json::json!({ "assets_ids": asset_ids, "type": WS_CONSTANTS::MARKET_CHANNEL }).to_string()
+}
+
+/// Add assets to an open market channel subscription
+pub fn market_subscribe_more_message(asset_ids: &[String]) -> String {
+    serde_json::json!({ "assets_ids": asset_ids, "operation": "subscribe" }).to_string()
+}
+
+/// Drop assets from an open market channel subscription
+pub fn market_unsubscribe_message(asset_ids: &[String]) -> String {
+    serde_json::json!({ "assets_ids": asset_ids, "operation": "unsubscribe" }).to_string()
+}
+
+/// A trade printed on the market channel (`last_trade_price` event)
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradePrint {
+    pub asset_id: String,
+    pub market: String,
+    pub price: f64,
+    pub size: f64,
+    pub side: String,
+    pub timestamp: i64,
+}
+
+#[derive(Deserialize)]
+struct RawMarketEvent {
+    #[serde(default)]
+    event_type: String,
+    #[serde(default)]
+    asset_id: String,
+    #[serde(default)]
+    market: String,
+    #[serde(default)]
+    price: Option<serde_json::Value>,
+    #[serde(default)]
+    size: Option<serde_json::Value>,
+    #[serde(default)]
+    side: String,
+    #[serde(default)]
+    timestamp: Option<serde_json::Value>,
+}
+
+/// Numbers arrive as JSON strings ("0.456") or plain numbers
+fn json_number(value: &Option<serde_json::Value>) -> Option<f64> {
+    match value.as_ref()? {
+        serde_json::Value::String(s) => s.parse().ok(),
+        serde_json::Value::Number(n) => n.as_f64(),
+        _ => None,
+    }
+}
+
+impl RawMarketEvent {
+    fn into_trade_print(self) -> Option<TradePrint> {
+        if self.event_type != "last_trade_price" || self.asset_id.is_empty() {
+            return None;
+        }
+        Some(TradePrint {
+            price: json_number(&self.price)?,
+            size: json_number(&self.size).unwrap_or(0.0),
+            timestamp: json_number(&self.timestamp).map(|t| t as i64).unwrap_or(0),
+            asset_id: self.asset_id,
+            market: self.market,
+            side: self.side,
+        })
+    }
+}
+
+/// Extract trade prints from a market channel frame (single event or array of events)
+///
+/// Book snapshots, price changes and heartbeats are ignored.
+pub fn parse_trade_prints(text: &str) -> Vec<TradePrint> {
+    let value: serde_json::Value = match serde_json::from_str(text) {
+        Ok(value) => value,
+        Err(_) => return Vec::new(),
+    };
+    let events = match value {
+        serde_json::Value::Array(events) => events,
+        event @ serde_json::Value::Object(_) => vec![event],
+        _ => return Vec::new(),
+    };
+    events
+        .into_iter()
+        .filter_map(|event| serde_json::from_value::<RawMarketEvent>(event).ok())
+        .filter_map(RawMarketEvent::into_trade_print)
+        .collect()
+}
+
+/// Timing of heartbeats and reconnects
+#[derive(Debug, Clone, Copy)]
+pub struct WsSettings {
+    pub ping_interval: Duration,
+    pub stale_timeout: Duration,
+    pub reconnect_base_delay: Duration,
+    pub reconnect_max_delay: Duration,
+}
+
+impl Default for WsSettings {
+    fn default() -> Self {
+        Self {
+            ping_interval: Duration::from_millis(WS_CONSTANTS::PING_INTERVAL_MS),
+            stale_timeout: Duration::from_millis(WS_CONSTANTS::STALE_TIMEOUT_MS),
+            reconnect_base_delay: Duration::from_millis(WS_CONSTANTS::RECONNECT_BASE_DELAY_MS),
+            reconnect_max_delay: Duration::from_millis(WS_CONSTANTS::RECONNECT_MAX_DELAY_MS),
+        }
+    }
+}
+
+impl WsSettings {
+    /// Exponential backoff for the given number of consecutive failed connections
+    pub fn reconnect_delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.min(16));
+        self.reconnect_base_delay
+            .saturating_mul(factor)
+            .min(self.reconnect_max_delay)
+    }
+}
+
+/// Set of assets the market subscriber should follow
+///
+/// Cloning shares the set; changing it notifies a running subscriber so it can subscribe to new
+/// assets and unsubscribe from dropped ones, and the full set is resubscribed after every reconnect.
+#[derive(Clone, Default)]
+pub struct MarketSubscription {
+    assets: Arc<RwLock<HashSet<String>>>,
+    changed: Arc<Notify>,
+}
+
+impl MarketSubscription {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Watch exactly `asset_ids`, returns whether the set changed
+    pub async fn set<I>(&self, asset_ids: I) -> bool
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let wanted: HashSet<String> = asset_ids.into_iter().filter(|asset| !asset.is_empty()).collect();
+        let mut assets = self.assets.write().await;
+        if *assets == wanted {
+            return false;
+        }
+        *assets = wanted;
+        drop(assets);
+        self.changed.notify_one();
+        true
+    }
+
+    async fn snapshot(&self) -> HashSet<String> {
+        self.assets.read().await.clone()
+    }
+}
+
+/// Why a WebSocket session ended
+struct SessionEnd {
+    received_messages: bool,
+    error: Option<AppError>,
+}
+
+/// Keep a market channel subscription alive until `is_running` is cleared
+///
+/// Trade prints for watched assets are sent to `tx`; the subscriber stops when the receiver is dropped.
+pub async fn run_market_subscriber(
+    ws_url: &str,
+    subscription: MarketSubscription,
+    tx: mpsc::UnboundedSender<TradePrint>,
+    settings: WsSettings,
+    is_running: &AtomicBool,
+) {
+    let url = market_channel_url(ws_url);
+    let mut attempt = 0u32;
+
+    while is_running.load(Ordering::SeqCst) && !tx.is_closed() {
+        // Nothing to subscribe to yet - wait for the first assets
+        if subscription.snapshot().await.is_empty() {
+            let _ = timeout(settings.ping_interval, subscription.changed.notified()).await;
+            continue;
+        }
+
+        let end = match timeout(settings.stale_timeout, tokio_tungstenite::connect_async(url.as_str())).await {
+            Ok(Ok((stream, _))) => run_session(stream, &subscription, &tx, settings, is_running).await,
+            Ok(Err(e)) => SessionEnd {
+                received_messages: false,
+                error: Some(AppError::NetworkError(format!("WebSocket connect failed: {}", e))),
+            },
+            Err(_) => SessionEnd {
+                received_messages: false,
+                error: Some(AppError::NetworkError("WebSocket connect timed out".to_string())),
+            },
+        };
+
+        if !is_running.load(Ordering::SeqCst) || tx.is_closed() {
+            break;
+        }
+        if end.received_messages {
+            attempt = 0;
+        }
+        let delay = settings.reconnect_delay(attempt);
+        attempt = attempt.saturating_add(1);
+        Logger::warning(&format!(
+            "CLOB WebSocket disconnected{} - reconnecting in {:.1}s",
+            end.error.map(|e| format!(" ({})", e)).unwrap_or_default(),
+            delay.as_secs_f64()
+        ));
+        sleep(delay).await;
+    }
+}
+
+async fn run_session<S>(
+    stream: tokio_tungstenite::WebSocketStream<S>,
+    subscription: &MarketSubscription,
+    tx: &mpsc::UnboundedSender<TradePrint>,
+    settings: WsSettings,
+    is_running: &AtomicBool,
+) -> SessionEnd
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    let (mut write, mut read) = stream.split();
+    let mut end = SessionEnd {
+        received_messages: false,
+        error: None,
+    };
+    let send_error = |e: tokio_tungstenite::tungstenite::Error| {
+        Some(AppError::NetworkError(format!("WebSocket send failed: {}", e)))
+    };
+
+    // (Re)subscribe to everything we watch
+    let mut subscribed = subscription.snapshot().await;
+    let assets: Vec<String> = subscribed.iter().cloned().collect();
+    if let Err(e) = write.send(Message::Text(market_subscription_message(&assets))).await {
+        end.error = send_error(e);
+        return end;
+    }
+    Logger::info(&format!("📡 CLOB WebSocket subscribed to {} asset(s)", assets.len()));
+
+    let mut heartbeat = interval(settings.ping_interval);
+    heartbeat.tick().await; // First tick completes immediately
+    let mut last_message = Instant::now();
+
+    loop {
+        tokio::select! {
+            _ = heartbeat.tick() => {
+                if !is_running.load(Ordering::SeqCst) {
+                    let _ = write.send(Message::Close(None)).await;
+                    return end;
+                }
+                if last_message.elapsed() > settings.stale_timeout {
+                    end.error = Some(AppError::NetworkError("heartbeat timed out".to_string()));
+                    return end;
+                }
+                if let Err(e) = write.send(Message::Text("PING".to_string())).await {
+                    end.error = send_error(e);
+                    return end;
+                }
+            }
+            _ = subscription.changed.notified() => {
+                let current = subscription.snapshot().await;
+                let added: Vec<String> = current.difference(&subscribed).cloned().collect();
+                let removed: Vec<String> = subscribed.difference(&current).cloned().collect();
+                let updates = [
+                    (!added.is_empty()).then(|| market_subscribe_more_message(&added)),
+                    (!removed.is_empty()).then(|| market_unsubscribe_message(&removed)),
+                ];
+                for update in updates.into_iter().flatten() {
+                    if let Err(e) = write.send(Message::Text(update)).await {
+                        end.error = send_error(e);
+                        return end;
+                    }
+                }
+                subscribed = current;
+            }
+            message = read.next() => {
+                match message {
+                    Some(Ok(Message::Text(text))) => {
+                        end.received_messages = true;
+                        last_message = Instant::now();
+                        if text == "PONG" {
+                            continue;
+                        }
+                        for print in parse_trade_prints(&text) {
+                            if tx.send(print).is_err() {
+                                return end; // Consumer is gone
+                            }
+                        }
+                    }
+                    Some(Ok(Message::Close(frame))) => {
+                        end.error = Some(AppError::NetworkError(format!(
+                            "closed by server{}",
+                            frame.map(|f| format!(": {}", f.reason)).unwrap_or_default()
+                        )));
+                        return end;
+                    }
+                    Some(Ok(_)) => {
+                        // Binary frames and protocol pings/pongs (pongs are answered by tungstenite)
+                        last_message = Instant::now();
+                    }
+                    Some(Err(e)) => {
+                        end.error = Some(AppError::NetworkError(format!("WebSocket read failed: {}", e)));
+                        return end;
+                    }
+                    None => {
+                        end.error = Some(AppError::NetworkError("stream ended".to_string()));
+                        return end;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    const LAST_TRADE: &str = r#"{"asset_id":"7132","event_type":"last_trade_price","fee_rate_bps":"0","market":"0xabc","price":"0.456","side":"BUY","size":"219.217767","timestamp":"1750428146322"}"#;
+
+    #[test]
+    fn builds_channel_urls() {
+        let base = "wss://ws-subscriptions-clob.polymarket.com/ws";
+        assert_eq!(market_channel_url(base), format!("{}/market", base));
+        assert_eq!(market_channel_url(&format!("{}/", base)), format!("{}/market", base));
+    }
+
+    #[test]
+    fn builds_subscription_messages() {
+        let assets = vec!["1".to_string(), "2".to_string()];
+        let initial: serde_json::Value = serde_json::from_str(&market_subscription_message(&assets)).unwrap();
+        assert_eq!(initial, serde_json::json!({"assets_ids": ["1", "2"], "type": "market"}));
+        let more: serde_json::Value = serde_json::from_str(&market_subscribe_more_message(&assets)).unwrap();
+        assert_eq!(more, serde_json::json!({"assets_ids": ["1", "2"], "operation": "subscribe"}));
+        let fewer: serde_json::Value = serde_json::from_str(&market_unsubscribe_message(&assets)).unwrap();
+        assert_eq!(fewer, serde_json::json!({"assets_ids": ["1", "2"], "operation": "unsubscribe"}));
+    }
+
+    #[test]
+    fn parses_trade_prints_and_ignores_other_frames() {
+        let print = TradePrint {
+            asset_id: "7132".to_string(),
+            market: "0xabc".to_string(),
+            price: 0.456,
+            size: 219.217767,
+            side: "BUY".to_string(),
+            timestamp: 1750428146322,
+        };
+        assert_eq!(parse_trade_prints(LAST_TRADE), vec![print.clone()]);
+
+        let batch = format!(
+            r#"[{{"event_type":"book","asset_id":"7132","bids":[],"asks":[]}},{},{{"event_type":"price_change","asset_id":"7132"}}]"#,
+            LAST_TRADE
+        );
+        assert_eq!(parse_trade_prints(&batch), vec![print]);
+
+        for frame in ["PONG", "[]", "{}", "not json", r#"{"event_type":"last_trade_price","asset_id":"1"}"#] {
+            assert!(parse_trade_prints(frame).is_empty(), "{}", frame);
+        }
+    }
+
+    #[test]
+    fn reconnect_delay_backs_off_exponentially() {
+        let settings = WsSettings::default();
+        let cases = [(0, 1_000), (1, 2_000), (2, 4_000), (4, 16_000), (5, 30_000), (40, 30_000)];
+        for (attempt, expected_ms) in cases {
+            assert_eq!(settings.reconnect_delay(attempt), Duration::from_millis(expected_ms), "attempt {}", attempt);
+        }
+    }
+
+    #[tokio::test]
+    async fn streams_prints_and_resubscribes_after_reconnect() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let ws_url = format!("ws://{}/ws", listener.local_addr().unwrap());
+
+        let server = tokio::spawn(async move {
+            let mut subscriptions = Vec::new();
+            for connection in 0..2 {
+                let (tcp, _) = listener.accept().await.unwrap();
+                let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+                let Some(Ok(Message::Text(text))) = ws.next().await else { panic!("expected subscription") };
+                subscriptions.push(serde_json::from_str::<serde_json::Value>(&text).unwrap());
+                ws.send(Message::Text(LAST_TRADE.to_string())).await.unwrap();
+                if connection == 0 {
+                    // Drop the first connection to force a reconnect
+                    ws.close(None).await.unwrap();
+                } else {
+                    // Heartbeat and subscription updates keep flowing on the second connection
+                    while subscriptions.len() < 4 {
+                        match ws.next().await {
+                            Some(Ok(Message::Text(text))) if text == "PING" => {
+                                ws.send(Message::Text("PONG".to_string())).await.unwrap();
+                            }
+                            Some(Ok(Message::Text(text))) => {
+                                subscriptions.push(serde_json::from_str::<serde_json::Value>(&text).unwrap());
+                            }
+                            other => panic!("unexpected frame: {:?}", other),
+                        }
+                    }
+                }
+            }
+            subscriptions
+        });
+
+        let subscription = MarketSubscription::new();
+        assert!(subscription.set(vec!["7132".to_string(), "7132".to_string()]).await);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let settings = WsSettings {
+            ping_interval: Duration::from_millis(50),
+            stale_timeout: Duration::from_secs(5),
+            reconnect_base_delay: Duration::from_millis(10),
+            reconnect_max_delay: Duration::from_millis(50),
+        };
+        static RUNNING: AtomicBool = AtomicBool::new(true);
+        let subscriber = {
+            let subscription = subscription.clone();
+            tokio::spawn(async move {
+                run_market_subscriber(&ws_url, subscription, tx, settings, &RUNNING).await;
+            })
+        };
+
+        let first = timeout(Duration::from_secs(5), rx.recv()).await.unwrap().unwrap();
+        assert_eq!(first.asset_id, "7132");
+        let second = timeout(Duration::from_secs(5), rx.recv()).await.unwrap().unwrap();
+        assert_eq!(second.price, 0.456);
+
+        // Swapping the watched assets updates the live subscription both ways
+        sleep(Duration::from_millis(120)).await;
+        assert!(subscription.set(vec!["999".to_string()]).await);
+
+        let subscriptions = timeout(Duration::from_secs(5), server).await.unwrap().unwrap();
+        RUNNING.store(false, Ordering::SeqCst);
+        drop(rx);
+        let _ = timeout(Duration::from_secs(5), subscriber).await;
+
+        let expected_initial = serde_json::json!({"assets_ids": ["7132"], "type": "market"});
+        assert_eq!(subscriptions[0], expected_initial);
+        assert_eq!(subscriptions[1], expected_initial); // resubscribed after reconnect
+        assert_eq!(subscriptions[2], serde_json::json!({"assets_ids": ["999"], "operation": "subscribe"}));
+        assert_eq!(subscriptions[3], serde_json::json!({"assets_ids": ["7132"], "operation": "unsubscribe"}));
+    }
+}
@@ -1,13 +1,10 @@
 // CLOB client creation and management
-// TODO: Implement Polymarket CLOB client
-// Note: This requires implementing the Polymarket CLOB client in Rust
-// The TypeScript version uses @polymarket/clob-client which doesn't have a Rust equivalent
 
 use crate::config::env::ENV;
+use crate::utils::constants::CLOB_CONSTANTS;
 use crate::utils::logger::Logger;
 
-// Placeholder type - needs proper CLOB client implementation
-pub type ClobClient = ();
+pub use crate::services::clob_client::{ClobClient, SignatureType};
 
 /// Check if a wallet is a Gnosis Safe by checking if it has contract code
 async fn is_gnosis_safe(address: &str) -> bool {
@@ -37,19 +34,32 @@ async fn is_gnosis_safe(address: &str) -> bool {
 
 /// Create and initialize CLOB client
 pub async fn create_clob_client() -> Result<ClobClient, Box<dyn std::error::Error>> {
-    // TODO: Implement full CLOB client creation matching TypeScript version
-    // This includes:
-    // 1. Create ethers wallet from private key
-    // 2. Initialize ClobClient with chain ID, host, wallet, and proxy wallet
-    // 3. Create or derive API key
-    // 4. Return configured client
-    
     // Detect if the proxy wallet is a Gnosis Safe or EOA (like PythonVersion)
     let is_proxy_safe = is_gnosis_safe(&ENV().proxy_wallet).await;
     let wallet_type = if is_proxy_safe { "Gnosis Safe" } else { "EOA (Externally Owned Account)" };
     Logger::info(&format!("Wallet type detected: {}", wallet_type));
     
-    // Placeholder - return empty tuple for now
-    // In production, this should return a proper ClobClient struct
-    Ok(())
+    // A Safe holds the funds and the EOA signs on its behalf; an EOA is both maker and signer
+    let (signature_type, funder) = if is_proxy_safe {
+        (SignatureType::PolyGnosisSafe, Some(ENV().proxy_wallet.as_str()))
+    } else {
+        (SignatureType::Eoa, None)
+    };
+    
+    let mut clob_client = ClobClient::new(
+        &ENV().clob_http_url,
+        CLOB_CONSTANTS::CHAIN_ID,
+        &ENV().private_key,
+        signature_type,
+        funder,
+        ENV().request_timeout_ms,
+    )?;
+    
+    // L2 credentials: cached file → derive existing key → create new key
+    clob_client
+        .create_or_derive_api_creds(std::path::Path::new(&ENV().clob_creds_file))
+        .await?;
+    Logger::info("CLOB API credentials ready");
+    
+    Ok(clob_client)
 }
@@ -0,0 +1,339 @@
+// Startup recovery of trades interrupted mid-execution
+// A crash leaves trades Claimed or Submitted; what their orders actually filled is read back from the CLOB
+
+use std::collections::HashSet;
+use chrono::Utc;
+use crate::config::env::ENV;
+use crate::interfaces::execution::ExecutionStatus;
+use crate::interfaces::ledger::{FillRecord, OrderRecord};
+use crate::interfaces::user::UserActivityInterface;
+use crate::services::clob_client::{match_fee, ClobTrade};
+use crate::services::create_clob_client::ClobClient;
+use crate::storage::{storage, TradeKey, TradeUpdate};
+use crate::utils::constants::DB_FIELDS;
+use crate::utils::errors::AppResult;
+use crate::utils::logger::Logger;
+use crate::utils::post_order::reduce_tracked_purchases;
+
+/// `trader_side` of fills where our order took liquidity (all copies are FOK market orders)
+const TAKER: &str = "TAKER";
+
+/// Token amounts below this are rounding noise
+const EPSILON: f64 = 1e-6;
+
+/// Tokens one of our orders matched and the USDC they traded for
+#[derive(Debug, Clone, PartialEq)]
+struct OrderMatch {
+    order_id: String,
+    tokens: f64,
+    usdc: f64,
+    /// Fee charged on the `fee_tokens` of it seen in our trades
+    fee: f64,
+    fee_tokens: f64,
+}
+
+/// What an interrupted trade matched, per order: its journaled orders plus our fills of the
+/// same token and side after it was claimed that belong to no known order
+///
+/// Fills in `used` were already attributed to another interrupted trade and are skipped; the
+/// ones seen here are added to it.
+fn matched_orders(
+    side: &str,
+    known_orders: &[OrderMatch],
+    fills: &[ClobTrade],
+    used: &mut HashSet<String>,
+) -> Vec<OrderMatch> {
+    let mut matches = known_orders.to_vec();
+    for fill in fills {
+        if fill.trader_side != TAKER || fill.side != side || !used.insert(fill.id.clone()) {
+            continue;
+        }
+        let (tokens, fee) = fill.order_fill(&fill.taker_order_id).unwrap_or_default();
+        // Fills of journaled orders are already counted through the order, apart from their fees
+        let known = known_orders.iter().any(|order| order.order_id == fill.taker_order_id);
+        match matches.iter_mut().find(|order| order.order_id == fill.taker_order_id) {
+            Some(order) => {
+                if !known {
+                    order.tokens += tokens;
+                    order.usdc += tokens * fill.price();
+                }
+                order.fee += fee;
+                order.fee_tokens += tokens;
+            }
+            None => matches.push(OrderMatch {
+                order_id: fill.taker_order_id.clone(),
+                tokens,
+                usdc: tokens * fill.price(),
+                fee,
+                fee_tokens: tokens,
+            }),
+        }
+    }
+    matches
+}
+
+/// Ledger fills for what `matches` matched beyond the fills already `recorded` for the trade
+///
+/// A crash between an order's response and its ledger write leaves the fill unrecorded. Fees are
+/// those our trades show for the order, or its signed rate from the order ledger if none do.
+fn missing_fills(
+    trade: &UserActivityInterface,
+    trader_address: &str,
+    matches: &[OrderMatch],
+    orders: &[OrderRecord],
+    recorded: &[FillRecord],
+    now_ms: i64,
+) -> Vec<FillRecord> {
+    matches
+        .iter()
+        .filter_map(|order| {
+            let recorded_tokens: f64 =
+                recorded.iter().filter(|fill| fill.order_id == order.order_id).map(|fill| fill.size).sum();
+            let tokens = order.tokens - recorded_tokens;
+            if tokens <= EPSILON {
+                return None;
+            }
+            let price = order.usdc / order.tokens;
+            let usdc = tokens * price;
+            let fee = if order.fee_tokens > EPSILON {
+                order.fee * tokens / order.fee_tokens
+            } else {
+                let fee_rate_bps = orders
+                    .iter()
+                    .find(|record| record.order_id == order.order_id)
+                    .map_or(0.0, |record| record.fee_rate_bps);
+                match_fee(fee_rate_bps, price, tokens)
+            };
+            Some(FillRecord {
+                order_id: order.order_id.clone(),
+                trader_address: trader_address.to_string(),
+                source_transaction_hash: trade.transaction_hash.clone(),
+                asset: trade.asset.clone(),
+                condition_id: trade.condition_id.clone(),
+                event_slug: trade.event_slug.clone(),
+                side: trade.side.clone(),
+                price,
+                size: tokens,
+                usdc,
+                fee,
+                transaction_hashes: Vec::new(),
+                paper: false,
+                trigger: None,
+                filled_at: now_ms,
+                latency_ms: now_ms - trade.timestamp * 1000,
+            })
+        })
+        .collect()
+}
+
+/// Record in the fill ledger what an interrupted trade matched but the ledger does not show
+async fn record_missing_fills(
+    trader_address: &str,
+    trade: &UserActivityInterface,
+    matches: &[OrderMatch],
+) -> AppResult<()> {
+    let orders = storage().orders_for_trade(trader_address, &trade.transaction_hash).await?;
+    let recorded = storage().fills_for_trade(trader_address, &trade.transaction_hash).await?;
+    let now_ms = Utc::now().timestamp_millis();
+    for fill in missing_fills(trade, trader_address, matches, &orders, &recorded, now_ms) {
+        storage().record_fill(&fill).await?;
+    }
+    Ok(())
+}
+
+/// Final status of an interrupted trade given what its orders matched (`None` if unknown)
+///
+/// Interrupted trades are never re-executed since the price has moved on since the trader's
+/// fill. Anything matched counts as a partial fill because the copy may have stopped short.
+fn reconcile(trade: &UserActivityInterface, matched: Option<f64>) -> TradeUpdate {
+    match matched {
+        Some(tokens) if tokens > 0.0 => TradeUpdate {
+            bought_tokens: (trade.side == DB_FIELDS::SIDE_BUY).then_some(tokens),
+            ..TradeUpdate::new(ExecutionStatus::PartiallyFilled)
+        },
+        Some(_) => TradeUpdate::new(ExecutionStatus::Failed {
+            error: "interrupted before any order filled".to_string(),
+        }),
+        None => TradeUpdate::new(ExecutionStatus::PartiallyFilled),
+    }
+}
+
+/// Look up what an interrupted trade's orders matched
+async fn lookup_matched(
+    clob_client: &ClobClient,
+    trade: &UserActivityInterface,
+    used: &mut HashSet<String>,
+) -> AppResult<Option<Vec<OrderMatch>>> {
+    let execution = &trade.execution;
+    // Paper orders only exist in the paper wallet; accepted ones filled, by an unknown amount
+    if ENV().dry_run {
+        return Ok(execution.order_ids.is_empty().then(Vec::new));
+    }
+
+    let mut known_orders = Vec::new();
+    for order_id in &execution.order_ids {
+        let (tokens, usdc) = match clob_client.get_order(order_id).await? {
+            Some(order) => (order.matched_size(), order.matched_size() * order.price.parse::<f64>().unwrap_or(0.0)),
+            None => (0.0, 0.0),
+        };
+        known_orders.push(OrderMatch { order_id: order_id.clone(), tokens, usdc, fee: 0.0, fee_tokens: 0.0 });
+    }
+    // Orders accepted after the last journaled one only show up as fills
+    let claimed_at = execution.claimed_at.map(|ms| ms / 1000).unwrap_or(trade.timestamp);
+    let fills = clob_client.get_trades(&trade.asset, claimed_at).await?;
+    Ok(Some(matched_orders(&trade.side, &known_orders, &fills, used)))
+}
+
+/// Settle every trade left Claimed or Submitted by a previous run
+///
+/// Runs before the executor starts. Trades whose orders cannot be looked up stay in flight and
+/// are retried on the next start.
+pub async fn recover_interrupted_trades(clob_client: &ClobClient) {
+    let mut recovered = 0;
+    for address in &ENV().user_addresses {
+        let trades = match storage().in_flight_trades(address).await {
+            Ok(trades) => trades,
+            Err(e) => {
+                Logger::error(&format!("Failed to load interrupted trades of {}: {}", address, e));
+                continue;
+            }
+        };
+
+        let mut used = HashSet::new();
+        for trade in trades {
+            let matches = match lookup_matched(clob_client, &trade, &mut used).await {
+                Ok(matches) => matches,
+                Err(e) => {
+                    Logger::warning(&format!(
+                        "Cannot reconcile interrupted trade {} yet: {}",
+                        trade.transaction_hash, e
+                    ));
+                    continue;
+                }
+            };
+            if let Some(matches) = &matches {
+                if let Err(e) = record_missing_fills(address, &trade, matches).await {
+                    Logger::warning(&format!(
+                        "Cannot record the fills of interrupted trade {} yet: {}",
+                        trade.transaction_hash, e
+                    ));
+                    continue;
+                }
+            }
+            let matched = matches.map(|matches| matches.iter().map(|order| order.tokens).sum::<f64>());
+            let update = reconcile(&trade, matched);
+            Logger::info(&format!(
+                "Interrupted trade {} ({}) settled as {}{}",
+                trade.transaction_hash,
+                trade.execution.status.as_str(),
+                update.status.as_str(),
+                matched.map(|tokens| format!(" ({:.2} tokens matched)", tokens)).unwrap_or_default()
+            ));
+            if let Some(sold) = matched.filter(|tokens| *tokens > 0.0 && trade.side == DB_FIELDS::SIDE_SELL) {
+                // Same reduction as a SELL that completed
+                match storage().tracked_purchases(address, &trade.asset, &trade.condition_id).await {
+                    Ok(purchases) => reduce_tracked_purchases(address, &trade, sold, purchases.iter().sum()).await,
+                    Err(e) => {
+                        Logger::warning(&format!(
+                            "Cannot reduce tracked purchases for interrupted sell {} yet: {}",
+                            trade.transaction_hash, e
+                        ));
+                        continue;
+                    }
+                }
+            }
+            match storage().update_trade(address, TradeKey::of(&trade), update).await {
+                Ok(()) => recovered += 1,
+                Err(e) => Logger::error(&format!("Failed to journal trade {}: {}", trade.transaction_hash, e)),
+            }
+        }
+    }
+    if recovered > 0 {
+        Logger::success(&format!("Reconciled {} interrupted trade(s) with the CLOB", recovered));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(id: &str, order: &str, side: &str, trader_side: &str, size: &str) -> ClobTrade {
+        ClobTrade {
+            id: id.to_string(),
+            taker_order_id: order.to_string(),
+            side: side.to_string(),
+            trader_side: trader_side.to_string(),
+            size: size.to_string(),
+            price: "0.5".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn order_match(order_id: &str, tokens: f64, usdc: f64) -> OrderMatch {
+        OrderMatch { order_id: order_id.to_string(), tokens, usdc, fee: 0.0, fee_tokens: 0.0 }
+    }
+
+    #[test]
+    fn counts_known_orders_and_unjournaled_fills_once() {
+        let known = vec![order_match("0xa", 10.0, 6.0)];
+        let charged = ClobTrade { fee_rate_bps: "200".to_string(), ..fill("2", "0xb", "BUY", TAKER, "4") };
+        let fills = vec![
+            fill("1", "0xa", "BUY", TAKER, "10"),
+            charged,
+            fill("5", "0xb", "BUY", TAKER, "2"),
+            fill("3", "0xc", "SELL", TAKER, "7"),
+            fill("4", "0xd", "BUY", "MAKER", "9"),
+        ];
+        let mut used = HashSet::new();
+        let matches = matched_orders("BUY", &known, &fills, &mut used);
+        // 2% of min(0.5, 0.5) on the 4 tokens of fill 2
+        let with_fees = |order: OrderMatch, fee: f64, fee_tokens: f64| OrderMatch { fee, fee_tokens, ..order };
+        assert_eq!(
+            matches,
+            vec![
+                with_fees(order_match("0xa", 10.0, 6.0), 0.0, 10.0),
+                with_fees(order_match("0xb", 6.0, 3.0), 0.04, 6.0),
+            ]
+        );
+        // A second interrupted trade on the same token cannot claim the same fill
+        assert!(matched_orders("BUY", &[], &fills, &mut used).is_empty());
+    }
+
+    #[test]
+    fn records_only_fills_missing_from_the_ledger() {
+        let trade: UserActivityInterface = serde_json::from_value(serde_json::json!({
+            "timestamp": 100, "side": "BUY", "asset": "111", "conditionId": "0xc", "transactionHash": "0x01"
+        }))
+        .unwrap();
+        // 0xd's fee is known from our trades, 0xb only has its signed rate
+        let charged = OrderMatch { fee: 0.1, fee_tokens: 4.0, ..order_match("0xd", 4.0, 2.0) };
+        let matches = vec![order_match("0xa", 10.0, 6.0), order_match("0xb", 6.0, 3.0), charged];
+        let orders = vec![
+            OrderRecord { order_id: "0xb".to_string(), fee_rate_bps: 100.0, ..Default::default() },
+            OrderRecord { order_id: "0xd".to_string(), fee_rate_bps: 1000.0, ..Default::default() },
+        ];
+        let recorded = vec![FillRecord { order_id: "0xa".to_string(), size: 10.0, ..Default::default() }];
+
+        let fills = missing_fills(&trade, "0xtrader", &matches, &orders, &recorded, 200_000);
+        assert_eq!(fills.len(), 2);
+        assert_eq!((fills[1].order_id.as_str(), fills[1].fee), ("0xd", 0.1));
+        let fill = &fills[0];
+        assert_eq!((fill.order_id.as_str(), fill.source_transaction_hash.as_str()), ("0xb", "0x01"));
+        assert_eq!((fill.asset.as_str(), fill.condition_id.as_str(), fill.side.as_str()), ("111", "0xc", "BUY"));
+        assert_eq!((fill.size, fill.usdc, fill.price, fill.fee), (6.0, 3.0, 0.5, 0.03));
+        assert_eq!((fill.filled_at, fill.latency_ms), (200_000, 100_000));
+    }
+
+    #[test]
+    fn interrupted_trades_are_settled_not_retried() {
+        let mut trade: UserActivityInterface =
+            serde_json::from_value(serde_json::json!({ "timestamp": 1, "side": "BUY" })).unwrap();
+        let update = reconcile(&trade, Some(12.5));
+        assert_eq!((update.status, update.bought_tokens), (ExecutionStatus::PartiallyFilled, Some(12.5)));
+        assert!(matches!(reconcile(&trade, Some(0.0)).status, ExecutionStatus::Failed { .. }));
+
+        trade.side = "SELL".to_string();
+        assert_eq!(reconcile(&trade, Some(3.0)).bought_tokens, None);
+        assert_eq!(reconcile(&trade, None).status, ExecutionStatus::PartiallyFilled);
+    }
+}
@@ -0,0 +1,222 @@
+// Exit manager
+// Stop-loss, take-profit and trailing-stop exits on copied positions, checked against our own prices
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use crate::config::env::{var, ENV};
+use crate::interfaces::user::UserActivityInterface;
+use crate::services::create_clob_client::ClobClient;
+use crate::services::pnl::{ledger_engine, OpenLot};
+use crate::services::trade_pipeline::fetch_my_portfolio;
+use crate::utils::constants::{DB_FIELDS, TRADING_CONSTANTS};
+use crate::utils::logger::Logger;
+use crate::utils::post_order::exit_position;
+
+/// Threshold that fired an exit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitTrigger {
+    StopLoss,
+    TakeProfit,
+    TrailingStop,
+}
+
+impl ExitTrigger {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExitTrigger::StopLoss => "STOP_LOSS",
+            ExitTrigger::TakeProfit => "TAKE_PROFIT",
+            ExitTrigger::TrailingStop => "TRAILING_STOP",
+        }
+    }
+}
+
+impl fmt::Display for ExitTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Exit thresholds in percent of the entry price; unset thresholds never fire
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ExitThresholds {
+    /// Sell once the price is this far below the average entry price
+    pub stop_loss_percent: Option<f64>,
+    /// Sell once the price is this far above the average entry price
+    pub take_profit_percent: Option<f64>,
+    /// Sell once the price is this far below its highest level since entry
+    pub trailing_stop_percent: Option<f64>,
+}
+
+impl ExitThresholds {
+    pub fn from_env() -> Self {
+        let parsed = |key: &str| var(key).ok().and_then(|v| v.trim().parse::<f64>().ok());
+        Self {
+            stop_loss_percent: parsed("STOP_LOSS_PERCENT"),
+            take_profit_percent: parsed("TAKE_PROFIT_PERCENT"),
+            trailing_stop_percent: parsed("TRAILING_STOP_PERCENT"),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.stop_loss_percent.is_some() || self.take_profit_percent.is_some() || self.trailing_stop_percent.is_some()
+    }
+
+    /// Threshold crossed by `price`, given the average entry price and the highest price seen
+    pub fn trigger(&self, entry_price: f64, price: f64, peak_price: f64) -> Option<ExitTrigger> {
+        if entry_price <= 0.0 {
+            return None;
+        }
+        let change_percent = (price - entry_price) / entry_price * 100.0;
+        if self.stop_loss_percent.is_some_and(|percent| change_percent <= -percent) {
+            return Some(ExitTrigger::StopLoss);
+        }
+        if self.take_profit_percent.is_some_and(|percent| change_percent >= percent) {
+            return Some(ExitTrigger::TakeProfit);
+        }
+        if self
+            .trailing_stop_percent
+            .is_some_and(|percent| peak_price > 0.0 && price <= peak_price * (1.0 - percent / 100.0))
+        {
+            return Some(ExitTrigger::TrailingStop);
+        }
+        None
+    }
+}
+
+/// Highest price seen per (trader, asset) lot since the exit manager started watching it
+static PEAKS: Mutex<Option<HashMap<(String, String), f64>>> = Mutex::new(None);
+
+/// Record `price` for the lot and return its peak, starting from the entry price
+fn update_peak(lot: &OpenLot, entry_price: f64, price: f64) -> f64 {
+    let mut peaks = PEAKS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let peak = peaks
+        .get_or_insert_with(HashMap::new)
+        .entry((lot.trader_address.clone(), lot.asset.clone()))
+        .or_insert(entry_price);
+    *peak = peak.max(price);
+    *peak
+}
+
+fn forget_peak(lot: &OpenLot) {
+    let mut peaks = PEAKS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(peaks) = peaks.as_mut() {
+        peaks.remove(&(lot.trader_address.clone(), lot.asset.clone()));
+    }
+}
+
+/// Current price of our position in `asset`: `curPrice` from the positions endpoint, or the best
+/// bid when paper trading (paper positions are valued at cost)
+async fn current_price(clob_client: &ClobClient, asset: &str, cur_price: f64) -> Option<f64> {
+    if !ENV().dry_run {
+        return Some(cur_price).filter(|price| *price > 0.0);
+    }
+    let book = clob_client.get_order_book(asset).await.ok()?;
+    book.best_bid().map(|bid| bid.price())
+}
+
+/// Sell every copied lot whose followed trader's thresholds are crossed
+///
+/// Lots come from the fill ledger, so each exit is attributed to the trader whose copies bought
+/// the tokens and checked against that trader's thresholds.
+pub async fn check_exits(clob_client: &ClobClient) {
+    let trader_configs = &ENV().trader_configs;
+    if !trader_configs.has_exits() {
+        return;
+    }
+    let positions = match fetch_my_portfolio().await {
+        Ok((positions, _)) => positions,
+        Err(e) => {
+            Logger::warning(&format!("Exit check skipped: failed to fetch positions: {}", e));
+            return;
+        }
+    };
+    let lots = match ledger_engine().await {
+        Ok((engine, _)) => engine.open_lots(),
+        Err(e) => {
+            Logger::warning(&format!("Exit check skipped: fill ledger unavailable: {}", e));
+            return;
+        }
+    };
+
+    let mut prices: HashMap<String, Option<f64>> = HashMap::new();
+    for lot in &lots {
+        let thresholds = trader_configs.for_trader(&lot.trader_address).exits;
+        let Some(position) = positions.iter().find(|pos| pos.asset == lot.asset) else {
+            // Redeemed or sold outside the bot
+            forget_peak(lot);
+            continue;
+        };
+        if !thresholds.is_enabled() {
+            continue;
+        }
+        let price = match prices.get(&lot.asset) {
+            Some(price) => *price,
+            None => {
+                let price = current_price(clob_client, &lot.asset, position.cur_price).await;
+                prices.insert(lot.asset.clone(), price);
+                price
+            }
+        };
+        let Some(price) = price else { continue };
+
+        let entry_price = lot.cost / lot.tokens;
+        let peak_price = update_peak(lot, entry_price, price);
+        let Some(trigger) = thresholds.trigger(entry_price, price, peak_price) else {
+            continue;
+        };
+        let tokens = lot.tokens.min(position.size);
+        if tokens < TRADING_CONSTANTS::MIN_ORDER_SIZE_TOKENS {
+            continue;
+        }
+
+        Logger::clear_line();
+        Logger::warning(&format!(
+            "🚪 {} on {} copied from {}: ${:.3} vs entry ${:.3} (peak ${:.3}) - selling {:.2} tokens",
+            trigger,
+            if position.slug.is_empty() { &lot.asset } else { &position.slug },
+            Logger::format_address(&lot.trader_address),
+            price,
+            entry_price,
+            peak_price,
+            tokens
+        ));
+        let exit = UserActivityInterface {
+            timestamp: chrono::Utc::now().timestamp(),
+            asset: lot.asset.clone(),
+            condition_id: lot.condition_id.clone(),
+            slug: position.slug.clone(),
+            event_slug: lot.event_slug.clone(),
+            side: DB_FIELDS::SIDE_SELL.to_string(),
+            ..Default::default()
+        };
+        let sold = exit_position(clob_client, &lot.trader_address, &exit, tokens, trigger.as_str()).await;
+        if sold > 0.0 {
+            forget_peak(lot);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thresholds_fire_on_entry_and_peak_moves() {
+        let thresholds = ExitThresholds {
+            stop_loss_percent: Some(20.0),
+            take_profit_percent: Some(50.0),
+            trailing_stop_percent: Some(10.0),
+        };
+        assert_eq!(thresholds.trigger(0.50, 0.46, 0.50), None);
+        assert_eq!(thresholds.trigger(0.50, 0.39, 0.50), Some(ExitTrigger::StopLoss));
+        assert_eq!(thresholds.trigger(0.50, 0.76, 0.76), Some(ExitTrigger::TakeProfit));
+        // Up 28% from entry, but more than 10% off the 0.72 peak
+        assert_eq!(thresholds.trigger(0.50, 0.64, 0.72), Some(ExitTrigger::TrailingStop));
+        assert_eq!(thresholds.trigger(0.50, 0.65, 0.72), None);
+
+        let stop_only = ExitThresholds { stop_loss_percent: Some(20.0), ..Default::default() };
+        assert_eq!(stop_only.trigger(0.50, 0.99, 0.99), None);
+        assert!(!ExitThresholds::default().is_enabled());
+    }
+}
@@ -0,0 +1,323 @@
+// Kill switch
+// Halts new BUYs when the UTC day's loss or the drawdown from the equity high-water mark passes its limit
+
+use std::fmt;
+use std::path::Path;
+use std::sync::RwLock;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use crate::config::env::ENV;
+use crate::services::create_clob_client::ClobClient;
+use crate::services::risk_manager::RiskConfig;
+use crate::services::trade_pipeline::fetch_my_portfolio;
+use crate::utils::errors::{AppError, AppResult};
+use crate::utils::logger::Logger;
+use crate::utils::post_order::flatten_positions;
+
+/// Limit that halted BUYs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum HaltTrigger {
+    /// MAX_DAILY_LOSS_USD
+    DailyLoss,
+    /// MAX_DRAWDOWN_PERCENT
+    Drawdown,
+}
+
+impl HaltTrigger {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HaltTrigger::DailyLoss => "DAILY_LOSS",
+            HaltTrigger::Drawdown => "DRAWDOWN",
+        }
+    }
+}
+
+impl fmt::Display for HaltTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Why and when BUYs were halted
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Halt {
+    pub trigger: HaltTrigger,
+    pub reason: String,
+    /// Equity when the limit was crossed
+    pub equity: f64,
+    /// Unix milliseconds
+    pub halted_at: i64,
+}
+
+/// Kill switch state, persisted in KILL_SWITCH_FILE so a halt survives restarts
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KillSwitchState {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub halt: Option<Halt>,
+    /// Highest equity seen; 0 until the next check sets it
+    #[serde(default)]
+    pub high_water_mark: f64,
+    /// UTC day (`YYYY-MM-DD`) of `day_start_equity`
+    #[serde(default)]
+    pub day: String,
+    /// Equity at the first check of `day`
+    #[serde(default)]
+    pub day_start_equity: f64,
+}
+
+impl KillSwitchState {
+    /// State from `path`; a missing file means no halt and no baselines yet
+    pub fn load(path: &str) -> AppResult<Self> {
+        if !Path::new(path).exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| AppError::ConfigurationError(format!("Cannot read {}: {}", path, e)))?;
+        serde_json::from_str(&contents)
+            .map_err(|e| AppError::ConfigurationError(format!("Invalid kill switch state in {}: {}", path, e)))
+    }
+
+    /// Write the state to `path` through a temporary file, so readers never see a partial file
+    pub fn save(&self, path: &str) -> AppResult<()> {
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| AppError::Unknown(format!("Failed to serialize kill switch state: {}", e)))?;
+        let temp_path = format!("{}.tmp", path);
+        std::fs::write(&temp_path, contents)
+            .and_then(|()| std::fs::rename(&temp_path, path))
+            .map_err(|e| AppError::ConfigurationError(format!("Failed to write {}: {}", path, e)))
+    }
+
+    /// Update the baselines with `equity` on `day` and halt if a limit is crossed
+    ///
+    /// Returns the halt only when this observation caused it.
+    pub fn observe(&mut self, equity: f64, day: &str, limits: &RiskConfig, now_ms: i64) -> Option<Halt> {
+        if self.day != day {
+            self.day = day.to_string();
+            self.day_start_equity = equity;
+        }
+        self.high_water_mark = self.high_water_mark.max(equity);
+        if self.halt.is_some() {
+            return None;
+        }
+
+        let daily_loss = self.day_start_equity - equity;
+        let drawdown_percent = if self.high_water_mark > 0.0 {
+            (self.high_water_mark - equity) / self.high_water_mark * 100.0
+        } else {
+            0.0
+        };
+        let (trigger, reason) = match (limits.max_daily_loss_usd, limits.max_drawdown_percent) {
+            (Some(max_loss), _) if daily_loss > max_loss => (
+                HaltTrigger::DailyLoss,
+                format!("lost ${:.2} since the start of {} UTC (limit ${:.2})", daily_loss, self.day, max_loss),
+            ),
+            (_, Some(max_drawdown)) if drawdown_percent > max_drawdown => (
+                HaltTrigger::Drawdown,
+                format!(
+                    "equity ${:.2} is {:.1}% below its high of ${:.2} (limit {:.1}%)",
+                    equity, drawdown_percent, self.high_water_mark, max_drawdown
+                ),
+            ),
+            _ => return None,
+        };
+        let halt = Halt { trigger, reason, equity, halted_at: now_ms };
+        self.halt = Some(halt.clone());
+        Some(halt)
+    }
+
+    /// Clear the halt; the next check starts the high-water mark and the day's loss from scratch
+    pub fn resume(&mut self) -> Option<Halt> {
+        self.high_water_mark = 0.0;
+        self.day.clear();
+        self.day_start_equity = 0.0;
+        self.halt.take()
+    }
+}
+
+/// Halt in force, shared with the order path
+static HALT: RwLock<Option<Halt>> = RwLock::new(None);
+
+fn set_halt(halt: Option<Halt>) {
+    *HALT.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = halt;
+}
+
+/// Why new BUYs are halted, if they are
+pub fn buying_halted() -> Option<Halt> {
+    HALT.read().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+}
+
+/// Pick up a halt left by a previous run
+pub fn init_kill_switch() -> AppResult<()> {
+    let state = KillSwitchState::load(&ENV().risk.kill_switch_file)?;
+    if let Some(halt) = &state.halt {
+        Logger::warning(&format!(
+            "🛑 New BUYs are halted since {} ({}: {})",
+            chrono::DateTime::from_timestamp_millis(halt.halted_at).unwrap_or_default().format("%Y-%m-%d %H:%M UTC"),
+            halt.trigger,
+            halt.reason
+        ));
+        Logger::warning("Resume with: cargo run --bin kill-switch -- resume");
+    }
+    set_halt(state.halt);
+    Ok(())
+}
+
+/// Cash plus open positions; paper positions are marked at the best bid
+async fn portfolio_equity(clob_client: &ClobClient) -> AppResult<f64> {
+    let (positions, balance) = fetch_my_portfolio().await?;
+    let mut equity = balance;
+    for position in &positions {
+        equity += if ENV().dry_run {
+            match clob_client.get_order_book(&position.asset).await {
+                Ok(book) => book.best_bid().map_or(0.0, |bid| bid.price() * position.size),
+                // Paper positions are otherwise valued at cost
+                Err(_) => position.current_value,
+            }
+        } else {
+            position.current_value
+        };
+    }
+    Ok(equity)
+}
+
+/// Observe `equity` against the state currently in `path` and persist the result
+///
+/// The file is read right before it is written, so a resume saved while the portfolio was
+/// being valued is kept rather than overwritten.
+fn record_equity(
+    path: &str,
+    equity: f64,
+    limits: &RiskConfig,
+    now: chrono::DateTime<Utc>,
+) -> AppResult<(KillSwitchState, Option<Halt>)> {
+    let mut state = KillSwitchState::load(path)?;
+    let tripped = state.observe(equity, &now.format("%Y-%m-%d").to_string(), limits, now.timestamp_millis());
+    state.save(path)?;
+    Ok((state, tripped))
+}
+
+/// Check the limits against current equity, halting (and flattening, if enabled) when crossed
+///
+/// The state file is re-read first, so a `kill-switch resume` takes effect without a restart.
+pub async fn check_kill_switch(clob_client: &ClobClient) {
+    let config = &ENV().risk;
+    let state = match KillSwitchState::load(&config.kill_switch_file) {
+        Ok(state) => state,
+        Err(e) => {
+            Logger::error(&format!("Kill switch check failed: {}", e));
+            return;
+        }
+    };
+    if buying_halted().is_some() && state.halt.is_none() {
+        Logger::success("Kill switch cleared - new BUYs resumed");
+    }
+    if !config.has_kill_switch() {
+        set_halt(state.halt);
+        return;
+    }
+
+    let equity = match portfolio_equity(clob_client).await {
+        Ok(equity) => equity,
+        Err(e) => {
+            Logger::warning(&format!("Could not value the portfolio for the kill switch: {}", e));
+            set_halt(state.halt);
+            return;
+        }
+    };
+    let tripped = match record_equity(&config.kill_switch_file, equity, config, Utc::now()) {
+        Ok((state, tripped)) => {
+            set_halt(state.halt);
+            tripped
+        }
+        Err(e) => {
+            Logger::error(&format!("Failed to persist kill switch state: {}", e));
+            set_halt(state.halt);
+            return;
+        }
+    };
+
+    if let Some(halt) = tripped {
+        Logger::clear_line();
+        Logger::error(&format!("🛑 KILL SWITCH ({}): {} - new BUYs halted", halt.trigger, halt.reason));
+        Logger::warning("Resume with: cargo run --bin kill-switch -- resume");
+        if config.flatten_on_halt {
+            flatten_positions(clob_client, halt.trigger.as_str()).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> RiskConfig {
+        RiskConfig { max_daily_loss_usd: Some(100.0), max_drawdown_percent: Some(20.0), ..Default::default() }
+    }
+
+    #[test]
+    fn daily_loss_and_drawdown_halt_until_resumed() {
+        let mut state = KillSwitchState::default();
+        assert_eq!(state.observe(1000.0, "2026-10-16", &limits(), 0), None);
+        assert_eq!(state.observe(950.0, "2026-10-16", &limits(), 1), None);
+        // A new day starts its loss from the first equity seen
+        assert_eq!(state.observe(880.0, "2026-10-17", &limits(), 2), None);
+        let halt = state.observe(779.0, "2026-10-17", &limits(), 3).unwrap();
+        assert_eq!((halt.trigger, halt.halted_at), (HaltTrigger::DailyLoss, 3));
+        // Halted once; further checks keep the halt without re-triggering
+        assert_eq!(state.observe(700.0, "2026-10-17", &limits(), 4), None);
+        assert!(state.halt.is_some());
+
+        assert_eq!(state.resume().map(|halt| halt.trigger), Some(HaltTrigger::DailyLoss));
+        assert_eq!(state.observe(700.0, "2026-10-17", &limits(), 5), None);
+        // Resuming re-bases the day's loss on the next equity seen
+        let halt = state.observe(553.0, "2026-10-17", &limits(), 6).unwrap();
+        assert_eq!(halt.trigger, HaltTrigger::DailyLoss);
+
+        let only_drawdown = RiskConfig { max_drawdown_percent: Some(20.0), ..Default::default() };
+        let mut state = KillSwitchState::default();
+        state.observe(1000.0, "2026-10-16", &only_drawdown, 0);
+        assert_eq!(state.observe(850.0, "2026-10-17", &only_drawdown, 1), None);
+        let halt = state.observe(790.0, "2026-10-18", &only_drawdown, 2).unwrap();
+        assert_eq!(halt.trigger, HaltTrigger::Drawdown);
+    }
+
+    #[test]
+    fn state_round_trips_through_the_file() {
+        let path = std::env::temp_dir().join(format!("kill_switch_{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        assert_eq!(KillSwitchState::load(path).unwrap(), KillSwitchState::default());
+
+        let mut state = KillSwitchState::default();
+        state.observe(1000.0, "2026-10-17", &limits(), 0);
+        state.observe(850.0, "2026-10-17", &limits(), 1);
+        state.save(path).unwrap();
+        let loaded = KillSwitchState::load(path).unwrap();
+        assert_eq!(loaded.halt.map(|halt| halt.trigger), Some(HaltTrigger::DailyLoss));
+        assert_eq!((loaded.high_water_mark, loaded.day_start_equity), (1000.0, 1000.0));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn keeps_a_resume_saved_while_equity_was_fetched() {
+        let path = std::env::temp_dir().join(format!("kill_switch_resume_{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        let mut state = KillSwitchState::default();
+        state.observe(1000.0, "2026-10-17", &limits(), 0);
+        state.observe(850.0, "2026-10-17", &limits(), 1);
+        state.save(path).unwrap();
+
+        // `kill-switch resume` runs while the check is valuing the portfolio
+        let mut resumed = KillSwitchState::load(path).unwrap();
+        resumed.resume();
+        resumed.save(path).unwrap();
+
+        let (state, tripped) = record_equity(path, 840.0, &limits(), Utc::now()).unwrap();
+        assert_eq!((state.halt, tripped), (None, None));
+        assert_eq!(KillSwitchState::load(path).unwrap().high_water_mark, 840.0);
+        assert!(!Path::new(&format!("{}.tmp", path)).exists());
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -1,3 +1,17 @@
+pub mod clob_auth;
+pub mod clob_client;
+pub mod clob_ws;
 pub mod create_clob_client;
+pub mod execution_recovery;
+pub mod exit_manager;
+pub mod kill_switch;
+pub mod onchain_monitor;
+pub mod paper_wallet;
+pub mod pnl;
+pub mod price_drift;
+pub mod resting_orders;
+pub mod risk_manager;
+pub mod slippage;
 pub mod trade_executor;
 pub mod trade_monitor;
+pub mod trade_pipeline;
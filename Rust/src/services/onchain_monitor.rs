@@ -0,0 +1,380 @@
+// On-chain trade detection
+// Watches OrderFilled logs of the CTF Exchange and NegRisk CTF Exchange for followed wallets
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::types::{Address, Filter, Log, H256, U256};
+use ethers::utils::keccak256;
+use tokio::time::{sleep, Duration};
+use crate::interfaces::execution::ExecutionState;
+use crate::interfaces::user::UserActivityInterface;
+use crate::services::trade_monitor::record_activity;
+use crate::services::trade_pipeline::{apply_market, fetch_token_market, TokenMarket};
+use crate::utils::constants::{CLOB_CONSTANTS, DB_FIELDS, ONCHAIN_CONSTANTS};
+use crate::utils::errors::{AppError, AppResult};
+use crate::utils::logger::Logger;
+
+/// topic0 of `OrderFilled`
+pub fn order_filled_topic() -> H256 {
+    H256::from(keccak256(ONCHAIN_CONSTANTS::ORDER_FILLED_EVENT))
+}
+
+/// A followed wallet's fill decoded from an `OrderFilled` log
+#[derive(Debug, Clone, PartialEq)]
+pub struct OnchainFill {
+    pub transaction_hash: H256,
+    pub block_number: u64,
+    pub wallet: Address,
+    pub asset: U256,
+    pub side: &'static str,
+    pub size: f64,      // Outcome tokens
+    pub usdc_size: f64, // USDC
+}
+
+/// 6-decimal raw amount to a float, None if it does not fit a u128
+fn from_units(amount: U256) -> Option<f64> {
+    let amount = u128::try_from(amount).ok()?;
+    Some(amount as f64 / 10f64.powi(CLOB_CONSTANTS::COLLATERAL_DECIMALS as i32))
+}
+
+/// Decode an `OrderFilled` log if its maker is one of `wallets`
+///
+/// `OrderFilled(orderHash, maker, taker, makerAssetId, takerAssetId, makerAmountFilled, takerAmountFilled, fee)`:
+/// asset ID 0 is USDC, so a maker giving asset 0 is buying the taker asset and vice versa.
+/// Every order a wallet gets filled on is emitted with the wallet as maker (taker orders are
+/// emitted with the exchange as taker, then summarized by `OrdersMatched`), so logs where a
+/// followed wallet is only the taker describe the counterparty's side and are skipped, and so
+/// are logs the RPC reports as removed by a reorg or whose amounts overflow.
+pub fn decode_order_filled(log: &Log, wallets: &HashSet<Address>) -> Option<OnchainFill> {
+    if log.removed == Some(true) {
+        return None;
+    }
+    if log.topics.len() != 4 || log.topics[0] != order_filled_topic() || log.data.len() < 5 * 32 {
+        return None;
+    }
+    let maker = Address::from(log.topics[2]);
+    if !wallets.contains(&maker) {
+        return None;
+    }
+
+    let word = |i: usize| U256::from_big_endian(&log.data[i * 32..(i + 1) * 32]);
+    let (maker_asset_id, taker_asset_id) = (word(0), word(1));
+    let (maker_amount, taker_amount) = (word(2), word(3));
+
+    let (side, asset, size, usdc_size) = if maker_asset_id.is_zero() {
+        (DB_FIELDS::SIDE_BUY, taker_asset_id, taker_amount, maker_amount)
+    } else {
+        (DB_FIELDS::SIDE_SELL, maker_asset_id, maker_amount, taker_amount)
+    };
+
+    Some(OnchainFill {
+        transaction_hash: log.transaction_hash?,
+        block_number: log.block_number?.as_u64(),
+        wallet: maker,
+        asset,
+        side,
+        size: from_units(size)?,
+        usdc_size: from_units(usdc_size)?,
+    })
+}
+
+/// Merge fills of the same wallet, asset and side within a transaction
+///
+/// Activities are keyed by transaction hash, asset and side, so an order filled against
+/// several makers in one transaction is recorded as a single trade.
+pub fn aggregate_fills(fills: Vec<OnchainFill>) -> Vec<OnchainFill> {
+    let mut aggregated: Vec<OnchainFill> = Vec::new();
+    for fill in fills {
+        match aggregated.iter_mut().find(|f| {
+            f.transaction_hash == fill.transaction_hash
+                && f.wallet == fill.wallet
+                && f.asset == fill.asset
+                && f.side == fill.side
+        }) {
+            Some(existing) => {
+                existing.size += fill.size;
+                existing.usdc_size += fill.usdc_size;
+            }
+            None => aggregated.push(fill),
+        }
+    }
+    aggregated
+}
+
+/// Convert a fill into the activity shape produced by the data API
+///
+/// Market metadata (conditionId, title, ...) is not on-chain; see `apply_market`.
+pub fn fill_to_activity(fill: &OnchainFill, proxy_wallet: &str, timestamp: i64) -> UserActivityInterface {
+    UserActivityInterface {
+        _id: None,
+        proxy_wallet: proxy_wallet.to_string(),
+        timestamp,
+        condition_id: String::new(),
+        r#type: DB_FIELDS::TYPE_TRADE.to_string(),
+        size: fill.size,
+        usdc_size: fill.usdc_size,
+        transaction_hash: format!("{:?}", fill.transaction_hash),
+        price: if fill.size > 0.0 { fill.usdc_size / fill.size } else { 0.0 },
+        asset: fill.asset.to_string(),
+        side: fill.side.to_string(),
+        outcome_index: 0,
+        title: String::new(),
+        slug: String::new(),
+        icon: String::new(),
+        event_slug: String::new(),
+        outcome: String::new(),
+        name: String::new(),
+        pseudonym: String::new(),
+        bio: String::new(),
+        profile_image: String::new(),
+        profile_image_optimized: String::new(),
+        execution: ExecutionState::default(),
+        my_bought_size: None,
+    }
+}
+
+/// Poll blocks `confirmations` behind the head for followed wallets' fills until `is_running` is cleared
+pub async fn run_onchain_monitor(
+    rpc_url: &str,
+    user_addresses: &[String],
+    confirmations: u64,
+    is_running: &AtomicBool,
+) -> AppResult<()> {
+    let provider = Provider::<Http>::try_from(rpc_url)
+        .map_err(|e| AppError::ConfigurationError(format!("Invalid RPC_URL: {}", e)))?;
+
+    // Collections are keyed by the address exactly as configured
+    let mut wallets: HashMap<Address, String> = HashMap::new();
+    for address in user_addresses {
+        let parsed = address
+            .parse::<Address>()
+            .map_err(|e| AppError::ConfigurationError(format!("Invalid trader address {}: {}", address, e)))?;
+        wallets.insert(parsed, address.clone());
+    }
+    let wallet_set: HashSet<Address> = wallets.keys().copied().collect();
+    let makers: Vec<H256> = wallet_set.iter().map(|wallet| H256::from(*wallet)).collect();
+    let exchanges: Vec<Address> = [CLOB_CONSTANTS::EXCHANGE_ADDRESS, CLOB_CONSTANTS::NEG_RISK_EXCHANGE_ADDRESS]
+        .iter()
+        .map(|address| address.parse().expect("valid exchange address"))
+        .collect();
+
+    let network_error = |e: ethers::providers::ProviderError| AppError::NetworkError(format!("RPC request failed: {}", e));
+    let head = provider.get_block_number().await.map_err(network_error)?.as_u64();
+    let mut next_block = head.saturating_sub(confirmations) + 1;
+    // Markets never change token IDs; lookups that fail are retried on the next fill
+    let mut token_markets: HashMap<U256, TokenMarket> = HashMap::new();
+    Logger::info(&format!(
+        "⛓️  Watching exchange OrderFilled logs for {} trader(s) from block {}",
+        wallets.len(),
+        next_block
+    ));
+
+    while is_running.load(Ordering::SeqCst) {
+        sleep(Duration::from_millis(ONCHAIN_CONSTANTS::POLL_INTERVAL_MS)).await;
+
+        // Only blocks with enough confirmations, so reorged fills are never recorded
+        let latest = match provider.get_block_number().await {
+            Ok(block) => block.as_u64().saturating_sub(confirmations),
+            Err(e) => {
+                Logger::warning(&format!("On-chain monitor: {}", network_error(e)));
+                continue;
+            }
+        };
+
+        while next_block <= latest && is_running.load(Ordering::SeqCst) {
+            let to_block = latest.min(next_block + ONCHAIN_CONSTANTS::MAX_BLOCK_RANGE - 1);
+            let filter = Filter::new()
+                .address(exchanges.clone())
+                .topic0(order_filled_topic())
+                .topic2(makers.clone())
+                .from_block(next_block)
+                .to_block(to_block);
+
+            let logs = match provider.get_logs(&filter).await {
+                Ok(logs) => logs,
+                Err(e) => {
+                    // Retry the same range on the next tick
+                    Logger::warning(&format!("On-chain monitor: {}", network_error(e)));
+                    break;
+                }
+            };
+
+            let fills = aggregate_fills(logs.iter().filter_map(|log| decode_order_filled(log, &wallet_set)).collect());
+            // Fills only come from this range, so its timestamps are not needed past it
+            let mut block_timestamps: HashMap<u64, i64> = HashMap::new();
+            for fill in fills {
+                let timestamp = match block_timestamps.get(&fill.block_number) {
+                    Some(timestamp) => *timestamp,
+                    None => {
+                        let timestamp = match provider.get_block(fill.block_number).await {
+                            Ok(Some(block)) => block.timestamp.as_u64() as i64,
+                            _ => chrono::Utc::now().timestamp(),
+                        };
+                        block_timestamps.insert(fill.block_number, timestamp);
+                        timestamp
+                    }
+                };
+                if let Entry::Vacant(entry) = token_markets.entry(fill.asset) {
+                    if let Some(market) = fetch_token_market(&fill.asset.to_string()).await {
+                        entry.insert(market);
+                    }
+                }
+                let address = &wallets[&fill.wallet];
+                let mut activity = fill_to_activity(&fill, address, timestamp);
+                // Without metadata the trade is backfilled when the data API reports it
+                if let Some(market) = token_markets.get(&fill.asset) {
+                    apply_market(&mut activity, market);
+                }
+                if let Err(e) = record_activity(address, &activity).await {
+                    Logger::error(&format!("Failed to save on-chain trade {}: {}", activity.transaction_hash, e));
+                }
+            }
+
+            next_block = to_block + 1;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRADER: &str = "0x7c3db723f1d4d8cb9c550095203b686cb11e5c6b";
+    const COUNTERPARTY: &str = "0x1111111111111111111111111111111111111111";
+    const TOKEN_ID: &str = "71321045679252212594626385532706912750332728571942532289631379312455583992563";
+    const TX_HASH: &str = "0x5a1c4f7e2b0d0cfd7d1f9a2d6a9e0c3b8e4f1a2b3c4d5e6f708192a3b4c5d6e7";
+
+    fn topic_for(address: &str) -> String {
+        format!("0x000000000000000000000000{}", &address[2..])
+    }
+
+    /// An eth_getLogs entry as returned by a Polygon RPC
+    fn fixture(maker: &str, taker: &str, data: &str, log_index: u64) -> Log {
+        serde_json::from_value(serde_json::json!({
+            "address": CLOB_CONSTANTS::EXCHANGE_ADDRESS.to_lowercase(),
+            "topics": [
+                format!("{:?}", order_filled_topic()),
+                "0x2a9c1f0e8f5d5e7c6b4a392817161514131211100f0e0d0c0b0a090807060504",
+                topic_for(maker),
+                topic_for(taker),
+            ],
+            "data": data,
+            "blockNumber": "0x3a2b1c0",
+            "blockHash": "0x9f8e7d6c5b4a39281706f5e4d3c2b1a09f8e7d6c5b4a39281706f5e4d3c2b1a0",
+            "transactionHash": TX_HASH,
+            "transactionIndex": "0x12",
+            "logIndex": format!("0x{:x}", log_index),
+            "removed": false
+        }))
+        .unwrap()
+    }
+
+    // makerAssetId 0 (USDC) → buy 10 tokens for 5 USDC
+    const BUY_DATA: &str = "0x00000000000000000000000000000000000000000000000000000000000000009dae480511c4c0cb5d6c7937924c1db5be221e758b7135fec2a1977a1c130af300000000000000000000000000000000000000000000000000000000004c4b4000000000000000000000000000000000000000000000000000000000009896800000000000000000000000000000000000000000000000000000000000000000";
+    // takerAssetId 0 (USDC) → sell 4 tokens for 2.6 USDC (fee 0.013)
+    const SELL_DATA: &str = "0x9dae480511c4c0cb5d6c7937924c1db5be221e758b7135fec2a1977a1c130af3000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000003d0900000000000000000000000000000000000000000000000000000000000027ac4000000000000000000000000000000000000000000000000000000000000032c8";
+    // Second partial buy in the same transaction: 3 tokens for 1.5 USDC
+    const BUY_DATA_PARTIAL: &str = "0x00000000000000000000000000000000000000000000000000000000000000009dae480511c4c0cb5d6c7937924c1db5be221e758b7135fec2a1977a1c130af3000000000000000000000000000000000000000000000000000000000016e36000000000000000000000000000000000000000000000000000000000002dc6c00000000000000000000000000000000000000000000000000000000000000000";
+
+    fn wallets() -> HashSet<Address> {
+        HashSet::from([TRADER.parse().unwrap()])
+    }
+
+    #[test]
+    fn order_filled_topic_matches_exchange_abi() {
+        assert_eq!(
+            format!("{:?}", order_filled_topic()),
+            "0xd0a08e8c493f9c94f29311604c9de1b4e8c8d4c06bd0c789af57f2d65bfec0f6"
+        );
+    }
+
+    #[test]
+    fn decodes_buy_and_sell_fills() {
+        let buy = decode_order_filled(&fixture(TRADER, COUNTERPARTY, BUY_DATA, 3), &wallets()).unwrap();
+        assert_eq!(buy.side, "BUY");
+        assert_eq!(buy.asset, U256::from_dec_str(TOKEN_ID).unwrap());
+        assert_eq!((buy.size, buy.usdc_size), (10.0, 5.0));
+        assert_eq!(buy.block_number, 0x3a2b1c0);
+        assert_eq!(buy.wallet, TRADER.parse::<Address>().unwrap());
+
+        let sell = decode_order_filled(&fixture(TRADER, COUNTERPARTY, SELL_DATA, 4), &wallets()).unwrap();
+        assert_eq!(sell.side, "SELL");
+        assert_eq!(sell.asset, U256::from_dec_str(TOKEN_ID).unwrap());
+        assert_eq!((sell.size, sell.usdc_size), (4.0, 2.6));
+    }
+
+    #[test]
+    fn skips_fills_where_followed_wallet_is_not_maker() {
+        // Counterparty's order filled by our trader: the trader's side is in its own log
+        assert_eq!(decode_order_filled(&fixture(COUNTERPARTY, TRADER, BUY_DATA, 1), &wallets()), None);
+
+        let mut other_event = fixture(TRADER, COUNTERPARTY, BUY_DATA, 1);
+        other_event.topics[0] = H256::from(keccak256(ONCHAIN_CONSTANTS::ORDERS_MATCHED_EVENT));
+        assert_eq!(decode_order_filled(&other_event, &wallets()), None);
+
+        let mut truncated = fixture(TRADER, COUNTERPARTY, BUY_DATA, 1);
+        truncated.data = truncated.data[..64].to_vec().into();
+        assert_eq!(decode_order_filled(&truncated, &wallets()), None);
+    }
+
+    #[test]
+    fn skips_removed_logs_and_overflowing_amounts() {
+        let mut removed = fixture(TRADER, COUNTERPARTY, BUY_DATA, 1);
+        removed.removed = Some(true);
+        assert_eq!(decode_order_filled(&removed, &wallets()), None);
+
+        // makerAmountFilled above 2^128 does not fit a u128
+        let mut overflowing = fixture(TRADER, COUNTERPARTY, BUY_DATA, 1);
+        let mut data = overflowing.data.to_vec();
+        data[2 * 32 + 15] = 1;
+        overflowing.data = data.into();
+        assert_eq!(decode_order_filled(&overflowing, &wallets()), None);
+    }
+
+    #[test]
+    fn aggregates_partial_fills_and_builds_activity() {
+        let fills: Vec<OnchainFill> = [(BUY_DATA, 1), (BUY_DATA_PARTIAL, 2), (SELL_DATA, 3)]
+            .iter()
+            .filter_map(|(data, index)| decode_order_filled(&fixture(TRADER, COUNTERPARTY, data, *index), &wallets()))
+            .collect();
+        let aggregated = aggregate_fills(fills);
+        assert_eq!(aggregated.len(), 2);
+        assert_eq!((aggregated[0].size, aggregated[0].usdc_size), (13.0, 6.5));
+
+        let activity = fill_to_activity(&aggregated[0], TRADER, 1_750_428_146);
+        assert_eq!(activity.transaction_hash, TX_HASH);
+        assert_eq!(activity.asset, TOKEN_ID);
+        assert_eq!(activity.side, "BUY");
+        assert_eq!(activity.r#type, "TRADE");
+        assert_eq!(activity.price, 0.5);
+        assert_eq!(activity.proxy_wallet, TRADER);
+        assert_eq!(activity.timestamp, 1_750_428_146);
+        assert!(activity.condition_id.is_empty());
+    }
+
+    #[test]
+    fn applies_gamma_market_metadata_by_token_id() {
+        let markets: Vec<TokenMarket> = serde_json::from_value(serde_json::json!([{
+            "conditionId": "0xabc",
+            "question": "Will it rain?",
+            "slug": "will-it-rain",
+            "icon": null,
+            "outcomes": "[\"Yes\", \"No\"]",
+            "clobTokenIds": format!("[\"111\", \"{}\"]", TOKEN_ID),
+            "events": [{ "slug": "weather" }]
+        }]))
+        .unwrap();
+        let fill = decode_order_filled(&fixture(TRADER, COUNTERPARTY, BUY_DATA, 1), &wallets()).unwrap();
+        let mut activity = fill_to_activity(&fill, TRADER, 1_750_428_146);
+        apply_market(&mut activity, &markets[0]);
+        assert_eq!(activity.condition_id, "0xabc");
+        assert_eq!((activity.slug.as_str(), activity.event_slug.as_str()), ("will-it-rain", "weather"));
+        assert_eq!(activity.title, "Will it rain?");
+        assert_eq!((activity.outcome_index, activity.outcome.as_str()), (1, "No"));
+        assert!(activity.icon.is_empty());
+    }
+}
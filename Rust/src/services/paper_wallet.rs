@@ -0,0 +1,374 @@
+// Paper trading wallet
+// Fills market orders against live order book snapshots instead of posting them
+
+use futures::TryStreamExt;
+use mongodb::bson::{doc, oid::ObjectId, Document};
+use tokio::sync::Mutex;
+use crate::config::env::ENV;
+use crate::interfaces::user::{OrderBook, OrderBookEntry, UserPositionInterface};
+use crate::models::paper_trading::{
+    get_paper_fill_collection, get_paper_position_collection, get_paper_wallet_collection,
+};
+use crate::services::clob_client::{MarketOrderArgs, OrderResponse, Side};
+use crate::services::create_clob_client::ClobClient;
+use crate::utils::errors::{AppError, AppResult};
+use crate::utils::logger::Logger;
+
+/// `_id` of the single paper wallet document
+const WALLET_ID: &str = "wallet";
+
+/// Amounts below this are rounding noise
+const EPSILON: f64 = 1e-9;
+
+/// Serializes paper fills so concurrent orders see each other's balance and position updates
+static FILL_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// Result of walking the book
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SimulatedFill {
+    pub usdc: f64,
+    pub tokens: f64,
+    pub levels: usize,
+}
+
+impl SimulatedFill {
+    pub fn avg_price(&self) -> f64 {
+        if self.tokens > 0.0 {
+            self.usdc / self.tokens
+        } else {
+            0.0
+        }
+    }
+}
+
+/// `(price, size)` of the non-empty levels, best first for asks (ascending) or bids (descending)
+pub fn sorted_levels(levels: &[OrderBookEntry], descending: bool) -> Vec<(f64, f64)> {
+    let mut levels: Vec<(f64, f64)> = levels
+        .iter()
+        .map(|level| (level.price(), level.size()))
+        .filter(|(price, size)| *price > 0.0 && *size > 0.0)
+        .collect();
+    levels.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+    if descending {
+        levels.reverse();
+    }
+    levels
+}
+
+/// Spend up to `usdc_amount` on the asks, cheapest first, without paying more than `limit_price`
+pub fn simulate_buy(book: &OrderBook, usdc_amount: f64, limit_price: f64) -> SimulatedFill {
+    let mut fill = SimulatedFill::default();
+    for (price, size) in sorted_levels(&book.asks, false) {
+        let remaining = usdc_amount - fill.usdc;
+        if remaining <= EPSILON || price > limit_price + EPSILON {
+            break;
+        }
+        let tokens = size.min(remaining / price);
+        fill.usdc += tokens * price;
+        fill.tokens += tokens;
+        fill.levels += 1;
+    }
+    fill
+}
+
+/// Sell up to `token_amount` into the bids, highest first, without accepting less than `limit_price`
+pub fn simulate_sell(book: &OrderBook, token_amount: f64, limit_price: f64) -> SimulatedFill {
+    let mut fill = SimulatedFill::default();
+    for (price, size) in sorted_levels(&book.bids, true) {
+        let remaining = token_amount - fill.tokens;
+        if remaining <= EPSILON || price < limit_price - EPSILON {
+            break;
+        }
+        let tokens = size.min(remaining);
+        fill.usdc += tokens * price;
+        fill.tokens += tokens;
+        fill.levels += 1;
+    }
+    fill
+}
+
+fn rejected(message: &str) -> OrderResponse {
+    OrderResponse {
+        success: false,
+        error_msg: message.to_string(),
+        ..Default::default()
+    }
+}
+
+fn database_error(e: mongodb::error::Error) -> AppError {
+    AppError::DatabaseError(e.to_string())
+}
+
+/// Create the paper wallet with `PAPER_STARTING_BALANCE` unless it already exists, returns its balance
+pub async fn init_paper_wallet() -> AppResult<f64> {
+    let starting_balance = ENV().paper_starting_balance;
+    get_paper_wallet_collection()
+        .update_one(
+            doc! { "_id": WALLET_ID },
+            doc! { "$setOnInsert": { "usdcBalance": starting_balance, "startingBalance": starting_balance } },
+        )
+        .upsert(true)
+        .await
+        .map_err(database_error)?;
+    get_paper_balance().await
+}
+
+/// Simulated USDC balance
+pub async fn get_paper_balance() -> AppResult<f64> {
+    let wallet = get_paper_wallet_collection()
+        .find_one(doc! { "_id": WALLET_ID })
+        .await
+        .map_err(database_error)?;
+    Ok(wallet.and_then(|wallet| wallet.get_f64("usdcBalance").ok()).unwrap_or(0.0))
+}
+
+/// Simulated open positions, valued at cost
+pub async fn get_paper_positions() -> AppResult<Vec<UserPositionInterface>> {
+    let mut cursor = get_paper_position_collection()
+        .find(doc! { "size": { "$gt": EPSILON } })
+        .await
+        .map_err(database_error)?;
+    let mut positions = Vec::new();
+    while let Some(position) = cursor.try_next().await.map_err(database_error)? {
+        let size = position.get_f64("size").unwrap_or(0.0);
+        let avg_price = position.get_f64("avgPrice").unwrap_or(0.0);
+        positions.push(UserPositionInterface {
+            _id: None,
+            proxy_wallet: ENV().proxy_wallet.clone(),
+            asset: position.get_str("asset").unwrap_or_default().to_string(),
+            condition_id: String::new(),
+            size,
+            avg_price,
+            initial_value: size * avg_price,
+            current_value: size * avg_price,
+            cash_pnl: 0.0,
+            percent_pnl: 0.0,
+            total_bought: position.get_f64("totalBought").unwrap_or(0.0),
+            realized_pnl: position.get_f64("realizedPnl").unwrap_or(0.0),
+            percent_realized_pnl: 0.0,
+            cur_price: avg_price,
+            redeemable: false,
+            mergeable: false,
+            title: String::new(),
+            slug: String::new(),
+            icon: String::new(),
+            event_slug: String::new(),
+            outcome: String::new(),
+            outcome_index: 0,
+            opposite_outcome: String::new(),
+            opposite_asset: String::new(),
+            end_date: String::new(),
+            negative_risk: false,
+        });
+    }
+    Ok(positions)
+}
+
+async fn get_paper_position(asset: &str) -> AppResult<Option<Document>> {
+    get_paper_position_collection()
+        .find_one(doc! { "asset": asset })
+        .await
+        .map_err(database_error)
+}
+
+/// Book a simulated fill into the paper wallet
+/// Book a fill into the paper wallet, returns false when the balance cannot cover a BUY
+async fn apply_fill(asset: &str, side: Side, fill: &SimulatedFill) -> AppResult<bool> {
+    let wallet = get_paper_wallet_collection();
+    let positions = get_paper_position_collection();
+    let position = get_paper_position(asset).await?;
+    let size = position.as_ref().and_then(|p| p.get_f64("size").ok()).unwrap_or(0.0);
+    let avg_price = position.as_ref().and_then(|p| p.get_f64("avgPrice").ok()).unwrap_or(0.0);
+
+    match side {
+        Side::Buy => {
+            // The balance guard lives in the filter so the debit can never drive it negative
+            let debited = wallet
+                .update_one(
+                    doc! { "_id": WALLET_ID, "usdcBalance": { "$gte": fill.usdc - EPSILON } },
+                    doc! { "$inc": { "usdcBalance": -fill.usdc } },
+                )
+                .await
+                .map_err(database_error)?;
+            if debited.matched_count == 0 {
+                return Ok(false);
+            }
+            let new_size = size + fill.tokens;
+            let new_avg_price = (size * avg_price + fill.usdc) / new_size;
+            positions
+                .update_one(
+                    doc! { "asset": asset },
+                    doc! {
+                        "$set": { "size": new_size, "avgPrice": new_avg_price },
+                        "$inc": { "totalBought": fill.usdc },
+                        "$setOnInsert": { "realizedPnl": 0.0 },
+                    },
+                )
+                .upsert(true)
+                .await
+                .map_err(database_error)?;
+        }
+        Side::Sell => {
+            wallet
+                .update_one(doc! { "_id": WALLET_ID }, doc! { "$inc": { "usdcBalance": fill.usdc } })
+                .await
+                .map_err(database_error)?;
+            let realized_pnl = fill.usdc - fill.tokens * avg_price;
+            positions
+                .update_one(
+                    doc! { "asset": asset },
+                    doc! {
+                        "$set": { "size": (size - fill.tokens).max(0.0) },
+                        "$inc": { "realizedPnl": realized_pnl },
+                    },
+                )
+                .await
+                .map_err(database_error)?;
+        }
+    }
+
+    get_paper_fill_collection()
+        .insert_one(doc! {
+            "asset": asset,
+            "side": match side { Side::Buy => "BUY", Side::Sell => "SELL" },
+            "usdcSize": fill.usdc,
+            "size": fill.tokens,
+            "price": fill.avg_price(),
+            "levels": fill.levels as i64,
+            "timestamp": chrono::Utc::now().timestamp(),
+        })
+        .await
+        .map_err(database_error)?;
+    Ok(true)
+}
+
+/// Fill a FOK market order against the current book into the paper wallet
+///
+/// Mirrors the CLOB: the order is rejected unless it fills completely within its price, and
+/// orders larger than the simulated balance or position are rejected as insufficient funds.
+pub async fn post_paper_order(clob_client: &ClobClient, args: &MarketOrderArgs) -> AppResult<OrderResponse> {
+    fill_paper_order(clob_client, args, true).await
+}
+
+/// Fill a limit order against the current book into the paper wallet
+///
+/// Whatever crosses at or within `args.price` fills; paper orders cannot rest, so the rest
+/// expires at once and an order with nothing to match is accepted as `unmatched`.
+pub async fn post_paper_limit_order(clob_client: &ClobClient, args: &MarketOrderArgs) -> AppResult<OrderResponse> {
+    fill_paper_order(clob_client, args, false).await
+}
+
+async fn fill_paper_order(
+    clob_client: &ClobClient,
+    args: &MarketOrderArgs,
+    fill_or_kill: bool,
+) -> AppResult<OrderResponse> {
+    let _guard = FILL_LOCK.lock().await;
+    match args.side {
+        Side::Buy if get_paper_balance().await? + EPSILON < args.amount => {
+            return Ok(rejected("not enough balance / allowance (paper wallet)"));
+        }
+        Side::Sell => {
+            let held = get_paper_position(&args.token_id)
+                .await?
+                .and_then(|position| position.get_f64("size").ok())
+                .unwrap_or(0.0);
+            if held + EPSILON < args.amount {
+                return Ok(rejected("not enough balance / allowance (paper position)"));
+            }
+        }
+        _ => {}
+    }
+
+    let book = clob_client.get_order_book(&args.token_id).await?;
+    let (fill, complete) = match args.side {
+        Side::Buy => {
+            let fill = simulate_buy(&book, args.amount, args.price);
+            (fill, fill.usdc + EPSILON >= args.amount)
+        }
+        Side::Sell => {
+            let fill = simulate_sell(&book, args.amount, args.price);
+            (fill, fill.tokens + EPSILON >= args.amount)
+        }
+    };
+    if fill_or_kill && !complete {
+        return Ok(rejected("order couldn't be fully filled, FOK orders are fully filled or killed (paper)"));
+    }
+    if fill.tokens <= EPSILON {
+        return Ok(OrderResponse {
+            success: true,
+            order_id: format!("paper-{}", ObjectId::new()),
+            status: "unmatched".to_string(),
+            ..Default::default()
+        });
+    }
+
+    if !apply_fill(&args.token_id, args.side, &fill).await? {
+        return Ok(rejected("not enough balance / allowance (paper wallet)"));
+    }
+    Logger::info(&format!(
+        "📝 Paper fill: {:.2} tokens for ${:.2} (avg ${:.4} over {} level{})",
+        fill.tokens,
+        fill.usdc,
+        fill.avg_price(),
+        fill.levels,
+        if fill.levels == 1 { "" } else { "s" }
+    ));
+
+    // For a BUY making is USDC spent and taking is tokens received; for a SELL it is the reverse
+    let (making, taking) = match args.side {
+        Side::Buy => (fill.usdc, fill.tokens),
+        Side::Sell => (fill.tokens, fill.usdc),
+    };
+    Ok(OrderResponse {
+        success: true,
+        order_id: format!("paper-{}", ObjectId::new()),
+        status: "matched".to_string(),
+        making_amount: format!("{:.6}", making),
+        taking_amount: format!("{:.6}", taking),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interfaces::user::tests::book;
+
+    fn approx(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn buy_walks_asks_up_to_limit() {
+        // 10 @ 0.52 = 5.20, then 20 @ 0.55 = 11.00
+        let fill = simulate_buy(&book(), 16.2, 0.60);
+        assert!(approx(fill.usdc, 16.2) && approx(fill.tokens, 30.0));
+        assert_eq!(fill.levels, 2);
+        assert!(approx(fill.avg_price(), 0.54));
+
+        // Limit at the best ask only reaches the first level
+        let fill = simulate_buy(&book(), 16.2, 0.52);
+        assert!(approx(fill.usdc, 5.2) && approx(fill.tokens, 10.0));
+        assert_eq!(fill.levels, 1);
+    }
+
+    #[test]
+    fn sell_walks_bids_down_to_limit() {
+        // 10 @ 0.50 = 5.00, then 40 @ 0.48 = 19.20
+        let fill = simulate_sell(&book(), 50.0, 0.45);
+        assert!(approx(fill.tokens, 50.0) && approx(fill.usdc, 24.2));
+        assert_eq!(fill.levels, 2);
+
+        let fill = simulate_sell(&book(), 50.0, 0.49);
+        assert!(approx(fill.tokens, 10.0) && approx(fill.usdc, 5.0));
+    }
+
+    #[test]
+    fn empty_book_fills_nothing() {
+        let empty = OrderBook { bids: vec![], asks: vec![] };
+        assert_eq!(simulate_buy(&empty, 10.0, 1.0), SimulatedFill::default());
+        assert_eq!(simulate_sell(&empty, 10.0, 0.0), SimulatedFill::default());
+        assert_eq!(SimulatedFill::default().avg_price(), 0.0);
+    }
+}
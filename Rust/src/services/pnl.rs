@@ -0,0 +1,312 @@
+// PnL engine
+// Average-cost PnL from our own fill ledger, per market, per followed trader and overall
+
+use std::collections::{BTreeMap, HashMap};
+use crate::backtest::history::fetch_market_quotes;
+use crate::config::env::ENV;
+use crate::interfaces::ledger::FillRecord;
+use crate::interfaces::user::UserActivityInterface;
+use crate::storage::storage;
+use crate::utils::constants::{BACKTEST_CONSTANTS, DB_FIELDS, POLYMARKET_API};
+use crate::utils::errors::{AppError, AppResult};
+use crate::utils::fetch_data::fetch_data;
+use crate::utils::logger::Logger;
+
+/// Token amounts below this are dust left by float rounding
+const EPSILON: f64 = 1e-9;
+
+/// Tokens one followed trader's copies hold in one outcome token
+#[derive(Debug, Clone, Default)]
+struct Lot {
+    condition_id: String,
+    event_slug: String,
+    tokens: f64,
+    /// Cost basis of `tokens`, fees included
+    cost: f64,
+    realized: f64,
+    fees: f64,
+    payouts: f64,
+}
+
+impl Lot {
+    /// Close the lot for `payout` USDC
+    fn settle(&mut self, payout: f64) {
+        self.realized += payout - self.cost;
+        self.payouts += payout;
+        self.tokens = 0.0;
+        self.cost = 0.0;
+    }
+}
+
+/// PnL of a group of lots, in USDC
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PnlTotals {
+    pub realized: f64,
+    pub unrealized: f64,
+    /// Trading fees paid, already included in realized and unrealized
+    pub fees: f64,
+    /// Resolution payouts and redemptions credited
+    pub payouts: f64,
+    /// Cost basis of the tokens still held
+    pub open_cost: f64,
+    /// Marked value of the tokens still held
+    pub open_value: f64,
+}
+
+impl PnlTotals {
+    pub fn total(&self) -> f64 {
+        self.realized + self.unrealized
+    }
+
+    fn add(&mut self, other: &PnlTotals) {
+        self.realized += other.realized;
+        self.unrealized += other.unrealized;
+        self.fees += other.fees;
+        self.payouts += other.payouts;
+        self.open_cost += other.open_cost;
+        self.open_value += other.open_value;
+    }
+}
+
+/// PnL broken down by the followed trader behind each fill, by market (condition ID) and by event
+#[derive(Debug, Clone, Default)]
+pub struct PnlReport {
+    pub overall: PnlTotals,
+    pub by_trader: BTreeMap<String, PnlTotals>,
+    pub by_market: BTreeMap<String, PnlTotals>,
+    /// Fills recorded without an event slug are left out
+    pub by_event: BTreeMap<String, PnlTotals>,
+}
+
+/// Tokens one followed trader's copies still hold in one outcome token
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpenLot {
+    pub trader_address: String,
+    pub asset: String,
+    pub condition_id: String,
+    pub event_slug: String,
+    pub tokens: f64,
+    /// Cost basis of `tokens`, fees included
+    pub cost: f64,
+}
+
+/// Average-cost accounting over our fills
+///
+/// Each followed trader's copies of an outcome token form their own lot, so PnL is attributed
+/// to the trader whose trade triggered the fill.
+#[derive(Debug, Default)]
+pub struct PnlEngine {
+    /// Keyed by (trader address, asset)
+    lots: BTreeMap<(String, String), Lot>,
+}
+
+impl PnlEngine {
+    /// Apply a fill; fills must come in the order they happened
+    ///
+    /// Sells of tokens bought before the ledger existed have no cost basis and are left out.
+    pub fn apply_fill(&mut self, fill: &FillRecord) {
+        let lot = self.lots.entry((fill.trader_address.clone(), fill.asset.clone())).or_default();
+        if lot.condition_id.is_empty() {
+            lot.condition_id = fill.condition_id.clone();
+        }
+        if lot.event_slug.is_empty() {
+            lot.event_slug = fill.event_slug.clone();
+        }
+        lot.fees += fill.fee;
+
+        if fill.side == DB_FIELDS::SIDE_BUY {
+            lot.tokens += fill.size;
+            lot.cost += fill.usdc + fill.fee;
+            return;
+        }
+        let sold = fill.size.min(lot.tokens);
+        if sold <= EPSILON {
+            lot.realized -= fill.fee;
+            return;
+        }
+        let basis = lot.cost * sold / lot.tokens;
+        lot.realized += (fill.usdc - fill.fee) * sold / fill.size - basis;
+        lot.cost -= basis;
+        lot.tokens -= sold;
+    }
+
+    /// Settle every open lot of a resolved outcome token at its payout per token
+    pub fn resolve(&mut self, asset: &str, payout: f64) {
+        for ((_, lot_asset), lot) in self.lots.iter_mut() {
+            if lot_asset == asset && lot.tokens > EPSILON {
+                lot.settle(lot.tokens * payout);
+            }
+        }
+    }
+
+    /// Credit a redemption of `usdc` to the lots still open in the market, by tokens held
+    ///
+    /// Only matters for markets whose payouts are unknown; resolved lots are already closed.
+    pub fn redeem(&mut self, condition_id: &str, usdc: f64) {
+        let open = |lot: &Lot| lot.condition_id == condition_id && lot.tokens > EPSILON;
+        let held: f64 = self.lots.values().filter(|lot| open(lot)).map(|lot| lot.tokens).sum();
+        if held <= EPSILON {
+            return;
+        }
+        for lot in self.lots.values_mut().filter(|lot| open(lot)) {
+            lot.settle(usdc * lot.tokens / held);
+        }
+    }
+
+    /// Lots still holding tokens
+    pub fn open_lots(&self) -> Vec<OpenLot> {
+        self.lots
+            .iter()
+            .filter(|(_, lot)| lot.tokens > EPSILON)
+            .map(|((trader, asset), lot)| OpenLot {
+                trader_address: trader.clone(),
+                asset: asset.clone(),
+                condition_id: lot.condition_id.clone(),
+                event_slug: lot.event_slug.clone(),
+                tokens: lot.tokens,
+                cost: lot.cost,
+            })
+            .collect()
+    }
+
+    /// Totals with open tokens marked at `marks` (asset → price); unmarked tokens are valued at cost
+    pub fn report(&self, marks: &HashMap<String, f64>) -> PnlReport {
+        let mut report = PnlReport::default();
+        for ((trader, asset), lot) in &self.lots {
+            let open_value = marks.get(asset).map_or(lot.cost, |price| lot.tokens * price);
+            let totals = PnlTotals {
+                realized: lot.realized,
+                unrealized: open_value - lot.cost,
+                fees: lot.fees,
+                payouts: lot.payouts,
+                open_cost: lot.cost,
+                open_value,
+            };
+            report.overall.add(&totals);
+            report.by_trader.entry(trader.clone()).or_default().add(&totals);
+            report.by_market.entry(lot.condition_id.clone()).or_default().add(&totals);
+            if !lot.event_slug.is_empty() {
+                report.by_event.entry(lot.event_slug.clone()).or_default().add(&totals);
+            }
+        }
+        report
+    }
+}
+
+/// Our redemptions from the data API, newest first
+async fn fetch_redemptions(wallet: &str) -> AppResult<Vec<UserActivityInterface>> {
+    let url = format!(
+        "{}{}?user={}&type={}&limit={}",
+        POLYMARKET_API::DATA_API_BASE,
+        POLYMARKET_API::ACTIVITY_ENDPOINT,
+        wallet,
+        DB_FIELDS::TYPE_REDEEM,
+        BACKTEST_CONSTANTS::ACTIVITY_PAGE_SIZE
+    );
+    fetch_data(&url).await.map_err(|e| AppError::NetworkError(e.to_string()))
+}
+
+/// Average-cost engine over the fill ledger, before any resolution or redemption, and its fills
+///
+/// Only fills of the current mode count: paper fills when paper trading, real ones otherwise.
+pub async fn ledger_engine() -> AppResult<(PnlEngine, Vec<FillRecord>)> {
+    let dry_run = ENV().dry_run;
+    let fills: Vec<FillRecord> = storage()
+        .fills_since(None)
+        .await?
+        .into_iter()
+        .filter(|fill| fill.paper == dry_run)
+        .collect();
+
+    let mut engine = PnlEngine::default();
+    for fill in &fills {
+        engine.apply_fill(fill);
+    }
+    Ok((engine, fills))
+}
+
+/// PnL of everything recorded in the fill ledger, plus market slugs keyed by condition ID
+pub async fn compute_pnl() -> AppResult<(PnlReport, HashMap<String, String>)> {
+    let (mut engine, fills) = ledger_engine().await?;
+
+    let quotes = fetch_market_quotes(fills.iter().map(|fill| fill.condition_id.as_str())).await?;
+    let mut marks = HashMap::new();
+    for quote in quotes.values() {
+        for (asset, payout) in quote.payouts() {
+            engine.resolve(asset, *payout);
+        }
+        marks.extend(quote.prices.iter().cloned());
+    }
+
+    // Paper positions are never redeemed on-chain
+    if !ENV().dry_run {
+        match fetch_redemptions(&ENV().proxy_wallet).await {
+            Ok(redemptions) => {
+                for redemption in redemptions.iter().rev() {
+                    engine.redeem(&redemption.condition_id, redemption.usdc_size);
+                }
+            }
+            Err(e) => Logger::warning(&format!("Could not fetch redemptions, PnL may miss payouts: {}", e)),
+        }
+    }
+
+    let slugs = quotes.into_iter().map(|(condition_id, quote)| (condition_id, quote.slug)).collect();
+    Ok((engine.report(&marks), slugs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(trader: &str, asset: &str, side: &str, size: f64, usdc: f64, fee: f64) -> FillRecord {
+        FillRecord {
+            trader_address: trader.to_string(),
+            asset: asset.to_string(),
+            condition_id: format!("c-{}", asset),
+            side: side.to_string(),
+            size,
+            usdc,
+            fee,
+            ..Default::default()
+        }
+    }
+
+    fn approx(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn average_cost_realizes_on_sells_and_marks_the_rest() {
+        let mut engine = PnlEngine::default();
+        engine.apply_fill(&fill("0xa", "A", "BUY", 100.0, 40.0, 0.0));
+        engine.apply_fill(&fill("0xa", "A", "BUY", 100.0, 60.0, 1.0));
+        // Average cost 0.505; sell half at 0.70 with a 0.5 fee
+        engine.apply_fill(&fill("0xa", "A", "SELL", 100.0, 70.0, 0.5));
+
+        let report = engine.report(&HashMap::from([("A".to_string(), 0.6)]));
+        let trader = &report.by_trader["0xa"];
+        assert!(approx(trader.realized, 69.5 - 50.5));
+        assert!(approx(trader.open_cost, 50.5));
+        assert!(approx(trader.unrealized, 60.0 - 50.5));
+        assert!(approx(trader.fees, 1.5));
+    }
+
+    #[test]
+    fn resolutions_and_redemptions_are_attributed_per_trader() {
+        let mut engine = PnlEngine::default();
+        engine.apply_fill(&fill("0xa", "A", "BUY", 10.0, 4.0, 0.0));
+        engine.apply_fill(&fill("0xb", "A", "BUY", 30.0, 15.0, 0.0));
+        engine.apply_fill(&fill("0xb", "B", "BUY", 20.0, 10.0, 0.0));
+        engine.resolve("A", 1.0);
+        // Market of B resolved with unknown payouts; the redemption closes it
+        engine.redeem("c-B", 0.0);
+        engine.redeem("c-A", 40.0);
+
+        let report = engine.report(&HashMap::new());
+        assert!(approx(report.by_trader["0xa"].realized, 6.0));
+        assert!(approx(report.by_trader["0xb"].realized, 15.0 - 10.0));
+        assert!(approx(report.by_market["c-A"].payouts, 40.0));
+        assert!(approx(report.overall.total(), 11.0));
+        assert!(approx(report.overall.open_cost, 0.0));
+    }
+}
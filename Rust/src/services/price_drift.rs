@@ -0,0 +1,202 @@
+// Price drift guard
+// Stops copies from chasing a book that has moved away from the followed trader's fill price
+
+use crate::config::env::var;
+use crate::services::clob_client::Side;
+use crate::utils::constants::TRADING_CONSTANTS;
+use crate::utils::errors::AppError;
+
+/// What to do with a copy once the book has drifted past MAX_PRICE_DRIFT
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriftAction {
+    /// Do not copy (the rest of) the trade
+    Skip,
+    /// Post a short-lived GTD limit order at the trader's price plus the tolerance
+    Limit,
+}
+
+/// How far the book moved against a copy
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriceDrift {
+    /// Price the followed trader got
+    pub leader_price: f64,
+    /// Best ask (BUY) or best bid (SELL) now
+    pub book_price: f64,
+    /// Largest adverse move allowed, in USDC per token
+    pub tolerance: f64,
+}
+
+impl PriceDrift {
+    /// Adverse move in cents
+    pub fn cents(&self) -> f64 {
+        (self.book_price - self.leader_price).abs() * 100.0
+    }
+
+    /// Adverse move relative to the trader's price
+    pub fn percent(&self) -> f64 {
+        (self.book_price - self.leader_price).abs() / self.leader_price * 100.0
+    }
+
+    /// Worst price still within the tolerance, on the market's tick grid
+    pub fn limit_price(&self, side: Side, tick_size: f64) -> f64 {
+        let ticks = match side {
+            Side::Buy => ((self.leader_price + self.tolerance) / tick_size + 1e-9).floor(),
+            Side::Sell => ((self.leader_price - self.tolerance) / tick_size - 1e-9).ceil(),
+        };
+        (ticks * tick_size).clamp(tick_size, 1.0 - tick_size)
+    }
+}
+
+/// `(cents, percent)` tolerances of a MAX_PRICE_DRIFT value such as `3c`, `5%` or `3c,5%`
+///
+/// `off` disables the guard.
+pub fn parse_price_drift(spec: &str) -> Result<(Option<f64>, Option<f64>), AppError> {
+    let spec = spec.trim();
+    if spec.eq_ignore_ascii_case("off") {
+        return Ok((None, None));
+    }
+    let mut cents = None;
+    let mut percent = None;
+    for term in spec.split(',').map(str::trim) {
+        let (number, slot) = if let Some(number) = term.strip_suffix('%') {
+            (number, &mut percent)
+        } else if let Some(number) = term.strip_suffix('c').or_else(|| term.strip_suffix('¢')) {
+            (number, &mut cents)
+        } else {
+            return Err(AppError::ConfigurationError(format!(
+                "\"{}\" needs a unit: cents (3c) or percent (5%)",
+                term
+            )));
+        };
+        let value = number
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite() && *value > 0.0)
+            .ok_or_else(|| AppError::ConfigurationError(format!("\"{}\" is not a positive tolerance", term)))?;
+        if slot.replace(value).is_some() {
+            return Err(AppError::ConfigurationError(format!("\"{}\" is given twice", term)));
+        }
+    }
+    Ok((cents, percent))
+}
+
+/// MAX_PRICE_DRIFT and what happens when a copy exceeds it
+#[derive(Debug, Clone)]
+pub struct PriceDriftConfig {
+    pub max_cents: Option<f64>,
+    pub max_percent: Option<f64>,
+    pub action: DriftAction,
+    /// Lifetime of the limit orders posted with `DriftAction::Limit`
+    pub limit_expiry_seconds: u64,
+}
+
+impl Default for PriceDriftConfig {
+    fn default() -> Self {
+        Self {
+            // Opt-in: copies chase the book unless MAX_PRICE_DRIFT is set
+            max_cents: None,
+            max_percent: None,
+            action: DriftAction::Skip,
+            limit_expiry_seconds: TRADING_CONSTANTS::DEFAULT_DRIFT_LIMIT_EXPIRY_SECONDS,
+        }
+    }
+}
+
+impl PriceDriftConfig {
+    /// Read the guard from MAX_PRICE_DRIFT and PRICE_DRIFT_ACTION; an invalid MAX_PRICE_DRIFT is an error
+    pub fn from_env() -> Result<Self, String> {
+        let defaults = Self::default();
+        let (max_cents, max_percent) = match var("MAX_PRICE_DRIFT") {
+            Ok(spec) => parse_price_drift(&spec).map_err(|e| {
+                let reason = match e {
+                    AppError::ConfigurationError(reason) => reason,
+                    e => e.to_string(),
+                };
+                format!("Invalid MAX_PRICE_DRIFT \"{}\": {} (examples: 3c, 5%, 3c,5%, off)", spec, reason)
+            })?,
+            Err(_) => (defaults.max_cents, defaults.max_percent),
+        };
+        let action = match var("PRICE_DRIFT_ACTION") {
+            Ok(action) if action.trim().eq_ignore_ascii_case("limit") => DriftAction::Limit,
+            _ => DriftAction::Skip,
+        };
+        Ok(Self {
+            max_cents,
+            max_percent,
+            action,
+            limit_expiry_seconds: var("PRICE_DRIFT_LIMIT_EXPIRY_SECONDS")
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(defaults.limit_expiry_seconds),
+        })
+    }
+
+    /// Largest adverse move allowed from `leader_price`; the tighter one when both are set
+    pub fn tolerance(&self, leader_price: f64) -> Option<f64> {
+        let cents = self.max_cents.map(|cents| cents / 100.0);
+        let percent = self.max_percent.map(|percent| leader_price * percent / 100.0);
+        match (cents, percent) {
+            (Some(cents), Some(percent)) => Some(cents.min(percent)),
+            (tolerance, None) | (None, tolerance) => tolerance,
+        }
+    }
+
+    /// Worst price a `side` copy of a trade at `leader_price` may pay (BUY) or accept (SELL)
+    pub fn worst_price(&self, side: Side, leader_price: f64) -> Option<f64> {
+        if leader_price <= 0.0 {
+            return None;
+        }
+        let tolerance = self.tolerance(leader_price)?;
+        Some(match side {
+            Side::Buy => leader_price + tolerance,
+            Side::Sell => leader_price - tolerance,
+        })
+    }
+
+    /// The drift, if the book moved against a `side` copy by more than the tolerance
+    ///
+    /// Moves in the copy's favour never count, and trades without a price are not checked.
+    pub fn check(&self, side: Side, leader_price: f64, book_price: f64) -> Option<PriceDrift> {
+        let worst_price = self.worst_price(side, leader_price)?;
+        let beyond = match side {
+            Side::Buy => book_price > worst_price + 1e-9,
+            Side::Sell => book_price < worst_price - 1e-9,
+        };
+        let tolerance = (worst_price - leader_price).abs();
+        beyond.then_some(PriceDrift { leader_price, book_price, tolerance })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tolerances_parse_with_units() {
+        assert_eq!(parse_price_drift("3c").unwrap(), (Some(3.0), None));
+        assert_eq!(parse_price_drift(" 2.5c , 5% ").unwrap(), (Some(2.5), Some(5.0)));
+        assert_eq!(parse_price_drift("OFF").unwrap(), (None, None));
+        for invalid in ["3", "0c", "-2%", "3c,4c", "5 cents"] {
+            assert!(parse_price_drift(invalid).is_err(), "{} should be rejected", invalid);
+        }
+    }
+
+    #[test]
+    fn only_adverse_moves_past_the_tighter_tolerance_count() {
+        let config = PriceDriftConfig { max_cents: Some(3.0), max_percent: Some(5.0), ..Default::default() };
+        // 5% of 0.40 is 2c, tighter than 3c
+        assert_eq!(config.check(Side::Buy, 0.40, 0.42), None);
+        let drift = config.check(Side::Buy, 0.40, 0.48).unwrap();
+        assert_eq!(drift.limit_price(Side::Buy, 0.01), 0.42);
+        assert!((drift.cents() - 8.0).abs() < 1e-9);
+        // A cheaper ask, or a higher bid, is never drift
+        assert_eq!(config.check(Side::Buy, 0.40, 0.30), None);
+        assert_eq!(config.check(Side::Sell, 0.40, 0.50), None);
+        let drift = config.check(Side::Sell, 0.80, 0.74).unwrap();
+        assert_eq!(drift.limit_price(Side::Sell, 0.01), 0.77);
+
+        // Off unless MAX_PRICE_DRIFT is set
+        assert_eq!(PriceDriftConfig::default().check(Side::Buy, 0.40, 0.99), None);
+    }
+}
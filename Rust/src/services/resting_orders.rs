@@ -0,0 +1,108 @@
+// Resting limit orders
+// GTD limit orders posted for drifted copies that are still on the book, until they are settled
+
+use std::sync::{Mutex, MutexGuard};
+use crate::interfaces::ledger::OrderRecord;
+use crate::utils::constants::DB_FIELDS;
+
+/// GTD limit order left on the book by a drifted copy, until it fills or expires
+#[derive(Debug, Clone)]
+pub struct RestingLimit {
+    /// As recorded in the order ledger
+    pub order: OrderRecord,
+    pub event_slug: String,
+    /// Side of the copied trade, which keys it in the journal
+    pub trade_side: String,
+    /// Tokens the order can match
+    pub size: f64,
+    /// Tokens already recorded as filled in the ledger
+    pub recorded_tokens: f64,
+    /// Tokens the copy filled with market orders before the limit was posted
+    pub prior_tokens: f64,
+    pub attempts: u32,
+    /// Unix seconds after which what is left is cancelled
+    pub expires_at: i64,
+}
+
+/// Unmatched notional of a resting BUY, which is not in the fill ledger yet
+#[derive(Debug, Clone, PartialEq)]
+pub struct RestingBuy {
+    pub trader_address: String,
+    pub condition_id: String,
+    pub event_slug: String,
+    pub usdc: f64,
+}
+
+static RESTING_LIMITS: Mutex<Vec<RestingLimit>> = Mutex::new(Vec::new());
+
+fn resting_limits() -> MutexGuard<'static, Vec<RestingLimit>> {
+    RESTING_LIMITS.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Track a limit order that stays on the book
+pub fn track(limit: RestingLimit) {
+    resting_limits().push(limit);
+}
+
+/// Take every tracked order for settling; hand back the unsettled ones with `restore`
+pub fn take_all() -> Vec<RestingLimit> {
+    std::mem::take(&mut *resting_limits())
+}
+
+/// Track orders again that could not be settled yet
+pub fn restore(limits: Vec<RestingLimit>) {
+    resting_limits().extend(limits);
+}
+
+fn unmatched_buys(limits: &[RestingLimit]) -> Vec<RestingBuy> {
+    limits
+        .iter()
+        .filter(|limit| limit.order.side == DB_FIELDS::SIDE_BUY)
+        .map(|limit| RestingBuy {
+            trader_address: limit.order.trader_address.clone(),
+            condition_id: limit.order.condition_id.clone(),
+            event_slug: limit.event_slug.clone(),
+            usdc: (limit.size - limit.recorded_tokens).max(0.0) * limit.order.price,
+        })
+        .collect()
+}
+
+/// What the BUY limit orders resting on the book can still spend
+pub fn resting_buys() -> Vec<RestingBuy> {
+    unmatched_buys(&resting_limits())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_unmatched_buys_are_counted() {
+        let limit = |side: &str, size: f64, recorded_tokens: f64| RestingLimit {
+            order: OrderRecord {
+                trader_address: "0xTrader".to_string(),
+                condition_id: "0xC".to_string(),
+                side: side.to_string(),
+                price: 0.5,
+                ..Default::default()
+            },
+            event_slug: "finals".to_string(),
+            trade_side: side.to_string(),
+            size,
+            recorded_tokens,
+            prior_tokens: 0.0,
+            attempts: 1,
+            expires_at: 0,
+        };
+        let buys = unmatched_buys(&[limit("BUY", 40.0, 10.0), limit("SELL", 100.0, 0.0)]);
+        assert_eq!(
+            buys,
+            vec![RestingBuy {
+                trader_address: "0xTrader".to_string(),
+                condition_id: "0xC".to_string(),
+                event_slug: "finals".to_string(),
+                usdc: 15.0,
+            }]
+        );
+    }
+}
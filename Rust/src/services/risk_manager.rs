@@ -0,0 +1,338 @@
+// Risk manager
+// Exposure caps per market, per event, per followed trader and on the whole portfolio, applied to sized BUYs
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use crate::config::copy_strategy::OrderSizeCalculation;
+use crate::config::env::var;
+use crate::interfaces::user::UserActivityInterface;
+use crate::services::pnl::{ledger_engine, PnlReport, PnlTotals};
+use crate::utils::errors::AppResult;
+use crate::services::resting_orders::resting_buys;
+
+/// Exposure cap that limited an order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RiskCap {
+    /// MAX_MARKET_EXPOSURE_USD, per condition ID
+    Market,
+    /// MAX_EVENT_EXPOSURE_USD, all outcomes of an event together
+    Event,
+    /// MAX_TRADER_EXPOSURE_USD, copies of one followed trader
+    Trader,
+    /// MAX_PORTFOLIO_EXPOSURE_PERCENT of total equity
+    Portfolio,
+}
+
+impl RiskCap {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RiskCap::Market => "MARKET_EXPOSURE",
+            RiskCap::Event => "EVENT_EXPOSURE",
+            RiskCap::Trader => "TRADER_EXPOSURE",
+            RiskCap::Portfolio => "PORTFOLIO_EXPOSURE",
+        }
+    }
+}
+
+impl fmt::Display for RiskCap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An order trimmed or rejected by an exposure cap
+#[derive(Debug, Clone, PartialEq)]
+pub struct RiskLimit {
+    pub cap: RiskCap,
+    /// Cap in USD
+    pub limit_usd: f64,
+    /// Exposure already held under the cap
+    pub exposure_usd: f64,
+    /// Order size before the cap
+    pub requested_usd: f64,
+    /// Order size the cap leaves room for; 0 when rejected
+    pub allowed_usd: f64,
+    /// The trade lacks the conditionId or event slug the cap is keyed by
+    pub unknown: bool,
+}
+
+impl RiskLimit {
+    pub fn rejected(&self) -> bool {
+        self.allowed_usd <= 0.0
+    }
+
+    /// Machine-readable reason, e.g. `MARKET_EXPOSURE_TRIMMED`
+    pub fn code(&self) -> String {
+        let outcome = if self.unknown {
+            "UNKNOWN"
+        } else if self.rejected() {
+            "REJECTED"
+        } else {
+            "TRIMMED"
+        };
+        format!("{}_{}", self.cap, outcome)
+    }
+}
+
+/// USD at risk in open positions, at cost
+#[derive(Debug, Clone, Default)]
+pub struct Exposure {
+    /// Keyed by condition ID
+    pub by_market: HashMap<String, f64>,
+    /// Keyed by event slug
+    pub by_event: HashMap<String, f64>,
+    /// Keyed by lowercase trader address
+    pub by_trader: HashMap<String, f64>,
+    pub total: f64,
+}
+
+impl Exposure {
+    /// Open cost basis of a PnL report
+    pub fn from_report(report: &PnlReport) -> Self {
+        let open_cost = |totals: &BTreeMap<String, PnlTotals>| {
+            totals.iter().map(|(key, totals)| (key.to_lowercase(), totals.open_cost)).collect()
+        };
+        Self {
+            by_market: open_cost(&report.by_market),
+            by_event: open_cost(&report.by_event),
+            by_trader: open_cost(&report.by_trader),
+            total: report.overall.open_cost,
+        }
+    }
+
+    /// Exposure of everything bought through the fill ledger, plus resting BUY limit orders
+    ///
+    /// Resolved markets count until they are sold or redeemed, which errs on the safe side.
+    pub async fn load() -> AppResult<Self> {
+        let (engine, _) = ledger_engine().await?;
+        let mut exposure = Self::from_report(&engine.report(&HashMap::new()));
+        // Resting BUY limits are not in the fill ledger until they match
+        for buy in resting_buys() {
+            exposure.add(&buy.trader_address, &buy.condition_id, &buy.event_slug, buy.usdc);
+        }
+        Ok(exposure)
+    }
+
+    /// Count `usd` more against a trader, a market and its event
+    pub fn add(&mut self, trader_address: &str, condition_id: &str, event_slug: &str, usd: f64) {
+        *self.by_trader.entry(trader_address.to_lowercase()).or_default() += usd;
+        *self.by_market.entry(condition_id.to_lowercase()).or_default() += usd;
+        if !event_slug.is_empty() {
+            *self.by_event.entry(event_slug.to_lowercase()).or_default() += usd;
+        }
+        self.total += usd;
+    }
+
+    fn get(map: &HashMap<String, f64>, key: &str) -> f64 {
+        map.get(&key.to_lowercase()).copied().unwrap_or(0.0)
+    }
+}
+
+/// Exposure caps and kill switch limits; unset limits are not enforced
+#[derive(Debug, Clone, Default)]
+pub struct RiskConfig {
+    pub max_market_exposure_usd: Option<f64>,
+    pub max_event_exposure_usd: Option<f64>,
+    pub max_trader_exposure_usd: Option<f64>,
+    pub max_portfolio_exposure_percent: Option<f64>,
+    /// Loss since the start of the UTC day that halts BUYs
+    pub max_daily_loss_usd: Option<f64>,
+    /// Equity drawdown from the high-water mark that halts BUYs
+    pub max_drawdown_percent: Option<f64>,
+    /// Sell every open position when BUYs are halted
+    pub flatten_on_halt: bool,
+    /// Where the halt and the equity baselines are kept between restarts
+    pub kill_switch_file: String,
+}
+
+impl RiskConfig {
+    pub fn from_env(dry_run: bool) -> Self {
+        let parsed = |key: &str| var(key).ok().and_then(|v| v.trim().parse::<f64>().ok());
+        // Paper equity has its own baselines so it never trips the live kill switch
+        let default_file = if dry_run { ".kill_switch.paper.json" } else { ".kill_switch.json" };
+        Self {
+            max_market_exposure_usd: parsed("MAX_MARKET_EXPOSURE_USD"),
+            max_event_exposure_usd: parsed("MAX_EVENT_EXPOSURE_USD"),
+            max_trader_exposure_usd: parsed("MAX_TRADER_EXPOSURE_USD"),
+            max_portfolio_exposure_percent: parsed("MAX_PORTFOLIO_EXPOSURE_PERCENT"),
+            max_daily_loss_usd: parsed("MAX_DAILY_LOSS_USD"),
+            max_drawdown_percent: parsed("MAX_DRAWDOWN_PERCENT"),
+            flatten_on_halt: var("FLATTEN_ON_HALT").ok().and_then(|v| v.trim().parse().ok()).unwrap_or(false),
+            kill_switch_file: var("KILL_SWITCH_FILE").unwrap_or_else(|_| default_file.to_string()),
+        }
+    }
+
+    pub fn has_kill_switch(&self) -> bool {
+        self.max_daily_loss_usd.is_some() || self.max_drawdown_percent.is_some()
+    }
+
+    pub fn has_exposure_caps(&self) -> bool {
+        self.max_market_exposure_usd.is_some()
+            || self.max_event_exposure_usd.is_some()
+            || self.max_trader_exposure_usd.is_some()
+            || self.max_portfolio_exposure_percent.is_some()
+    }
+
+    /// Trim a sized BUY copying `trade` so that no cap is exceeded once it fills
+    ///
+    /// Total equity is `balance` plus the cost of the open positions. Every cap that limits the
+    /// order is recorded in `calc.risk_limits`; an order left below `min_order_usd` is rejected.
+    /// A market or event cap cannot be checked without the key it is grouped by, so such a
+    /// trade is rejected as `*_EXPOSURE_UNKNOWN`.
+    pub fn apply(
+        &self,
+        calc: &mut OrderSizeCalculation,
+        exposure: &Exposure,
+        trade: &UserActivityInterface,
+        trader_address: &str,
+        balance: f64,
+        min_order_usd: f64,
+    ) {
+        if calc.final_amount <= 0.0 {
+            return;
+        }
+        let equity = balance + exposure.total;
+        let caps = [
+            (RiskCap::Market, self.max_market_exposure_usd, &trade.condition_id, &exposure.by_market),
+            (RiskCap::Event, self.max_event_exposure_usd, &trade.event_slug, &exposure.by_event),
+        ];
+        let mut checks: Vec<(RiskCap, f64, f64)> = Vec::new();
+        for (cap, limit, key, held) in caps {
+            let Some(limit) = limit else { continue };
+            if key.is_empty() {
+                calc.reasoning.push_str(&format!(" → {} cap ${:.2}: market metadata unknown", cap, limit));
+                calc.risk_limits.push(RiskLimit {
+                    cap,
+                    limit_usd: limit,
+                    exposure_usd: 0.0,
+                    requested_usd: calc.final_amount,
+                    allowed_usd: 0.0,
+                    unknown: true,
+                });
+                calc.final_amount = 0.0;
+                return;
+            }
+            checks.push((cap, limit, Exposure::get(held, key)));
+        }
+        if let Some(limit) = self.max_trader_exposure_usd {
+            checks.push((RiskCap::Trader, limit, Exposure::get(&exposure.by_trader, trader_address)));
+        }
+        if let Some(percent) = self.max_portfolio_exposure_percent {
+            checks.push((RiskCap::Portfolio, equity * percent / 100.0, exposure.total));
+        }
+
+        for (cap, limit_usd, exposure_usd) in checks {
+            let headroom = (limit_usd - exposure_usd).max(0.0);
+            if calc.final_amount <= headroom {
+                continue;
+            }
+            calc.reasoning.push_str(&format!(
+                " → {} cap ${:.2} with ${:.2} held: ${:.2} → ${:.2}",
+                cap, limit_usd, exposure_usd, calc.final_amount, headroom
+            ));
+            calc.risk_limits.push(RiskLimit {
+                cap,
+                limit_usd,
+                exposure_usd,
+                requested_usd: calc.final_amount,
+                allowed_usd: headroom,
+                unknown: false,
+            });
+            calc.final_amount = headroom;
+        }
+
+        if !calc.risk_limits.is_empty() && calc.final_amount < min_order_usd {
+            calc.reasoning.push_str(&format!(" → Below minimum ${:.2} after risk limits", min_order_usd));
+            if let Some(last) = calc.risk_limits.last_mut() {
+                last.allowed_usd = 0.0;
+            }
+            calc.final_amount = 0.0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::copy_strategy::{CopyStrategy, CopyStrategyConfig};
+
+    const TRADER: &str = "0x1111111111111111111111111111111111111111";
+
+    fn sized(amount: f64) -> OrderSizeCalculation {
+        let config = CopyStrategyConfig {
+            strategy: CopyStrategy::FIXED,
+            copy_size: amount,
+            max_order_size_usd: 1_000.0,
+            ..Default::default()
+        };
+        config.calculate_order_size(amount, 10_000.0, 0.0)
+    }
+
+    fn trade() -> UserActivityInterface {
+        serde_json::from_value(serde_json::json!({ "timestamp": 1, "conditionId": "0xC", "eventSlug": "finals" })).unwrap()
+    }
+
+    fn exposure() -> Exposure {
+        Exposure {
+            by_market: HashMap::from([("0xc".to_string(), 40.0)]),
+            by_event: HashMap::from([("finals".to_string(), 70.0)]),
+            by_trader: HashMap::from([(TRADER.to_string(), 100.0)]),
+            total: 200.0,
+        }
+    }
+
+    #[test]
+    fn tightest_cap_trims_the_order_and_is_recorded() {
+        let config = RiskConfig {
+            max_market_exposure_usd: Some(100.0),
+            max_event_exposure_usd: Some(95.0),
+            max_trader_exposure_usd: Some(500.0),
+            ..Default::default()
+        };
+        let mut calc = sized(50.0);
+        config.apply(&mut calc, &exposure(), &trade(), &TRADER.to_uppercase().replace("0X", "0x"), 800.0, 1.0);
+
+        assert_eq!(calc.final_amount, 25.0);
+        let codes: Vec<String> = calc.risk_limits.iter().map(RiskLimit::code).collect();
+        assert_eq!(codes, vec!["EVENT_EXPOSURE_TRIMMED"]);
+        assert_eq!((calc.risk_limits[0].requested_usd, calc.risk_limits[0].exposure_usd), (50.0, 70.0));
+    }
+
+    #[test]
+    fn order_without_headroom_is_rejected() {
+        let config = RiskConfig {
+            max_trader_exposure_usd: Some(120.0),
+            // 20% of $1000 equity is already held
+            max_portfolio_exposure_percent: Some(20.0),
+            ..Default::default()
+        };
+        let mut calc = sized(30.0);
+        config.apply(&mut calc, &exposure(), &trade(), TRADER, 800.0, 1.0);
+
+        assert_eq!(calc.final_amount, 0.0);
+        let codes: Vec<String> = calc.risk_limits.iter().map(RiskLimit::code).collect();
+        assert_eq!(codes, vec!["TRADER_EXPOSURE_TRIMMED", "PORTFOLIO_EXPOSURE_REJECTED"]);
+
+        let mut calc = sized(30.0);
+        RiskConfig::default().apply(&mut calc, &exposure(), &trade(), TRADER, 800.0, 1.0);
+        assert_eq!((calc.final_amount, calc.risk_limits.len()), (30.0, 0));
+    }
+
+    #[test]
+    fn trade_without_the_key_of_a_configured_cap_is_rejected() {
+        let config = RiskConfig { max_event_exposure_usd: Some(500.0), ..Default::default() };
+        let mut unknown_event = trade();
+        unknown_event.event_slug.clear();
+        let mut calc = sized(30.0);
+        config.apply(&mut calc, &exposure(), &unknown_event, TRADER, 800.0, 1.0);
+        assert_eq!(calc.final_amount, 0.0);
+        let codes: Vec<String> = calc.risk_limits.iter().map(RiskLimit::code).collect();
+        assert_eq!(codes, vec!["EVENT_EXPOSURE_UNKNOWN"]);
+
+        // Caps that are not configured need no key
+        let config = RiskConfig { max_market_exposure_usd: Some(500.0), ..Default::default() };
+        let mut calc = sized(30.0);
+        config.apply(&mut calc, &exposure(), &unknown_event, TRADER, 800.0, 1.0);
+        assert_eq!((calc.final_amount, calc.risk_limits.len()), (30.0, 0));
+    }
+}
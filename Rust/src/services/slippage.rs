@@ -0,0 +1,155 @@
+// Slippage estimation
+// Walks a fetched order book before posting to price an order and bound it by MAX_SLIPPAGE_BPS
+
+use crate::interfaces::user::OrderBook;
+use crate::services::clob_client::Side;
+use crate::services::paper_wallet::sorted_levels;
+
+/// Amounts below this are rounding noise
+const EPSILON: f64 = 1e-9;
+
+/// Slippage of `price` from `best_price` in basis points; positive when worse for `side`
+pub fn slippage_bps(side: Side, best_price: f64, price: f64) -> f64 {
+    if best_price <= 0.0 {
+        return 0.0;
+    }
+    let worse = match side {
+        Side::Buy => price - best_price,
+        Side::Sell => best_price - price,
+    };
+    worse / best_price * 10_000.0
+}
+
+/// What an order would fill walking the current book, best level first
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct FillEstimate {
+    /// Best ask (BUY) or best bid (SELL)
+    pub best_price: f64,
+    /// Volume-weighted fill price
+    pub vwap: f64,
+    /// Last level touched; the order's worst acceptable price
+    pub worst_price: f64,
+    pub usdc: f64,
+    pub tokens: f64,
+    pub levels: usize,
+    /// The order was shrunk to keep the VWAP within the slippage bound
+    pub slippage_limited: bool,
+}
+
+impl FillEstimate {
+    /// Expected slippage of the VWAP from the best price
+    pub fn bps(&self, side: Side) -> f64 {
+        slippage_bps(side, self.best_price, self.vwap)
+    }
+
+    /// Size in the order's unit: USDC for a BUY, tokens for a SELL
+    pub fn amount(&self, side: Side) -> f64 {
+        match side {
+            Side::Buy => self.usdc,
+            Side::Sell => self.tokens,
+        }
+    }
+}
+
+/// Estimate filling `amount` (USDC for a BUY, tokens for a SELL) against `book`
+///
+/// Levels past `limit_price` are never touched. With `max_bps`, the order stops where its VWAP
+/// would slip more than that from the best price, taking part of the last level if it fits.
+/// An empty book gives an empty estimate.
+pub fn estimate_fill(
+    book: &OrderBook,
+    side: Side,
+    amount: f64,
+    limit_price: Option<f64>,
+    max_bps: Option<f64>,
+) -> FillEstimate {
+    let levels = match side {
+        Side::Buy => sorted_levels(&book.asks, false),
+        Side::Sell => sorted_levels(&book.bids, true),
+    };
+    let Some(&(best_price, _)) = levels.first() else {
+        return FillEstimate::default();
+    };
+    let mut estimate = FillEstimate { best_price, worst_price: best_price, ..Default::default() };
+    // Worst VWAP allowed by the slippage bound
+    let vwap_bound = max_bps.map(|bps| match side {
+        Side::Buy => best_price * (1.0 + bps / 10_000.0),
+        Side::Sell => best_price * (1.0 - bps / 10_000.0),
+    });
+
+    for (price, size) in levels {
+        if limit_price.is_some_and(|limit| slippage_bps(side, limit, price) > EPSILON) {
+            break;
+        }
+        let left = amount - estimate.amount(side);
+        if left <= EPSILON {
+            break;
+        }
+        let mut tokens = match side {
+            Side::Buy => size.min(left / price),
+            Side::Sell => size.min(left),
+        };
+        if let Some(bound) = vwap_bound {
+            // Tokens x at `price` keep (usdc + x * price) / (tokens + x) on the right side of the bound
+            let room = match side {
+                Side::Buy if price > bound => (bound * estimate.tokens - estimate.usdc) / (price - bound),
+                Side::Sell if price < bound => (estimate.usdc - bound * estimate.tokens) / (bound - price),
+                _ => f64::INFINITY,
+            };
+            if room < tokens {
+                tokens = room.max(0.0);
+                estimate.slippage_limited = true;
+            }
+        }
+        if tokens <= EPSILON {
+            break;
+        }
+        estimate.usdc += tokens * price;
+        estimate.tokens += tokens;
+        estimate.worst_price = price;
+        estimate.levels += 1;
+        if estimate.slippage_limited {
+            break;
+        }
+    }
+    estimate.vwap = if estimate.tokens > 0.0 { estimate.usdc / estimate.tokens } else { best_price };
+    estimate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interfaces::user::tests::book;
+
+    #[test]
+    fn walks_the_book_and_reports_vwap_and_worst_level() {
+        // $5.20 at 0.52, then $22 at 0.55: 50 tokens for $27.20
+        let estimate = estimate_fill(&book(), Side::Buy, 27.2, None, None);
+        assert_eq!((estimate.levels, estimate.worst_price, estimate.slippage_limited), (2, 0.55, false));
+        assert!((estimate.tokens - 50.0).abs() < 1e-9);
+        assert!((estimate.bps(Side::Buy) - 461.54).abs() < 0.01);
+
+        // Levels past the limit price are left alone
+        let estimate = estimate_fill(&book(), Side::Buy, 500.0, Some(0.55), None);
+        assert_eq!(estimate.worst_price, 0.55);
+        assert!((estimate.usdc - 60.2).abs() < 1e-9);
+
+        let estimate = estimate_fill(&book(), Side::Sell, 50.0, None, None);
+        assert_eq!((estimate.levels, estimate.worst_price), (2, 0.48));
+        assert!((estimate.usdc - 24.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn slippage_bound_shrinks_the_order() {
+        // 500 bps above 0.52 allows a VWAP of 0.546: 10 tokens at 0.52 plus 65 at 0.55
+        let estimate = estimate_fill(&book(), Side::Buy, 60.0, None, Some(500.0));
+        assert!(estimate.slippage_limited);
+        assert!((estimate.tokens - 75.0).abs() < 1e-9);
+        assert!((estimate.bps(Side::Buy) - 500.0).abs() < 1e-6);
+
+        // Within the bound, nothing changes
+        let estimate = estimate_fill(&book(), Side::Sell, 10.0, None, Some(1.0));
+        assert_eq!((estimate.tokens, estimate.slippage_limited), (10.0, false));
+        assert_eq!(estimate_fill(&OrderBook { bids: vec![], asks: vec![] }, Side::Buy, 10.0, None, None).levels, 0);
+    }
+}
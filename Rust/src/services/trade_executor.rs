@@ -2,28 +2,27 @@
 
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::collections::HashMap;
-use tokio::time::{sleep, Duration, Instant};
-use mongodb::bson::doc;
-use futures::stream::TryStreamExt;
+use tokio::time::{interval, Duration, Instant, MissedTickBehavior};
+use mongodb::bson::oid::ObjectId;
 use crate::config::env::ENV;
 use crate::utils::logger::Logger;
 use crate::utils::constants::{DB_FIELDS, TRADING_CONSTANTS};
-use crate::utils::fetch_data::fetch_data;
-use crate::utils::get_my_balance::get_my_balance;
-use crate::models::user_history::get_user_activity_collection;
-use crate::interfaces::user::{UserActivityInterface, UserPositionInterface};
-
-// Placeholder for CLOB client type - will need proper implementation
-pub type ClobClient = (); // TODO: Replace with actual CLOB client type
+use crate::utils::post_order::{post_order, settle_limit_orders};
+use crate::interfaces::execution::{ExecutionState, ExecutionStatus};
+use crate::interfaces::user::UserActivityInterface;
+use crate::services::create_clob_client::ClobClient;
+use crate::services::exit_manager::check_exits;
+use crate::services::kill_switch::{buying_halted, check_kill_switch};
+use crate::services::trade_pipeline::{
+    claim, enrich_from_market, enrich_from_positions, journal, load_context, mark_skipped, route_trade, DetectedTrade, Route,
+    TradeReceiver,
+};
+use crate::storage::{TradeKey, TradeUpdate};
 
 static IS_RUNNING: AtomicBool = AtomicBool::new(true);
 
 /// Trade with user address attached
-#[derive(Debug, Clone)]
-struct TradeWithUser {
-    trade: UserActivityInterface,
-    user_address: String,
-}
+type TradeWithUser = DetectedTrade;
 
 /// Aggregated trade structure
 #[derive(Debug, Clone)]
@@ -107,10 +106,11 @@ fn add_to_aggregation_buffer(trade: TradeWithUser) {
     }
 }
 
-/// Check buffer and return ready aggregated trades
-fn get_ready_aggregated_trades() -> Vec<AggregatedTrade> {
+/// Check buffer and return aggregations whose window passed, split into ready and below-minimum
+fn get_ready_aggregated_trades() -> (Vec<AggregatedTrade>, Vec<AggregatedTrade>) {
     let buffer = get_aggregation_buffer();
     let mut ready = Vec::new();
+    let mut skipped = Vec::new();
     // PythonVersion uses milliseconds: int(time.time() * 1000)
     let now = chrono::Utc::now().timestamp_millis();
     let window_ms = ENV().trade_aggregation_window_seconds as i64 * 1000; // Convert to milliseconds
@@ -136,10 +136,7 @@ fn get_ready_aggregated_trades() -> Vec<AggregatedTrade> {
                     agg.trades.len(),
                     min_total_usd
                 ));
-                
-                // Mark all trades in this aggregation as processed
-                // Note: This will be done asynchronously in the main loop
-                // For now, just log that we're skipping them
+                skipped.push(agg.clone());
             }
             to_remove.push(key.clone());
         }
@@ -150,62 +147,53 @@ fn get_ready_aggregated_trades() -> Vec<AggregatedTrade> {
         buffer.remove(&key);
     }
     
-    ready
+    (ready, skipped)
 }
 
-/// Read unprocessed trades from database
-async fn read_temp_trades() -> anyhow::Result<Vec<TradeWithUser>> {
-    let mut all_trades = Vec::new();
-    let user_addresses = ENV().user_addresses.clone();
+/// Copy a trade: size it against current positions and balances, then execute
+async fn execute_trade(
+    clob_client: &ClobClient,
+    detected: &DetectedTrade,
+    trade: &mut UserActivityInterface,
+    label: &str,
+) {
+    let user_address = detected.user_address.as_str();
+    let context = load_context(user_address, trade, detected.position_snapshot).await;
+    enrich_from_positions(trade, &context);
+    enrich_from_market(trade).await;
     
-    for address in &user_addresses {
-        let collection = get_user_activity_collection(address.as_str());
-        
-        // Only get trades that haven't been processed yet
-        let filter = doc! {
-            "type": DB_FIELDS::TYPE_TRADE,
-            DB_FIELDS::BOT_EXECUTED: false,
-            DB_FIELDS::BOT_EXECUTED_TIME: 0
-        };
-        
-        // Query documents
-        let cursor = collection.find(filter).await?;
-        let docs: Vec<mongodb::bson::Document> = cursor.try_collect().await?;
-        
-        // Convert documents to UserActivityInterface
-        for doc in docs {
-            match mongodb::bson::from_document::<UserActivityInterface>(doc) {
-                Ok(activity) => {
-                    all_trades.push(TradeWithUser {
-                        trade: activity,
-                        user_address: address.clone(),
-                    });
-                }
-                Err(e) => {
-                    // Silently skip documents that can't be deserialized
-                    // This can happen if the document structure doesn't match
-                    let _ = e; // Suppress unused variable warning
-                }
-            }
-        }
-    }
+    Logger::balance(context.my_balance, context.user_balance, user_address);
     
-    Ok(all_trades)
+    // Trader sells are mirrored proportionally; "merge" (full liquidation) is not triggered by trades
+    let condition = if trade.side == DB_FIELDS::SIDE_BUY {
+        "buy"
+    } else {
+        "sell"
+    };
+    
+    if let Err(e) = post_order(
+        clob_client,
+        condition,
+        context.my_position.as_ref(),
+        context.user_position.as_ref(),
+        context.user_position_before,
+        trade,
+        context.my_balance,
+        context.user_balance,
+        user_address,
+    ).await {
+        Logger::error(&format!("Error executing {}: {}", label, e));
+        let update = TradeUpdate::new(ExecutionStatus::Failed { error: e.to_string() });
+        journal(user_address, TradeKey::of(trade), update).await;
+    }
 }
 
 /// Execute individual trades
-async fn do_trading(clob_client: &ClobClient, trades: &[TradeWithUser]) -> anyhow::Result<()> {
-    use crate::utils::post_order::post_order;
-    use crate::utils::constants::POLYMARKET_API;
-    use crate::config::env::ENV;
-    
+async fn do_trading(clob_client: &ClobClient, trades: &[TradeWithUser]) {
     for trade in trades {
-        // Mark trade as being processed immediately (using _id like PythonVersion)
-        let collection = get_user_activity_collection(&trade.user_address);
-        if let Some(trade_id) = &trade.trade._id {
-            let filter = doc! { "_id": trade_id };
-            let update = doc! { "$set": { DB_FIELDS::BOT_EXECUTED_TIME: 1 } };
-            let _ = collection.update_one(filter, update).await;
+        // Journal the pickup before placing orders
+        if !claim(trade).await {
+            continue;
         }
         
         Logger::trade(
@@ -223,74 +211,15 @@ async fn do_trading(clob_client: &ClobClient, trades: &[TradeWithUser]) -> anyho
             },
         );
         
-        // Fetch positions for both wallets
-        let proxy_wallet = ENV().proxy_wallet.clone();
-        let my_positions_url = format!(
-            "{}{}?user={}",
-            POLYMARKET_API::DATA_API_BASE,
-            POLYMARKET_API::POSITIONS_ENDPOINT,
-            proxy_wallet
-        );
-        let user_positions_url = format!(
-            "{}{}?user={}",
-            POLYMARKET_API::DATA_API_BASE,
-            POLYMARKET_API::POSITIONS_ENDPOINT,
-            trade.user_address
-        );
-        
-        let my_positions: Vec<UserPositionInterface> = fetch_data(&my_positions_url).await.unwrap_or_default();
-        let user_positions: Vec<UserPositionInterface> = fetch_data(&user_positions_url).await.unwrap_or_default();
-        
-        let my_position = my_positions.iter()
-            .find(|pos| pos.condition_id == trade.trade.condition_id);
-        let user_position = user_positions.iter()
-            .find(|pos| pos.condition_id == trade.trade.condition_id);
-        
-        // Get USDC balance
-        let my_balance = get_my_balance(&proxy_wallet).await.unwrap_or(0.0);
-        
-        // Calculate trader's total portfolio value from positions
-        let user_balance: f64 = user_positions.iter()
-            .map(|pos| pos.current_value)
-            .sum();
-        
-        Logger::balance(my_balance, user_balance, &trade.user_address);
-        
-        // Determine condition: buy, sell, or merge
-        let condition = if trade.trade.side == DB_FIELDS::SIDE_BUY {
-            "buy"
-        } else if my_position.is_some() {
-            "merge" // If we have a position and trader is selling, merge
-        } else {
-            "sell"
-        };
-        
-        // Execute the trade
-        if let Err(e) = post_order(
-            clob_client,
-            condition,
-            my_position,
-            user_position,
-            &trade.trade,
-            my_balance,
-            user_balance,
-            &trade.user_address,
-        ).await {
-            Logger::error(&format!("Error executing trade: {}", e));
-        }
+        let mut activity = trade.trade.clone();
+        execute_trade(clob_client, trade, &mut activity, "trade").await;
         
         Logger::separator();
     }
-    
-    Ok(())
 }
 
 /// Execute aggregated trades
-async fn do_aggregated_trading(clob_client: &ClobClient, aggregated_trades: &[AggregatedTrade]) -> anyhow::Result<()> {
-    use crate::utils::post_order::post_order;
-    use crate::utils::constants::POLYMARKET_API;
-    use crate::config::env::ENV;
-    
+async fn do_aggregated_trading(clob_client: &ClobClient, aggregated_trades: &[AggregatedTrade]) {
     for agg in aggregated_trades {
         Logger::header(&format!("📊 AGGREGATED TRADE ({} trades combined)", agg.trades.len()));
         Logger::info(&format!("Market: {}", agg.slug.as_ref().unwrap_or(&agg.asset)));
@@ -298,112 +227,93 @@ async fn do_aggregated_trading(clob_client: &ClobClient, aggregated_trades: &[Ag
         Logger::info(&format!("Total volume: ${:.2}", agg.total_usdc_size));
         Logger::info(&format!("Average price: ${:.4}", agg.average_price));
         
-        // Mark all individual trades as being processed (using _id like PythonVersion)
+        // Journal the pickup of all individual trades
+        let mut claimed = Vec::new();
         for trade in &agg.trades {
-            let collection = get_user_activity_collection(&trade.user_address);
-            if let Some(trade_id) = &trade.trade._id {
-                let filter = doc! { "_id": trade_id };
-                let update = doc! { "$set": { DB_FIELDS::BOT_EXECUTED_TIME: 1 } };
-                let _ = collection.update_one(filter, update).await;
+            if claim(trade).await {
+                claimed.push(trade);
             }
         }
+        let Some((first_trade, merged)) = claimed.split_first() else {
+            continue;
+        };
         
-        // Use first trade as template for aggregated trade
-        let first_trade = &agg.trades[0];
-        
-        // Fetch positions
-        let proxy_wallet = ENV().proxy_wallet.clone();
-        let my_positions_url = format!(
-            "{}{}?user={}",
-            POLYMARKET_API::DATA_API_BASE,
-            POLYMARKET_API::POSITIONS_ENDPOINT,
-            proxy_wallet
-        );
-        let user_positions_url = format!(
-            "{}{}?user={}",
-            POLYMARKET_API::DATA_API_BASE,
-            POLYMARKET_API::POSITIONS_ENDPOINT,
-            agg.user_address
-        );
-        
-        let my_positions: Vec<UserPositionInterface> = fetch_data(&my_positions_url).await.unwrap_or_default();
-        let user_positions: Vec<UserPositionInterface> = fetch_data(&user_positions_url).await.unwrap_or_default();
-        
-        let my_position = my_positions.iter()
-            .find(|pos| pos.condition_id == agg.condition_id);
-        let user_position = user_positions.iter()
-            .find(|pos| pos.condition_id == agg.condition_id);
-        
-        // Get balances
-        let my_balance = get_my_balance(&proxy_wallet).await.unwrap_or(0.0);
-        let user_balance: f64 = user_positions.iter()
-            .map(|pos| pos.current_value)
-            .sum();
-        
-        Logger::balance(my_balance, user_balance, &agg.user_address);
+        // First trade is the template for the aggregated trade and carries its execution state
         
         // Create synthetic trade using aggregated values
-        use mongodb::bson::oid::ObjectId;
-        let synthetic_trade = UserActivityInterface {
+        let mut synthetic_trade = UserActivityInterface {
             _id: first_trade.trade._id.or(Some(ObjectId::new())),
-            proxy_wallet: first_trade.trade.proxy_wallet.clone(),
-            timestamp: first_trade.trade.timestamp,
             condition_id: agg.condition_id.clone(),
-            r#type: first_trade.trade.r#type.clone(),
-            size: first_trade.trade.size,
             usdc_size: agg.total_usdc_size,
-            transaction_hash: first_trade.trade.transaction_hash.clone(),
             price: agg.average_price,
             asset: agg.asset.clone(),
             side: agg.side.clone(),
-            outcome_index: first_trade.trade.outcome_index,
-            title: first_trade.trade.title.clone(),
-            slug: first_trade.trade.slug.clone(),
-            icon: first_trade.trade.icon.clone(),
-            event_slug: first_trade.trade.event_slug.clone(),
-            outcome: first_trade.trade.outcome.clone(),
-            name: first_trade.trade.name.clone(),
-            pseudonym: first_trade.trade.pseudonym.clone(),
-            bio: first_trade.trade.bio.clone(),
-            profile_image: first_trade.trade.profile_image.clone(),
-            profile_image_optimized: first_trade.trade.profile_image_optimized.clone(),
-            bot: false,
-            bot_excuted_time: 0,
+            execution: ExecutionState::default(),
             my_bought_size: None,
+            ..first_trade.trade.clone()
         };
         
-        // Determine condition
-        let condition = if agg.side == DB_FIELDS::SIDE_BUY {
-            "buy"
-        } else if my_position.is_some() {
-            "merge"
-        } else {
-            "sell"
-        };
+        execute_trade(clob_client, first_trade, &mut synthetic_trade, "aggregated trade").await;
         
-        // Execute aggregated trade
-        if let Err(e) = post_order(
-            clob_client,
-            condition,
-            my_position,
-            user_position,
-            &synthetic_trade,
-            my_balance,
-            user_balance,
-            &agg.user_address,
-        ).await {
-            Logger::error(&format!("Error executing aggregated trade: {}", e));
+        for trade in merged {
+            let reason = format!("aggregated into {}", first_trade.trade.transaction_hash);
+            mark_skipped(trade, &reason).await;
         }
         
         Logger::separator();
     }
+}
+
+/// Filter a batch of detected trades, then execute or buffer them
+async fn handle_detected_trades(clob_client: &ClobClient, trades: Vec<TradeWithUser>) {
+    let aggregation_enabled = ENV().trade_aggregation_enabled;
+    let mut trades_to_execute = Vec::new();
     
-    Ok(())
+    for trade in trades {
+        match route_trade(&trade.trade, aggregation_enabled) {
+            Route::Execute => trades_to_execute.push(trade),
+            Route::Aggregate => {
+                let market_name = if !trade.trade.slug.is_empty() {
+                    trade.trade.slug.as_str()
+                } else {
+                    trade.trade.asset.as_str()
+                };
+                Logger::info(&format!(
+                    "Adding ${:.2} {} trade to aggregation buffer for {}",
+                    trade.trade.usdc_size,
+                    trade.trade.side,
+                    market_name
+                ));
+                add_to_aggregation_buffer(trade);
+            }
+            Route::Skip(reason) => {
+                Logger::info(&format!("Skipping {} ({})", trade.trade.transaction_hash, reason));
+                mark_skipped(&trade, reason).await;
+            }
+        }
+    }
+    
+    if trades_to_execute.is_empty() {
+        return;
+    }
+    
+    Logger::clear_line();
+    if aggregation_enabled {
+        // Large trades are executed immediately (not aggregated)
+        Logger::header("⚡ IMMEDIATE TRADE (above threshold)");
+    } else {
+        Logger::header(&format!(
+            "⚡ {} NEW TRADE{} TO COPY",
+            trades_to_execute.len(),
+            if trades_to_execute.len() > 1 { "S" } else { "" }
+        ));
+    }
+    do_trading(clob_client, &trades_to_execute).await;
 }
 
 /// Main trade executor function
-/// Monitors database for new trades and executes them
-pub async fn trade_executor(_clob_client: ClobClient) -> anyhow::Result<()> {
+/// Receives newly detected trades from the monitor and executes them
+pub async fn trade_executor(clob_client: ClobClient, mut trades: TradeReceiver) -> anyhow::Result<()> {
     let user_addresses = ENV().user_addresses.clone();
     Logger::success(&format!("Trade executor ready for {} trader(s)", user_addresses.len()));
     
@@ -415,119 +325,87 @@ pub async fn trade_executor(_clob_client: ClobClient) -> anyhow::Result<()> {
         ));
     }
     
+    // Drives the aggregation window and the waiting animation while no trades arrive
+    let mut ticker = interval(Duration::from_millis(300));
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
     let mut last_check = Instant::now();
     
+    // While the kill switch is tripped, BUYs are skipped but SELLs are still copied
+    let mut kill_switch_ticker = interval(Duration::from_secs(TRADING_CONSTANTS::KILL_SWITCH_CHECK_INTERVAL_SECONDS));
+    kill_switch_ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    
+    // Stop-loss, take-profit and trailing-stop exits; a no-op unless thresholds are configured
+    let mut exit_ticker = interval(Duration::from_secs(TRADING_CONSTANTS::EXIT_CHECK_INTERVAL_SECONDS));
+    exit_ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    
+    // Limit orders posted for copies the book drifted away from
+    let mut limit_ticker = interval(Duration::from_secs(TRADING_CONSTANTS::LIMIT_ORDER_CHECK_INTERVAL_SECONDS));
+    limit_ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    
     while IS_RUNNING.load(Ordering::SeqCst) {
-        match read_temp_trades().await {
-            Ok(trades) => {
+        tokio::select! {
+            received = trades.recv() => {
+                let Some(first) = received else {
+                    Logger::warning("Trade channel closed");
+                    break;
+                };
+                let mut batch = vec![first];
+                while let Ok(trade) = trades.try_recv() {
+                    batch.push(trade);
+                }
+                
+                Logger::clear_line();
                 if ENV().trade_aggregation_enabled {
-                    // Process with aggregation logic
-                    if !trades.is_empty() {
-                        Logger::clear_line();
-                        Logger::info(&format!(
-                            "📥 {} new trade{} detected",
-                            trades.len(),
-                            if trades.len() > 1 { "s" } else { "" }
-                        ));
-                        
-                        // Separate trades into those to aggregate and those to execute immediately
-                        let mut trades_to_aggregate = Vec::new();
-                        let mut trades_to_execute = Vec::new();
-                        
-                        for trade in trades {
-                            // Only aggregate BUY trades below minimum threshold
-                            if trade.trade.side == DB_FIELDS::SIDE_BUY
-                                && trade.trade.usdc_size < TRADING_CONSTANTS::TRADE_AGGREGATION_MIN_TOTAL_USD
-                            {
-                                let market_name = if !trade.trade.slug.is_empty() {
-                                    trade.trade.slug.as_str()
-                                } else {
-                                    trade.trade.asset.as_str()
-                                };
-                                Logger::info(&format!(
-                                    "Adding ${:.2} {} trade to aggregation buffer for {}",
-                                    trade.trade.usdc_size,
-                                    trade.trade.side,
-                                    market_name
-                                ));
-                                trades_to_aggregate.push(trade);
-                            } else {
-                                trades_to_execute.push(trade);
-                            }
-                        }
-                        
-                        // Add to aggregation buffer
-                        for trade in trades_to_aggregate {
-                            add_to_aggregation_buffer(trade);
-                        }
-                        
-                        // Execute large trades immediately (not aggregated)
-                        if !trades_to_execute.is_empty() {
-                            Logger::clear_line();
-                            Logger::header("⚡ IMMEDIATE TRADE (above threshold)");
-                            let _ = do_trading(&_clob_client, &trades_to_execute).await;
-                        }
-                        last_check = Instant::now();
-                    }
-                    
-                    // Check for ready aggregated trades
-                    let ready_aggregations = get_ready_aggregated_trades();
-                    if !ready_aggregations.is_empty() {
-                        Logger::clear_line();
-                        Logger::header(&format!(
-                            "⚡ {} AGGREGATED TRADE{} READY",
-                            ready_aggregations.len(),
-                            if ready_aggregations.len() > 1 { "S" } else { "" }
-                        ));
-                        let _ = do_aggregated_trading(&_clob_client, &ready_aggregations).await;
-                        last_check = Instant::now();
-                    }
-                    
-                    // Update waiting message
-                    if ready_aggregations.is_empty() {
-                        if last_check.elapsed().as_millis() > 300 {
-                            let buffered_count = get_aggregation_buffer().len();
-                            if buffered_count > 0 {
-                                Logger::waiting(
-                                    user_addresses.len(),
-                                    Some(&format!("{} trade group(s) pending", buffered_count))
-                                );
-                            } else {
-                                Logger::waiting(user_addresses.len(), None);
-                            }
-                            last_check = Instant::now();
-                        }
-                    }
-                } else {
-                    // Original non-aggregation logic
-                    if !trades.is_empty() {
-                        Logger::clear_line();
-                        Logger::header(&format!(
-                            "⚡ {} NEW TRADE{} TO COPY",
-                            trades.len(),
-                            if trades.len() > 1 { "S" } else { "" }
-                        ));
-                        let _ = do_trading(&_clob_client, &trades).await;
-                        last_check = Instant::now();
-                    } else {
-                        // Update waiting message every 300ms for smooth animation
-                        if last_check.elapsed().as_millis() > 300 {
-                            Logger::waiting(user_addresses.len(), None);
-                            last_check = Instant::now();
-                        }
-                    }
+                    Logger::info(&format!(
+                        "📥 {} new trade{} detected",
+                        batch.len(),
+                        if batch.len() > 1 { "s" } else { "" }
+                    ));
                 }
+                handle_detected_trades(&clob_client, batch).await;
+                last_check = Instant::now();
             }
-            Err(e) => {
-                Logger::error(&format!("Error reading trades: {}", e));
-            }
+            _ = ticker.tick() => {}
+            _ = kill_switch_ticker.tick() => check_kill_switch(&clob_client).await,
+            _ = exit_ticker.tick() => check_exits(&clob_client).await,
+            _ = limit_ticker.tick() => settle_limit_orders(&clob_client).await,
         }
         
-        if !IS_RUNNING.load(Ordering::SeqCst) {
-            break;
+        if ENV().trade_aggregation_enabled {
+            // Check for ready aggregated trades
+            let (ready_aggregations, skipped_aggregations) = get_ready_aggregated_trades();
+            for agg in &skipped_aggregations {
+                for trade in &agg.trades {
+                    mark_skipped(trade, "aggregated total below minimum").await;
+                }
+            }
+            if !ready_aggregations.is_empty() {
+                Logger::clear_line();
+                Logger::header(&format!(
+                    "⚡ {} AGGREGATED TRADE{} READY",
+                    ready_aggregations.len(),
+                    if ready_aggregations.len() > 1 { "S" } else { "" }
+                ));
+                do_aggregated_trading(&clob_client, &ready_aggregations).await;
+                last_check = Instant::now();
+            }
         }
         
-        sleep(Duration::from_millis(300)).await; // 300ms polling interval
+        // Update waiting message every 300ms for smooth animation
+        if last_check.elapsed().as_millis() > 300 {
+            let buffered_count = get_aggregation_buffer().len();
+            if let Some(halt) = buying_halted() {
+                Logger::waiting(user_addresses.len(), Some(&format!("BUYs halted: {}", halt.trigger)));
+            } else if buffered_count > 0 {
+                Logger::waiting(
+                    user_addresses.len(),
+                    Some(&format!("{} trade group(s) pending", buffered_count))
+                );
+            } else {
+                Logger::waiting(user_addresses.len(), None);
+            }
+            last_check = Instant::now();
+        }
     }
     
     Logger::info("Trade executor stopped");
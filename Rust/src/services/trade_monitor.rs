@@ -1,16 +1,21 @@
 // Trade monitoring service
 
+use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicBool, Ordering};
-use tokio::time::{sleep, Duration};
-use mongodb::bson::doc;
+use std::sync::{Arc, OnceLock};
+use tokio::sync::{mpsc, Mutex, RwLock};
+use tokio::time::{sleep, timeout_at, Duration, Instant};
 use chrono::Utc;
-use futures::stream::TryStreamExt;
 use crate::config::env::ENV;
 use crate::utils::logger::Logger;
-use crate::utils::constants::{POLYMARKET_API, TIME_CONSTANTS, DB_FIELDS};
+use crate::services::onchain_monitor::run_onchain_monitor;
+use crate::services::pnl::compute_pnl;
+use crate::services::trade_pipeline::{DetectedTrade, TradeSender};
+use crate::services::clob_ws::{run_market_subscriber, MarketSubscription, TradePrint, WsSettings};
+use crate::utils::constants::{POLYMARKET_API, TIME_CONSTANTS, DB_FIELDS, WS_CONSTANTS};
 use crate::utils::fetch_data::fetch_data;
 use crate::utils::get_my_balance::get_my_balance;
-use crate::models::user_history::{get_user_activity_collection, get_user_position_collection};
+use crate::storage::{storage, HISTORICAL_TRADE};
 use crate::interfaces::user::{UserActivityInterface, UserPositionInterface};
 
 static IS_RUNNING: AtomicBool = AtomicBool::new(true);
@@ -29,8 +34,7 @@ pub async fn init() -> anyhow::Result<()> {
     let mut counts = Vec::new();
     
     for address in &user_addresses {
-        let collection = get_user_activity_collection(address.as_str());
-        let count = collection.count_documents(doc! {}).await?;
+        let count = storage().count_trades(address).await?;
         counts.push(count as usize);
     }
     
@@ -41,7 +45,7 @@ pub async fn init() -> anyhow::Result<()> {
     let proxy_wallet = ENV().proxy_wallet.clone();
     match get_my_positions(&proxy_wallet).await {
         Ok(positions) => {
-            let current_balance = get_my_balance(&proxy_wallet).await.unwrap_or_else(|_| 0.0);
+            let current_balance = get_my_balance(&proxy_wallet).await.unwrap_or(0.0);
             
             if !positions.is_empty() {
                 // Calculate overall profitability
@@ -84,33 +88,17 @@ pub async fn init() -> anyhow::Result<()> {
     let mut profitabilities = Vec::new();
     
     for address in &user_addresses {
-        let position_collection = get_user_position_collection(address.as_str());
-        // Fetch positions from database (like PythonVersion does)
-        let mut positions: Vec<UserPositionInterface> = Vec::new();
-        
-        match position_collection.find(doc! {}).await {
-            Ok(mut cursor) => {
-                while let Ok(Some(doc)) = cursor.try_next().await {
-                    // Convert MongoDB document to UserPositionInterface
-                    // Note: MongoDB stores fields in camelCase, but our interface expects snake_case
-                    // The serde rename attributes handle this conversion
-                    match mongodb::bson::from_document::<UserPositionInterface>(doc) {
-                        Ok(position) => positions.push(position),
-                        Err(e) => {
-                            // Silently skip documents that can't be deserialized
-                            // This can happen if the document structure doesn't match
-                            let _ = e; // Suppress unused variable warning
-                        }
-                    }
-                }
-            }
+        // Fetch positions from storage (like PythonVersion does)
+        let positions = match storage().positions(address).await {
+            Ok(positions) => positions,
             Err(e) => {
                 Logger::error(&format!("Error fetching positions from DB for {}...{}: {}", 
                     &address[..6.min(address.len())],
                     &address[address.len().saturating_sub(4)..],
                     e));
+                Vec::new()
             }
-        }
+        };
         
         position_counts.push(positions.len());
         
@@ -136,6 +124,15 @@ pub async fn init() -> anyhow::Result<()> {
     
     Logger::clear_line();
     Logger::traders_positions(&user_addresses, &position_counts, Some(&position_details), Some(&profitabilities));
+
+    // What copying each trader actually earned us, from our own fills
+    match compute_pnl().await {
+        Ok((report, slugs)) => {
+            Logger::clear_line();
+            Logger::pnl_report(&report, &slugs);
+        }
+        Err(e) => Logger::error(&format!("Failed to compute PnL from the fill ledger: {}", e)),
+    }
     
     Ok(())
 }
@@ -151,129 +148,99 @@ async fn get_my_positions(address: &str) -> Result<Vec<UserPositionInterface>, B
     fetch_data::<Vec<UserPositionInterface>>(&url).await
 }
 
-/// Fetch and process trade data from Polymarket API
-async fn fetch_trade_data() -> anyhow::Result<()> {
-    let user_addresses = ENV().user_addresses.clone();
+/// Serializes trader syncs so the polling loop and WebSocket triggers never insert the same trade twice
+static SYNC_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// Executor side of the trade pipeline, set once the monitor starts
+static TRADE_SENDER: OnceLock<TradeSender> = OnceLock::new();
+
+/// Assets held by followed traders, used to route WebSocket trade prints back to traders
+///
+/// A trader's first trade in a market they do not hold yet has no print to route; polling and the
+/// on-chain monitor catch those.
+type WatchedAssets = Arc<RwLock<HashMap<String, HashSet<String>>>>;
+
+/// Journal trade activities whose transaction is not known yet, and hand new ones to the executor
+///
+/// Returns the number of new trades. Callers must hold `SYNC_LOCK`. When an on-chain record
+/// (no market metadata) is seen again through the data API, its metadata is filled in instead.
+async fn store_new_activities(address: &str, activities: &[UserActivityInterface]) -> anyhow::Result<usize> {
+    let stored = storage().insert_trades_if_absent(address, activities).await?;
+    // Positions are synced after activities, so the stored ones still predate these trades
+    let snapshot = if stored.is_empty() {
+        Vec::new()
+    } else {
+        storage().positions(address).await.unwrap_or_default()
+    };
+    for trade in &stored {
+        Logger::info(&format!(
+            "New trade detected for {}...{}",
+            &address[..6.min(address.len())],
+            &address[address.len().saturating_sub(4)..]
+        ));
+        let position_snapshot = snapshot.iter().find(|pos| pos.asset == trade.asset).map(|pos| pos.size);
+        publish_trade(address, trade.clone(), position_snapshot);
+    }
+    Ok(stored.len())
+}
+
+/// Send a journaled trade down the pipeline
+fn publish_trade(address: &str, trade: UserActivityInterface, position_snapshot: Option<f64>) {
+    let Some(sender) = TRADE_SENDER.get() else {
+        return;
+    };
+    if sender.send(DetectedTrade { trade, user_address: address.to_string(), position_snapshot }).is_err() {
+        Logger::warning("Trade executor is not running, trade left in the journal only");
+    }
+}
+
+/// Record a trade detected outside the data API (e.g. on-chain)
+pub(crate) async fn record_activity(address: &str, activity: &UserActivityInterface) -> anyhow::Result<bool> {
+    let _guard = SYNC_LOCK.lock().await;
+    Ok(store_new_activities(address, std::slice::from_ref(activity)).await? > 0)
+}
+
+/// Fetch new trade activities of a trader and store them, returns the number of new trades
+async fn sync_trader_activity(address: &str) -> anyhow::Result<usize> {
+    let _guard = SYNC_LOCK.lock().await;
     let too_old_timestamp = ENV().too_old_timestamp;
-    // PythonVersion uses milliseconds: int((datetime.now() - timedelta(hours=TOO_OLD_TIMESTAMP)).timestamp() * 1000)
-    let current_timestamp_ms = Utc::now().timestamp_millis();
-    let cutoff_timestamp_ms = current_timestamp_ms - (too_old_timestamp * 3600 * 1000); // Convert hours to milliseconds
+    let cutoff_timestamp = Utc::now().timestamp() - too_old_timestamp * 3600; // Convert hours to seconds
+    let mut new_trades = 0;
     
-    for address in &user_addresses {
-        let activity_collection = get_user_activity_collection(address.as_str());
-        let position_collection = get_user_position_collection(address.as_str());
-        
-        // Fetch trade activities from Polymarket API
-        let api_url = format!(
-            "{}{}?user={}&type={}",
-            POLYMARKET_API::DATA_API_BASE,
-            POLYMARKET_API::ACTIVITY_ENDPOINT,
-            address,
-            DB_FIELDS::TYPE_TRADE
-        );
-        
-        match fetch_data::<Vec<UserActivityInterface>>(&api_url).await {
-            Ok(activities) => {
-                if activities.is_empty() {
-                    continue;
-                }
-                
-                // Track seen transaction hashes in this batch to avoid processing duplicates
-                let mut seen_hashes = std::collections::HashSet::new();
-                
-                // Process each activity
-                for activity in activities {
-                    // Skip if too old (activity.timestamp is in milliseconds from API)
-                    if activity.timestamp < cutoff_timestamp_ms {
-                        continue;
-                    }
-                    
-                    // Skip if we've already seen this transaction hash in this batch
-                    if !seen_hashes.insert(activity.transaction_hash.clone()) {
-                        continue; // Duplicate in same API response
-                    }
-                    
-                    // Check if this trade already exists in database
-                    let filter = doc! {
-                        "transactionHash": &activity.transaction_hash
-                    };
-                    
-                    match activity_collection.find_one(filter.clone()).await {
-                        Ok(Some(_)) => {
-                            continue; // Already processed this trade
-                        }
-                        Ok(None) => {
-                            // Trade doesn't exist, proceed to insert
-                        }
-                        Err(e) => {
-                            Logger::error(&format!(
-                                "Error checking for existing trade {}...{}: {}",
-                                &address[..6.min(address.len())],
-                                &address[address.len().saturating_sub(4)..],
-                                e
-                            ));
-                            continue;
-                        }
-                    }
-                    
-                    // Save new trade to database
-                    let new_activity = doc! {
-                        "proxyWallet": &activity.proxy_wallet,
-                        "timestamp": activity.timestamp,
-                        "conditionId": &activity.condition_id,
-                        "type": &activity.r#type,
-                        "size": activity.size,
-                        "usdcSize": activity.usdc_size,
-                        "transactionHash": &activity.transaction_hash,
-                        "price": activity.price,
-                        "asset": &activity.asset,
-                        "side": &activity.side,
-                        "outcomeIndex": activity.outcome_index,
-                        "title": &activity.title,
-                        "slug": &activity.slug,
-                        "icon": &activity.icon,
-                        "eventSlug": &activity.event_slug,
-                        "outcome": &activity.outcome,
-                        "name": &activity.name,
-                        "pseudonym": &activity.pseudonym,
-                        "bio": &activity.bio,
-                        "profileImage": &activity.profile_image,
-                        "profileImageOptimized": &activity.profile_image_optimized,
-                        "bot": false,
-                        "botExcutedTime": 0,
-                    };
-                    
-                    // Insert as document - MongoDB accepts Document directly
-                    match activity_collection.insert_one(new_activity).await {
-                        Ok(_) => {
-                            Logger::info(&format!(
-                                "New trade detected for {}...{}",
-                                &address[..6.min(address.len())],
-                                &address[address.len().saturating_sub(4)..]
-                            ));
-                        }
-                        Err(e) => {
-                            // If it's a duplicate key error, that's okay - trade already exists
-                            let error_str = e.to_string();
-                            if !error_str.contains("duplicate") && !error_str.contains("E11000") {
-                                Logger::error(&format!(
-                                    "Failed to save trade for {}...{}: {}",
-                                    &address[..6.min(address.len())],
-                                    &address[address.len().saturating_sub(4)..],
-                                    e
-                                ));
-                            }
-                            // Silently skip duplicates - they're already in the database
-                        }
-                    }
-                }
+    // Fetch trade activities from Polymarket API
+    let api_url = format!(
+        "{}{}?user={}&type={}",
+        POLYMARKET_API::DATA_API_BASE,
+        POLYMARKET_API::ACTIVITY_ENDPOINT,
+        address,
+        DB_FIELDS::TYPE_TRADE
+    );
+    
+    // Stringify the error right away: the boxed error is not Send
+    match fetch_data::<Vec<UserActivityInterface>>(&api_url).await.map_err(|e| e.to_string()) {
+        Ok(activities) => {
+            if activities.is_empty() {
+                return Ok(0);
             }
-            Err(e) => {
-                // Don't log errors for empty responses - this is normal if trader has no recent trades
-                let error_str = e.to_string();
-                if !error_str.contains("Empty response") && !error_str.contains("Failed to parse") {
+            
+            // Skip trades that are too old (the data API reports unix seconds) and duplicate
+            // trades within the same API response
+            let mut seen_trades = std::collections::HashSet::new();
+            let recent: Vec<UserActivityInterface> = activities
+                .into_iter()
+                .filter(|activity| activity.timestamp >= cutoff_timestamp)
+                .filter(|activity| {
+                    let key = (activity.transaction_hash.clone(), activity.asset.clone(), activity.side.clone());
+                    seen_trades.insert(key)
+                })
+                .collect();
+            
+            // One write per trader and poll; the unique index on the trade key does the dedupe
+            match store_new_activities(address, &recent).await {
+                Ok(count) => new_trades = count,
+                Err(e) => {
                     Logger::error(&format!(
-                        "Error fetching data for {}...{}: {}",
+                        "Failed to save trades for {}...{}: {}",
                         &address[..6.min(address.len())],
                         &address[address.len().saturating_sub(4)..],
                         e
@@ -281,120 +248,178 @@ async fn fetch_trade_data() -> anyhow::Result<()> {
                 }
             }
         }
-        
-        // Also fetch and update positions
-        let positions_url = format!(
-            "{}{}?user={}",
-            POLYMARKET_API::DATA_API_BASE,
-            POLYMARKET_API::POSITIONS_ENDPOINT,
-            address
-        );
-        
-        match fetch_data::<Vec<UserPositionInterface>>(&positions_url).await {
-            Ok(positions) => {
-                if !positions.is_empty() {
-                    for position in positions {
-                        let filter = doc! {
-                            "asset": &position.asset,
-                            "conditionId": &position.condition_id
-                        };
-                        
-                        let update = doc! {
-                            "$set": {
-                                "proxyWallet": &position.proxy_wallet,
-                                "asset": &position.asset,
-                                "conditionId": &position.condition_id,
-                                "size": position.size,
-                                "avgPrice": position.avg_price,
-                                "initialValue": position.initial_value,
-                                "currentValue": position.current_value,
-                                "cashPnl": position.cash_pnl,
-                                "percentPnl": position.percent_pnl,
-                                "totalBought": position.total_bought,
-                                "realizedPnl": position.realized_pnl,
-                                "percentRealizedPnl": position.percent_realized_pnl,
-                                "curPrice": position.cur_price,
-                                "redeemable": position.redeemable,
-                                "mergeable": position.mergeable,
-                                "title": &position.title,
-                                "slug": &position.slug,
-                                "icon": &position.icon,
-                                "eventSlug": &position.event_slug,
-                                "outcome": &position.outcome,
-                                "outcomeIndex": position.outcome_index,
-                                "oppositeOutcome": &position.opposite_outcome,
-                                "oppositeAsset": &position.opposite_asset,
-                                "endDate": &position.end_date,
-                                "negativeRisk": position.negative_risk,
-                            }
-                        };
-                        
-                        // Use upsert=True like PythonVersion to create new positions if they don't exist
-                        // MongoDB Rust driver doesn't support upsert in update_one, so we check first
-                        match position_collection.find_one(filter.clone()).await {
-                            Ok(Some(_)) => {
-                                // Position exists, update it
-                                let _ = position_collection.update_one(filter.clone(), update).await;
-                            }
-                            Ok(None) => {
-                                // Position doesn't exist, insert it
-                                let new_position = doc! {
-                                    "proxyWallet": &position.proxy_wallet,
-                                    "asset": &position.asset,
-                                    "conditionId": &position.condition_id,
-                                    "size": position.size,
-                                    "avgPrice": position.avg_price,
-                                    "initialValue": position.initial_value,
-                                    "currentValue": position.current_value,
-                                    "cashPnl": position.cash_pnl,
-                                    "percentPnl": position.percent_pnl,
-                                    "totalBought": position.total_bought,
-                                    "realizedPnl": position.realized_pnl,
-                                    "percentRealizedPnl": position.percent_realized_pnl,
-                                    "curPrice": position.cur_price,
-                                    "redeemable": position.redeemable,
-                                    "mergeable": position.mergeable,
-                                    "title": &position.title,
-                                    "slug": &position.slug,
-                                    "icon": &position.icon,
-                                    "eventSlug": &position.event_slug,
-                                    "outcome": &position.outcome,
-                                    "outcomeIndex": position.outcome_index,
-                                    "oppositeOutcome": &position.opposite_outcome,
-                                    "oppositeAsset": &position.opposite_asset,
-                                    "endDate": &position.end_date,
-                                    "negativeRisk": position.negative_risk,
-                                };
-                                let _ = position_collection.insert_one(new_position).await;
-                            }
-                            Err(_) => {
-                                // Error checking, skip this position
-                            }
-                        }
-                    }
-                }
+        Err(e) => {
+            // Don't log errors for empty responses - this is normal if trader has no recent trades
+            let error_str = e.to_string();
+            if !error_str.contains("Empty response") && !error_str.contains("Failed to parse") {
+                Logger::error(&format!(
+                    "Error fetching data for {}...{}: {}",
+                    &address[..6.min(address.len())],
+                    &address[address.len().saturating_sub(4)..],
+                    e
+                ));
             }
-            Err(e) => {
-                // Don't log errors for empty responses - this is normal if trader has no positions
-                let error_str = e.to_string();
-                if !error_str.contains("Empty response") && !error_str.contains("Failed to parse") {
-                    Logger::error(&format!(
-                        "Error fetching positions for {}...{}: {}",
-                        &address[..6.min(address.len())],
-                        &address[address.len().saturating_sub(4)..],
-                        e
-                    ));
-                }
+        }
+    }
+    
+    
+    Ok(new_trades)
+}
+
+/// Fetch and store a trader's positions, returns the assets currently held
+async fn sync_trader_positions(address: &str) -> anyhow::Result<Vec<String>> {
+    let mut assets = Vec::new();
+    
+    // Also fetch and update positions
+    let positions_url = format!(
+        "{}{}?user={}",
+        POLYMARKET_API::DATA_API_BASE,
+        POLYMARKET_API::POSITIONS_ENDPOINT,
+        address
+    );
+    
+    match fetch_data::<Vec<UserPositionInterface>>(&positions_url).await.map_err(|e| e.to_string()) {
+        Ok(positions) => {
+            assets.extend(
+                positions
+                    .iter()
+                    .filter(|position| position.size > 0.0)
+                    .map(|position| position.asset.clone()),
+            );
+            
+            // Upsert like PythonVersion so new positions are created, all in one write
+            if let Err(e) = storage().upsert_positions(address, &positions).await {
+                Logger::error(&format!(
+                    "Failed to save positions for {}...{}: {}",
+                    &address[..6.min(address.len())],
+                    &address[address.len().saturating_sub(4)..],
+                    e
+                ));
+            }
+        }
+        Err(e) => {
+            // Don't log errors for empty responses - this is normal if trader has no positions
+            let error_str = e.to_string();
+            if !error_str.contains("Empty response") && !error_str.contains("Failed to parse") {
+                Logger::error(&format!(
+                    "Error fetching positions for {}...{}: {}",
+                    &address[..6.min(address.len())],
+                    &address[address.len().saturating_sub(4)..],
+                    e
+                ));
             }
         }
     }
     
+    Ok(assets)
+}
+
+/// Fetch and process trade data from Polymarket API
+async fn fetch_trade_data(watched: &WatchedAssets, subscription: &MarketSubscription) -> anyhow::Result<()> {
+    let user_addresses = ENV().user_addresses.clone();
+    
+    for address in &user_addresses {
+        sync_trader_activity(address).await?;
+        
+        // Also fetch and update positions, and route trade prints on held assets to this trader
+        let assets = sync_trader_positions(address).await?;
+        let mut watched = watched.write().await;
+        for traders in watched.values_mut() {
+            traders.remove(address);
+        }
+        for asset in assets {
+            watched.entry(asset).or_default().insert(address.clone());
+        }
+        // Assets nobody holds any more are unsubscribed
+        watched.retain(|_, traders| !traders.is_empty());
+        subscription.set(watched.keys().cloned()).await;
+    }
+    
     Ok(())
 }
 
+/// Traders in `pending` whose last triggered sync is at least `debounce` old
+fn due_traders(
+    pending: &HashSet<String>,
+    last_sync: &HashMap<String, Instant>,
+    now: Instant,
+    debounce: Duration,
+) -> Vec<String> {
+    pending
+        .iter()
+        .filter(|address| last_sync.get(*address).is_none_or(|synced| now >= *synced + debounce))
+        .cloned()
+        .collect()
+}
+
+/// WebSocket-triggered polling: re-check followed traders as soon as an asset they hold trades on the CLOB
+///
+/// Prints carry no wallet, so the trade itself still comes from the data API: this only moves the
+/// next `/activity` poll for the traders holding the asset forward. Each trader is synced at most
+/// once per `TRIGGER_DEBOUNCE_MS`; prints within that window are served by one sync at its end.
+async fn handle_trade_prints(mut rx: mpsc::UnboundedReceiver<TradePrint>, watched: WatchedAssets) {
+    let debounce = Duration::from_millis(WS_CONSTANTS::TRIGGER_DEBOUNCE_MS);
+    let mut last_sync: HashMap<String, Instant> = HashMap::new();
+    let mut pending: HashSet<String> = HashSet::new();
+    loop {
+        // Wait for prints, or until a debounced trader may be synced again
+        let next_due = pending
+            .iter()
+            .filter_map(|address| last_sync.get(address))
+            .min()
+            .map(|synced| *synced + debounce);
+        let received = match next_due {
+            Some(due) => timeout_at(due, rx.recv()).await.ok(),
+            None => Some(rx.recv().await),
+        };
+        let mut prints = match received {
+            Some(Some(print)) => vec![print],
+            Some(None) => break,
+            None => Vec::new(),
+        };
+        if !IS_RUNNING.load(Ordering::SeqCst) {
+            break;
+        }
+        
+        // Coalesce bursts of prints into one sync per trader
+        while let Ok(print) = rx.try_recv() {
+            prints.push(print);
+        }
+        {
+            let watched = watched.read().await;
+            pending.extend(prints.iter().filter_map(|print| watched.get(&print.asset_id)).flatten().cloned());
+        }
+        let now = Instant::now();
+        let traders = due_traders(&pending, &last_sync, now, debounce);
+        for address in &traders {
+            pending.remove(address);
+            last_sync.insert(address.clone(), now);
+        }
+        
+        let mut missed = Vec::new();
+        for address in traders {
+            match sync_trader_activity(&address).await {
+                Ok(0) => missed.push(address),
+                Ok(_) => {}
+                Err(e) => Logger::error(&format!("Error in WebSocket-triggered sync: {}", e)),
+            }
+        }
+        if missed.is_empty() {
+            continue;
+        }
+        // The data API may not have indexed the fills yet - look once more, after every trader was checked
+        sleep(Duration::from_millis(WS_CONSTANTS::TRIGGER_RETRY_DELAY_MS)).await;
+        for address in missed {
+            if let Err(e) = sync_trader_activity(&address).await {
+                Logger::error(&format!("Error in WebSocket-triggered sync: {}", e));
+            }
+        }
+    }
+}
+
 /// Main trade monitor function
-/// Monitors traders for new trades and updates database
-pub async fn trade_monitor() -> anyhow::Result<()> {
+/// Monitors traders for new trades, journals them in storage and sends them to the executor
+pub async fn trade_monitor(trades: TradeSender) -> anyhow::Result<()> {
     // Initialize and display status (only if not already done)
     if !INIT_COMPLETED.swap(true, Ordering::SeqCst) {
         init().await?;
@@ -410,25 +435,16 @@ pub async fn trade_monitor() -> anyhow::Result<()> {
     ));
     Logger::separator();
     
-    // On first run, mark all existing historical trades as already processed
+    // On first run, skip all existing historical trades
     if IS_FIRST_RUN.swap(false, Ordering::SeqCst) {
         Logger::info("First run: marking all historical trades as processed...");
         for address in &user_addresses {
-            let collection = get_user_activity_collection(address);
-            let filter = doc! { DB_FIELDS::BOT_EXECUTED: false };
-            let update = doc! {
-                "$set": {
-                    DB_FIELDS::BOT_EXECUTED: true,
-                    DB_FIELDS::BOT_EXECUTED_TIME: 999
-                }
-            };
-            
-            match collection.update_many(filter, update).await {
-                Ok(result) => {
-                    if result.modified_count > 0 {
+            match storage().skip_pending_trades(address, HISTORICAL_TRADE).await {
+                Ok(modified_count) => {
+                    if modified_count > 0 {
                         Logger::info(&format!(
                             "Marked {} historical trades as processed for {}...{}",
-                            result.modified_count,
+                            modified_count,
                             &address[..6.min(address.len())],
                             &address[address.len().saturating_sub(4)..]
                         ));
@@ -444,10 +460,39 @@ pub async fn trade_monitor() -> anyhow::Result<()> {
         Logger::separator();
     }
     
-    let fetch_interval_ms = fetch_interval as u64 * TIME_CONSTANTS::SECOND_MS;
+    // Historical trades are settled above, so only trades detected from here on are sent
+    if TRADE_SENDER.set(trades).is_err() {
+        Logger::warning("Trade monitor already started");
+    }
+    
+    let watched: WatchedAssets = Arc::new(RwLock::new(HashMap::new()));
+    let subscription = MarketSubscription::new();
+    
+    // Trade prints over the CLOB WebSocket trigger early syncs; polling below fills any gaps
+    if ENV().clob_ws_enabled {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let ws_subscription = subscription.clone();
+        tokio::spawn(async move {
+            run_market_subscriber(&ENV().clob_ws_url, ws_subscription, tx, WsSettings::default(), &IS_RUNNING).await;
+        });
+        tokio::spawn(handle_trade_prints(rx, watched.clone()));
+        Logger::info("WebSocket-triggered polling enabled (CLOB trade prints)");
+    }
+    
+    // Exchange OrderFilled logs give tx-level truth without the data API's indexing lag
+    if ENV().onchain_monitor_enabled {
+        tokio::spawn(async move {
+            if let Err(e) = run_onchain_monitor(&ENV().rpc_url, &ENV().user_addresses, ENV().onchain_confirmations, &IS_RUNNING)
+                .await {
+                Logger::error(&format!("On-chain monitor stopped: {}", e));
+            }
+        });
+    }
+    
+    let fetch_interval_ms = fetch_interval * TIME_CONSTANTS::SECOND_MS;
     
     while IS_RUNNING.load(Ordering::SeqCst) {
-        if let Err(e) = fetch_trade_data().await {
+        if let Err(e) = fetch_trade_data(&watched, &subscription).await {
             Logger::error(&format!("Error in fetch_trade_data: {}", e));
         }
         
@@ -461,3 +506,23 @@ pub async fn trade_monitor() -> anyhow::Result<()> {
     Logger::info("Trade monitor stopped");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn triggered_syncs_are_debounced_per_trader() {
+        let debounce = Duration::from_millis(WS_CONSTANTS::TRIGGER_DEBOUNCE_MS);
+        let now = Instant::now();
+        let pending: HashSet<String> = ["0xa", "0xb", "0xc"].iter().map(|s| s.to_string()).collect();
+        let last_sync = HashMap::from([
+            ("0xa".to_string(), now - debounce / 2), // Synced just now: waits
+            ("0xb".to_string(), now - debounce),     // Window over
+        ]);
+        let mut due = due_traders(&pending, &last_sync, now, debounce);
+        due.sort();
+        assert_eq!(due, vec!["0xb", "0xc"]);
+        assert_eq!(due_traders(&pending, &last_sync, now + debounce, debounce).len(), 3);
+    }
+}
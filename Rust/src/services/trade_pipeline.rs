@@ -0,0 +1,359 @@
+// In-process trade pipeline
+// detect → filter → size → execute → persist, with storage as the durable journal rather than the transport
+
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use crate::config::env::ENV;
+use crate::interfaces::execution::ExecutionStatus;
+use crate::interfaces::user::{UserActivityInterface, UserPositionInterface};
+use crate::services::paper_wallet::{get_paper_balance, get_paper_positions};
+use crate::storage::{storage, TradeKey, TradeUpdate};
+use crate::utils::constants::{DB_FIELDS, POLYMARKET_API, TRADING_CONSTANTS};
+use crate::utils::errors::{AppError, AppResult};
+use crate::utils::fetch_data::fetch_data;
+use crate::utils::get_my_balance::get_my_balance;
+use crate::utils::logger::Logger;
+
+/// A newly journaled trade of a followed trader
+///
+/// Only sent after the activity was inserted, so each trade reaches the executor exactly once.
+#[derive(Debug, Clone)]
+pub struct DetectedTrade {
+    pub trade: UserActivityInterface,
+    pub user_address: String,
+    /// Trader's stored position in the asset when the trade was detected, before it was synced past it
+    pub position_snapshot: Option<f64>,
+}
+
+pub type TradeSender = mpsc::UnboundedSender<DetectedTrade>;
+pub type TradeReceiver = mpsc::UnboundedReceiver<DetectedTrade>;
+
+/// Channel between the trade monitor (detect) and the trade executor (everything after)
+pub fn channel() -> (TradeSender, TradeReceiver) {
+    mpsc::unbounded_channel()
+}
+
+/// Filter stage outcome
+#[derive(Debug, Clone, PartialEq)]
+pub enum Route {
+    /// Copy right away
+    Execute,
+    /// Small BUY held back to be combined with follow-ups
+    Aggregate,
+    /// Not copied; journaled as processed with the reason logged
+    Skip(&'static str),
+}
+
+/// Filter stage: decide what to do with a detected trade
+pub fn route_trade(trade: &UserActivityInterface, aggregation_enabled: bool) -> Route {
+    if trade.r#type != DB_FIELDS::TYPE_TRADE {
+        return Route::Skip("not a trade");
+    }
+    if trade.side != DB_FIELDS::SIDE_BUY && trade.side != DB_FIELDS::SIDE_SELL {
+        return Route::Skip("unknown side");
+    }
+    if trade.asset.is_empty() {
+        return Route::Skip("missing asset");
+    }
+    if aggregation_enabled
+        && trade.side == DB_FIELDS::SIDE_BUY
+        && trade.usdc_size < TRADING_CONSTANTS::TRADE_AGGREGATION_MIN_TOTAL_USD
+    {
+        return Route::Aggregate;
+    }
+    Route::Execute
+}
+
+/// Inputs of the size stage: both sides' positions in the traded asset and portfolio values
+#[derive(Debug, Clone, Default)]
+pub struct TradeContext {
+    pub my_position: Option<UserPositionInterface>,
+    pub user_position: Option<UserPositionInterface>,
+    /// Trader's position before the trade, for sizing SELLs; see `position_before_sell`
+    pub user_position_before: Option<f64>,
+    pub my_balance: f64,
+    pub user_balance: f64,
+}
+
+/// Trader's position before selling `trade_size` tokens, None once the position is gone
+///
+/// The positions API may not have indexed the sell yet, as with trades detected over WebSocket or
+/// on-chain. When the position has not dropped below `snapshot`, the size stored before the trade
+/// was detected, the API still reports the pre-trade size; otherwise the sell is added back.
+pub fn position_before_sell(trade_size: f64, position_after: Option<f64>, snapshot: Option<f64>) -> Option<f64> {
+    let position_after = position_after?;
+    match snapshot {
+        Some(snapshot) if position_after + 1e-6 >= snapshot => Some(position_after),
+        _ => Some(position_after + trade_size),
+    }
+}
+
+fn positions_url(user: &str) -> String {
+    format!(
+        "{}{}?user={}",
+        POLYMARKET_API::DATA_API_BASE,
+        POLYMARKET_API::POSITIONS_ENDPOINT,
+        user
+    )
+}
+
+/// Our open positions and USDC balance, from the paper wallet in dry-run mode
+///
+/// Unlike `load_context`, a failed lookup is an error rather than an empty portfolio.
+pub async fn fetch_my_portfolio() -> AppResult<(Vec<UserPositionInterface>, f64)> {
+    if ENV().dry_run {
+        return Ok((get_paper_positions().await?, get_paper_balance().await?));
+    }
+    let proxy_wallet = &ENV().proxy_wallet;
+    let positions = fetch_data(&positions_url(proxy_wallet))
+        .await
+        .map_err(|e| AppError::NetworkError(e.to_string()))?;
+    let balance = get_my_balance(proxy_wallet)
+        .await
+        .map_err(|e| AppError::NetworkError(e.to_string()))?;
+    Ok((positions, balance))
+}
+
+/// Size stage: fetch what `post_order` needs to size a copy of `trade`
+///
+/// `position_snapshot` is the trader's stored position from before the trade, if known.
+pub async fn load_context(
+    user_address: &str,
+    trade: &UserActivityInterface,
+    position_snapshot: Option<f64>,
+) -> TradeContext {
+    let proxy_wallet = ENV().proxy_wallet.clone();
+
+    // In dry-run mode our side is the paper wallet
+    let (my_positions, my_balance) = if ENV().dry_run {
+        (
+            get_paper_positions().await.unwrap_or_default(),
+            get_paper_balance().await.unwrap_or(0.0),
+        )
+    } else {
+        (
+            fetch_data::<Vec<UserPositionInterface>>(&positions_url(&proxy_wallet)).await.unwrap_or_default(),
+            get_my_balance(&proxy_wallet).await.unwrap_or(0.0),
+        )
+    };
+    let user_positions: Vec<UserPositionInterface> = fetch_data(&positions_url(user_address)).await.unwrap_or_default();
+
+    // Match on the outcome token: both outcomes of a market share the same conditionId
+    let my_position = my_positions.iter().find(|pos| pos.asset == trade.asset).cloned();
+    let user_position = user_positions.iter().find(|pos| pos.asset == trade.asset).cloned();
+
+    let position_after = user_position.as_ref().map(|pos| pos.size);
+    let user_position_before = position_before_sell(trade.size, position_after, position_snapshot);
+    TradeContext {
+        my_position,
+        user_position,
+        user_position_before,
+        my_balance,
+        // Trader's total portfolio value from positions
+        user_balance: user_positions.iter().map(|pos| pos.current_value).sum(),
+    }
+}
+
+/// Fill in market metadata missing from trades detected on-chain
+///
+/// Sells are matched to earlier buys by conditionId, so it must be known before executing.
+pub fn enrich_from_positions(trade: &mut UserActivityInterface, context: &TradeContext) {
+    if !trade.condition_id.is_empty() {
+        return;
+    }
+    if let Some(position) = context.user_position.as_ref().or(context.my_position.as_ref()) {
+        trade.condition_id = position.condition_id.clone();
+        trade.outcome_index = position.outcome_index;
+        trade.title = position.title.clone();
+        trade.slug = position.slug.clone();
+        trade.icon = position.icon.clone();
+        trade.event_slug = position.event_slug.clone();
+        trade.outcome = position.outcome.clone();
+    }
+}
+
+/// Event a Gamma market belongs to
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct GammaEvent {
+    #[serde(default)]
+    pub slug: Option<String>,
+}
+
+/// Market of an outcome token as returned by the Gamma API (list fields are JSON-encoded strings)
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TokenMarket {
+    #[serde(rename = "conditionId", default)]
+    pub condition_id: Option<String>,
+    #[serde(default)]
+    pub question: Option<String>,
+    #[serde(default)]
+    pub slug: Option<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub outcomes: Option<String>,
+    #[serde(rename = "clobTokenIds", default)]
+    pub clob_token_ids: Option<String>,
+    #[serde(default)]
+    pub events: Vec<GammaEvent>,
+}
+
+/// Gamma API market of an outcome token, if it could be looked up
+pub async fn fetch_token_market(token_id: &str) -> Option<TokenMarket> {
+    let url = format!(
+        "{}{}?clob_token_ids={}",
+        POLYMARKET_API::GAMMA_API_BASE,
+        POLYMARKET_API::MARKETS_ENDPOINT,
+        token_id
+    );
+    match fetch_data::<Vec<TokenMarket>>(&url).await {
+        Ok(markets) => markets
+            .into_iter()
+            .find(|market| market.condition_id.as_deref().is_some_and(|id| !id.is_empty())),
+        Err(e) => {
+            Logger::warning(&format!("Could not look up the market of token {}: {}", token_id, e));
+            None
+        }
+    }
+}
+
+/// Fill in market metadata `trade` is missing from its Gamma API market
+pub fn apply_market(trade: &mut UserActivityInterface, market: &TokenMarket) {
+    let fill = |field: &mut String, value: Option<&String>| {
+        if field.is_empty() {
+            *field = value.cloned().unwrap_or_default();
+        }
+    };
+    fill(&mut trade.condition_id, market.condition_id.as_ref());
+    fill(&mut trade.title, market.question.as_ref());
+    fill(&mut trade.slug, market.slug.as_ref());
+    fill(&mut trade.icon, market.icon.as_ref());
+    fill(&mut trade.event_slug, market.events.iter().find_map(|event| event.slug.as_ref()));
+
+    let list = |field: &Option<String>| -> Vec<String> {
+        field.as_deref().and_then(|json| serde_json::from_str(json).ok()).unwrap_or_default()
+    };
+    if trade.outcome.is_empty() {
+        if let Some(index) = list(&market.clob_token_ids).iter().position(|token| *token == trade.asset) {
+            trade.outcome_index = index as i32;
+            trade.outcome = list(&market.outcomes).get(index).cloned().unwrap_or_default();
+        }
+    }
+}
+
+/// Look up market metadata still missing after `enrich_from_positions` by token ID
+///
+/// Exposure caps are keyed by conditionId and event slug, so a BUY is not sized without them.
+pub async fn enrich_from_market(trade: &mut UserActivityInterface) {
+    if trade.asset.is_empty() || (!trade.condition_id.is_empty() && !trade.event_slug.is_empty()) {
+        return;
+    }
+    if let Some(market) = fetch_token_market(&trade.asset).await {
+        apply_market(trade, &market);
+    }
+}
+
+/// Persist stage: claim a journaled trade before placing any order for it
+/// A trade that cannot be claimed (already claimed, settled, or storage unreachable) is not
+/// executed, so a trade is never copied twice.
+pub async fn claim(detected: &DetectedTrade) -> bool {
+    match storage().claim_trade(&detected.user_address, TradeKey::of(&detected.trade)).await {
+        Ok(true) => true,
+        Ok(false) => {
+            Logger::warning(&format!("Trade {} is no longer pending - not copying it", detected.trade.transaction_hash));
+            false
+        }
+        Err(e) => {
+            Logger::error(&format!(
+                "Failed to claim trade {}: {} - not copying it",
+                detected.trade.transaction_hash, e
+            ));
+            false
+        }
+    }
+}
+
+/// Persist stage: journal a transition of a claimed trade
+pub async fn journal(user_address: &str, key: TradeKey<'_>, update: TradeUpdate) {
+    if let Err(e) = storage().update_trade(user_address, key, update).await {
+        Logger::error(&format!("Failed to journal trade {}: {}", key.transaction_hash, e));
+    }
+}
+
+/// Persist stage: journal that a trade will not be copied
+pub async fn mark_skipped(detected: &DetectedTrade, reason: &str) {
+    let update = TradeUpdate::new(ExecutionStatus::Skipped { reason: reason.to_string() });
+    journal(&detected.user_address, TradeKey::of(&detected.trade), update).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(r#type: &str, side: &str, usdc_size: f64) -> UserActivityInterface {
+        serde_json::from_value(serde_json::json!({
+            "timestamp": 1_750_428_146,
+            "type": r#type,
+            "side": side,
+            "asset": "71321045679252212594626385532706912750332728571942532289631379312455583992563",
+            "usdcSize": usdc_size,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn routes_detected_trades() {
+        let min = TRADING_CONSTANTS::TRADE_AGGREGATION_MIN_TOTAL_USD;
+        let cases = [
+            (trade("TRADE", "BUY", min + 1.0), true, Route::Execute),
+            (trade("TRADE", "BUY", min / 2.0), true, Route::Aggregate),
+            (trade("TRADE", "BUY", min / 2.0), false, Route::Execute),
+            (trade("TRADE", "SELL", min / 2.0), true, Route::Execute),
+            (trade("REDEEM", "", 5.0), true, Route::Skip("not a trade")),
+            (trade("TRADE", "", 5.0), true, Route::Skip("unknown side")),
+        ];
+        for (activity, aggregation_enabled, expected) in cases {
+            assert_eq!(route_trade(&activity, aggregation_enabled), expected, "{:?}", activity);
+        }
+
+        let mut no_asset = trade("TRADE", "BUY", 5.0);
+        no_asset.asset.clear();
+        assert_eq!(route_trade(&no_asset, false), Route::Skip("missing asset"));
+    }
+
+    #[test]
+    fn enriches_onchain_trades_from_trader_position() {
+        let position: UserPositionInterface = serde_json::from_value(serde_json::json!({
+            "conditionId": "0xabc",
+            "outcomeIndex": 1,
+            "title": "Will it rain?",
+            "slug": "will-it-rain",
+            "outcome": "No",
+        }))
+        .unwrap();
+        let context = TradeContext { user_position: Some(position), ..Default::default() };
+
+        let mut onchain = trade("TRADE", "SELL", 5.0);
+        enrich_from_positions(&mut onchain, &context);
+        assert_eq!(onchain.condition_id, "0xabc");
+        assert_eq!((onchain.outcome_index, onchain.slug.as_str()), (1, "will-it-rain"));
+
+        // Data API trades already carry their metadata
+        let mut from_api = trade("TRADE", "SELL", 5.0);
+        from_api.condition_id = "0xdef".to_string();
+        enrich_from_positions(&mut from_api, &context);
+        assert_eq!(from_api.condition_id, "0xdef");
+        assert!(from_api.slug.is_empty());
+    }
+
+    #[test]
+    fn pre_sell_position_accounts_for_an_unindexed_sell() {
+        // Trader held 100 and sold 25; the API already reports 75
+        assert_eq!(position_before_sell(25.0, Some(75.0), Some(100.0)), Some(100.0));
+        assert_eq!(position_before_sell(25.0, Some(75.0), None), Some(100.0));
+        // Detected before the API indexed the sell: it still reports the 100 stored before it
+        assert_eq!(position_before_sell(25.0, Some(100.0), Some(100.0)), Some(100.0));
+        // Position gone
+        assert_eq!(position_before_sell(25.0, None, Some(100.0)), None);
+    }
+}
@@ -0,0 +1,410 @@
+// In-memory backend: nothing survives a restart; for tests and throwaway runs
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use async_trait::async_trait;
+use mongodb::bson::oid::ObjectId;
+use crate::interfaces::user::{UserActivityInterface, UserPositionInterface};
+use crate::interfaces::execution::ExecutionStatus;
+use crate::interfaces::ledger::{FillRecord, OrderRecord};
+use crate::storage::{
+    apply_update, fill_metadata, new_trade, now_millis, LedgerStore, PositionStore, Storage, StorageBackend, TradeKey,
+    TradeStore, TradeUpdate,
+};
+use crate::utils::constants::DB_FIELDS;
+use crate::utils::errors::AppResult;
+
+/// A trader's positions keyed by (asset, conditionId)
+type PositionMap = HashMap<(String, String), UserPositionInterface>;
+
+#[derive(Default)]
+pub struct MemoryStore {
+    /// Journaled trades per trader, in insertion order
+    trades: Mutex<HashMap<String, Vec<UserActivityInterface>>>,
+    /// Positions per trader
+    positions: Mutex<HashMap<String, PositionMap>>,
+    /// Our orders and fills, in recording order
+    orders: Mutex<Vec<OrderRecord>>,
+    fills: Mutex<Vec<FillRecord>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn with_trades<T>(&self, address: &str, f: impl FnOnce(&mut Vec<UserActivityInterface>) -> T) -> T {
+        let mut trades = self.trades.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        f(trades.entry(address.to_string()).or_default())
+    }
+
+    /// Fills passing `filter`, oldest first
+    fn fills_matching(&self, filter: impl Fn(&FillRecord) -> bool) -> Vec<FillRecord> {
+        let fills = self.fills.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut fills: Vec<_> = fills.iter().filter(|fill| filter(fill)).cloned().collect();
+        fills.sort_by_key(|fill| fill.filled_at);
+        fills
+    }
+
+    /// Trades whose status passes `filter`, oldest first
+    fn trades_matching(&self, address: &str, filter: impl Fn(&ExecutionStatus) -> bool) -> Vec<UserActivityInterface> {
+        let mut trades: Vec<_> = self.with_trades(address, |trades| {
+            trades.iter().filter(|trade| filter(&trade.execution.status)).cloned().collect()
+        });
+        trades.sort_by_key(|trade| trade.timestamp);
+        trades
+    }
+}
+
+/// Filled BUY of the outcome token whose bought size is still tracked
+fn is_tracked_purchase(trade: &UserActivityInterface, asset: &str, condition_id: &str) -> bool {
+    trade.asset == asset
+        && trade.condition_id == condition_id
+        && trade.side == DB_FIELDS::SIDE_BUY
+        && matches!(trade.execution.status, ExecutionStatus::Filled | ExecutionStatus::PartiallyFilled)
+        && trade.my_bought_size.is_some_and(|size| size > 0.0)
+}
+
+#[async_trait]
+impl TradeStore for MemoryStore {
+    async fn insert_trades_if_absent(
+        &self,
+        address: &str,
+        activities: &[UserActivityInterface],
+    ) -> AppResult<Vec<UserActivityInterface>> {
+        Ok(self.with_trades(address, |trades| {
+            let mut inserted = Vec::new();
+            for activity in activities {
+                match trades.iter_mut().find(|trade| TradeKey::of(activity).matches(trade)) {
+                    Some(known) => {
+                        if known.condition_id.is_empty() && !activity.condition_id.is_empty() {
+                            fill_metadata(known, activity);
+                        }
+                    }
+                    None => {
+                        let stored = new_trade(activity, Some(ObjectId::new()));
+                        trades.push(stored.clone());
+                        inserted.push(stored);
+                    }
+                }
+            }
+            inserted
+        }))
+    }
+
+    async fn count_trades(&self, address: &str) -> AppResult<u64> {
+        Ok(self.with_trades(address, |trades| trades.len() as u64))
+    }
+
+    async fn trades_since(&self, address: &str, since: Option<i64>) -> AppResult<Vec<UserActivityInterface>> {
+        let mut trades: Vec<_> = self.with_trades(address, |trades| {
+            trades
+                .iter()
+                .filter(|trade| trade.r#type == DB_FIELDS::TYPE_TRADE)
+                .filter(|trade| since.is_none_or(|since| trade.timestamp >= since))
+                .cloned()
+                .collect()
+        });
+        trades.sort_by_key(|trade| trade.timestamp);
+        Ok(trades)
+    }
+
+    async fn pending_trades(&self, address: &str) -> AppResult<Vec<UserActivityInterface>> {
+        Ok(self.trades_matching(address, |status| *status == ExecutionStatus::Pending))
+    }
+
+    async fn in_flight_trades(&self, address: &str) -> AppResult<Vec<UserActivityInterface>> {
+        Ok(self.trades_matching(address, ExecutionStatus::is_in_flight))
+    }
+
+    async fn skip_pending_trades(&self, address: &str, reason: &str) -> AppResult<u64> {
+        let update = TradeUpdate::new(ExecutionStatus::Skipped { reason: reason.to_string() });
+        let now = now_millis();
+        Ok(self.with_trades(address, |trades| {
+            let mut updated = 0;
+            for trade in trades.iter_mut().filter(|trade| trade.execution.status == ExecutionStatus::Pending) {
+                apply_update(&mut trade.execution, &update, now);
+                updated += 1;
+            }
+            updated
+        }))
+    }
+
+    async fn claim_trade(&self, address: &str, key: TradeKey<'_>) -> AppResult<bool> {
+        let update = TradeUpdate::new(ExecutionStatus::Claimed);
+        Ok(self.with_trades(address, |trades| {
+            match trades
+                .iter_mut()
+                .find(|trade| key.matches(trade) && trade.execution.status == ExecutionStatus::Pending)
+            {
+                Some(trade) => {
+                    apply_update(&mut trade.execution, &update, now_millis());
+                    true
+                }
+                None => false,
+            }
+        }))
+    }
+
+    async fn update_trade(&self, address: &str, key: TradeKey<'_>, update: TradeUpdate) -> AppResult<()> {
+        self.with_trades(address, |trades| {
+            if let Some(trade) = trades.iter_mut().find(|trade| key.matches(trade)) {
+                apply_update(&mut trade.execution, &update, now_millis());
+                if let Some(bought_tokens) = update.bought_tokens {
+                    trade.my_bought_size = Some(bought_tokens);
+                }
+            }
+        });
+        Ok(())
+    }
+
+    async fn tracked_purchases(&self, address: &str, asset: &str, condition_id: &str) -> AppResult<Vec<f64>> {
+        Ok(self.with_trades(address, |trades| {
+            trades
+                .iter()
+                .filter(|trade| is_tracked_purchase(trade, asset, condition_id))
+                .filter_map(|trade| trade.my_bought_size)
+                .collect()
+        }))
+    }
+
+    async fn scale_tracked_purchases(&self, address: &str, asset: &str, condition_id: &str, factor: f64) -> AppResult<()> {
+        self.with_trades(address, |trades| {
+            for trade in trades.iter_mut().filter(|trade| is_tracked_purchase(trade, asset, condition_id)) {
+                trade.my_bought_size = trade.my_bought_size.map(|size| size * factor.max(0.0));
+            }
+        });
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl PositionStore for MemoryStore {
+    async fn upsert_positions(&self, address: &str, positions: &[UserPositionInterface]) -> AppResult<()> {
+        let mut stored = self.positions.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let stored = stored.entry(address.to_string()).or_default();
+        for position in positions {
+            stored.insert((position.asset.clone(), position.condition_id.clone()), position.clone());
+        }
+        Ok(())
+    }
+
+    async fn positions(&self, address: &str) -> AppResult<Vec<UserPositionInterface>> {
+        let positions = self.positions.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        Ok(positions
+            .get(address)
+            .map(|positions| positions.values().cloned().collect())
+            .unwrap_or_default())
+    }
+}
+
+#[async_trait]
+impl LedgerStore for MemoryStore {
+    async fn record_order(&self, order: &OrderRecord, fill: Option<&FillRecord>) -> AppResult<()> {
+        self.orders.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push(order.clone());
+        if let Some(fill) = fill {
+            self.fills.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push(fill.clone());
+        }
+        Ok(())
+    }
+
+    async fn record_fill(&self, fill: &FillRecord) -> AppResult<()> {
+        self.fills.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push(fill.clone());
+        Ok(())
+    }
+
+    async fn orders_for_trade(&self, trader_address: &str, transaction_hash: &str) -> AppResult<Vec<OrderRecord>> {
+        let orders = self.orders.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut orders: Vec<_> = orders
+            .iter()
+            .filter(|order| order.trader_address == trader_address && order.source_transaction_hash == transaction_hash)
+            .cloned()
+            .collect();
+        orders.sort_by_key(|order| order.submitted_at);
+        Ok(orders)
+    }
+
+    async fn fills_for_trade(&self, trader_address: &str, transaction_hash: &str) -> AppResult<Vec<FillRecord>> {
+        Ok(self.fills_matching(|fill| {
+            fill.trader_address == trader_address && fill.source_transaction_hash == transaction_hash
+        }))
+    }
+
+    async fn fills_since(&self, since: Option<i64>) -> AppResult<Vec<FillRecord>> {
+        Ok(self.fills_matching(|fill| since.is_none_or(|since| fill.filled_at >= since)))
+    }
+}
+
+#[async_trait]
+impl Storage for MemoryStore {
+    fn backend(&self) -> StorageBackend {
+        StorageBackend::Memory
+    }
+
+    async fn ping(&self) -> AppResult<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) fn trade(hash: &str, side: &str, timestamp: i64) -> UserActivityInterface {
+        serde_json::from_value(serde_json::json!({
+            "timestamp": timestamp,
+            "type": "TRADE",
+            "side": side,
+            "asset": "111",
+            "conditionId": "0xabc",
+            "transactionHash": hash,
+            "usdcSize": 10.0,
+        }))
+        .unwrap()
+    }
+
+    /// Journal semantics every backend must share
+    pub(crate) async fn exercise_trade_store(store: &dyn Storage) {
+        let address = "0xtrader";
+        let buy = trade("0x01", "BUY", 200);
+        let stored = store.insert_trades_if_absent(address, &[buy.clone(), buy.clone()]).await.unwrap();
+        assert_eq!(stored.len(), 1, "duplicates within a batch are journaled once");
+        assert!(stored[0]._id.is_some());
+        let stored = store
+            .insert_trades_if_absent(address, &[buy, trade("0x02", "SELL", 100)])
+            .await
+            .unwrap();
+        assert_eq!(stored.iter().map(|t| t.transaction_hash.as_str()).collect::<Vec<_>>(), vec!["0x02"]);
+        assert_eq!(store.count_trades(address).await.unwrap(), 2);
+
+        let since: Vec<_> = store.trades_since(address, Some(150)).await.unwrap();
+        assert_eq!(since.len(), 1);
+        let all = store.trades_since(address, None).await.unwrap();
+        assert_eq!(all[0].transaction_hash, "0x02", "oldest first");
+
+        let buy_key = TradeKey { transaction_hash: "0x01", asset: "111", side: "BUY" };
+        assert!(store.claim_trade(address, buy_key).await.unwrap());
+        assert!(!store.claim_trade(address, buy_key).await.unwrap(), "a trade is claimed once");
+        let submitted = TradeUpdate {
+            attempts: Some(1),
+            order_id: Some("order-1".to_string()),
+            ..TradeUpdate::new(ExecutionStatus::Submitted)
+        };
+        store.update_trade(address, buy_key, submitted).await.unwrap();
+        let in_flight = store.in_flight_trades(address).await.unwrap();
+        assert_eq!(in_flight.len(), 1);
+        assert_eq!(in_flight[0].execution.order_ids, vec!["order-1"]);
+        assert!(in_flight[0].execution.claimed_at.is_some());
+        assert!(store.tracked_purchases(address, "111", "0xabc").await.unwrap().is_empty(), "not filled yet");
+
+        let filled = TradeUpdate { bought_tokens: Some(20.0), ..TradeUpdate::new(ExecutionStatus::Filled) };
+        store.update_trade(address, buy_key, filled).await.unwrap();
+        assert!(store.in_flight_trades(address).await.unwrap().is_empty());
+        let pending = store.pending_trades(address).await.unwrap();
+        assert_eq!(pending.iter().map(|t| t.transaction_hash.as_str()).collect::<Vec<_>>(), vec!["0x02"]);
+
+        assert_eq!(store.tracked_purchases(address, "111", "0xabc").await.unwrap(), vec![20.0]);
+        store.scale_tracked_purchases(address, "111", "0xabc", 0.25).await.unwrap();
+        assert_eq!(store.tracked_purchases(address, "111", "0xabc").await.unwrap(), vec![5.0]);
+        store.scale_tracked_purchases(address, "111", "0xabc", 0.0).await.unwrap();
+        assert!(store.tracked_purchases(address, "111", "0xabc").await.unwrap().is_empty());
+
+        assert_eq!(store.skip_pending_trades(address, "historical").await.unwrap(), 1);
+        assert!(store.pending_trades(address).await.unwrap().is_empty());
+        let skipped = store.trades_since(address, None).await.unwrap();
+        assert_eq!(skipped[0].execution.status, ExecutionStatus::Skipped { reason: "historical".to_string() });
+        let sell_key = TradeKey { transaction_hash: "0x02", asset: "111", side: "SELL" };
+        assert!(!store.claim_trade(address, sell_key).await.unwrap());
+        let filled = store.trades_since(address, Some(150)).await.unwrap();
+        assert_eq!((filled[0].execution.status.clone(), filled[0].execution.attempts), (ExecutionStatus::Filled, 1));
+        assert!(filled[0].execution.completed_at.is_some());
+
+        // On-chain record without metadata gets it from the data API copy
+        let mut onchain = trade("0x03", "BUY", 300);
+        onchain.condition_id.clear();
+        store.insert_trades_if_absent(address, &[onchain]).await.unwrap();
+        let mut api = trade("0x03", "BUY", 300);
+        api.slug = "will-it-rain".to_string();
+        assert!(store.insert_trades_if_absent(address, &[api]).await.unwrap().is_empty());
+        let backfilled = store.trades_since(address, Some(300)).await.unwrap();
+        assert_eq!((backfilled[0].condition_id.as_str(), backfilled[0].slug.as_str()), ("0xabc", "will-it-rain"));
+
+        // A transaction that traded several assets journals one trade per asset
+        let mut other_asset = trade("0x04", "BUY", 400);
+        other_asset.asset = "222".to_string();
+        let stored = store
+            .insert_trades_if_absent(address, &[trade("0x04", "BUY", 400), other_asset])
+            .await
+            .unwrap();
+        assert_eq!(stored.len(), 2);
+        let other_key = TradeKey { transaction_hash: "0x04", asset: "222", side: "BUY" };
+        assert!(store.claim_trade(address, other_key).await.unwrap());
+        let pending = store.pending_trades(address).await.unwrap();
+        let assets: Vec<_> = pending
+            .iter()
+            .filter(|t| t.transaction_hash == "0x04")
+            .map(|t| t.asset.as_str())
+            .collect();
+        assert_eq!(assets, vec!["111"], "claiming one asset leaves the other pending");
+    }
+
+    pub(crate) async fn exercise_position_store(store: &dyn Storage) {
+        let address = "0xtrader";
+        let mut position: UserPositionInterface =
+            serde_json::from_value(serde_json::json!({ "asset": "111", "conditionId": "0xabc", "size": 10.0 })).unwrap();
+        store.upsert_positions(address, &[position.clone()]).await.unwrap();
+        position.size = 4.0;
+        store.upsert_positions(address, &[position]).await.unwrap();
+        let positions = store.positions(address).await.unwrap();
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].size, 4.0);
+        assert!(store.positions("0xother").await.unwrap().is_empty());
+    }
+
+    pub(crate) async fn exercise_ledger_store(store: &dyn Storage) {
+        let order = |id: &str, source: &str, submitted_at: i64| OrderRecord {
+            order_id: id.to_string(),
+            trader_address: "0xtrader".to_string(),
+            source_transaction_hash: source.to_string(),
+            asset: "111".to_string(),
+            side: "BUY".to_string(),
+            order_type: "FOK".to_string(),
+            accepted: !id.is_empty(),
+            submitted_at,
+            ..Default::default()
+        };
+        let fill = |order: &OrderRecord, filled_at: i64| FillRecord {
+            order_id: order.order_id.clone(),
+            trader_address: order.trader_address.clone(),
+            source_transaction_hash: order.source_transaction_hash.clone(),
+            size: 10.0,
+            filled_at,
+            ..Default::default()
+        };
+
+        let second = order("0xo2", "0x01", 20);
+        store.record_order(&second, Some(&fill(&second, 21))).await.unwrap();
+        let first = order("0xo1", "0x01", 10);
+        store.record_order(&first, Some(&fill(&first, 11))).await.unwrap();
+        store.record_order(&order("", "0x01", 30), None).await.unwrap();
+        let other = order("0xo3", "0x02", 40);
+        store.record_order(&other, Some(&fill(&other, 41))).await.unwrap();
+
+        let orders = store.orders_for_trade("0xtrader", "0x01").await.unwrap();
+        assert_eq!(orders.iter().map(|o| o.order_id.as_str()).collect::<Vec<_>>(), vec!["0xo1", "0xo2", ""]);
+        assert_eq!(orders[0], first);
+        let fills = store.fills_for_trade("0xtrader", "0x01").await.unwrap();
+        assert_eq!(fills.iter().map(|f| f.filled_at).collect::<Vec<_>>(), vec![11, 21]);
+        assert_eq!(store.fills_since(Some(21)).await.unwrap().len(), 2);
+        assert_eq!(store.fills_since(None).await.unwrap().len(), 3);
+        assert!(store.orders_for_trade("0xother", "0x01").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn memory_store_behaves_like_the_journal() {
+        let store = MemoryStore::new();
+        exercise_trade_store(&store).await;
+        exercise_position_store(&store).await;
+        exercise_ledger_store(&store).await;
+    }
+}
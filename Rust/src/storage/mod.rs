@@ -0,0 +1,310 @@
+// Trade journal, position and order ledger storage
+// The monitor and executor go through these traits; STORAGE_BACKEND picks MongoDB, SQLite or memory
+
+pub mod memory;
+pub mod mongo;
+pub mod sqlite;
+
+use std::sync::OnceLock;
+use async_trait::async_trait;
+use mongodb::bson::oid::ObjectId;
+use crate::config::env::ENV;
+use crate::interfaces::execution::{ExecutionState, ExecutionStatus};
+use crate::interfaces::ledger::{FillRecord, OrderRecord};
+use crate::interfaces::user::{UserActivityInterface, UserPositionInterface};
+use crate::utils::errors::{AppError, AppResult};
+
+pub use memory::MemoryStore;
+pub use mongo::MongoStore;
+pub use sqlite::SqliteStore;
+
+/// Skip reason of trades already journaled when the bot first ran
+pub const HISTORICAL_TRADE: &str = "historical trade";
+
+/// Which backend holds the trade journal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageBackend {
+    Mongo,
+    Sqlite,
+    Memory,
+}
+
+impl StorageBackend {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "mongo" | "mongodb" => Some(Self::Mongo),
+            "sqlite" => Some(Self::Sqlite),
+            "memory" => Some(Self::Memory),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Mongo => "mongo",
+            Self::Sqlite => "sqlite",
+            Self::Memory => "memory",
+        }
+    }
+}
+
+/// Identity of a journaled trade within a trader's journal
+///
+/// One transaction can fill a trader in several assets (or on both sides of one), and each of
+/// those fills is a trade of its own, so the transaction hash alone does not identify a trade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TradeKey<'a> {
+    pub transaction_hash: &'a str,
+    pub asset: &'a str,
+    pub side: &'a str,
+}
+
+impl<'a> TradeKey<'a> {
+    pub fn of(trade: &'a UserActivityInterface) -> Self {
+        Self { transaction_hash: &trade.transaction_hash, asset: &trade.asset, side: &trade.side }
+    }
+
+    pub fn matches(&self, trade: &UserActivityInterface) -> bool {
+        *self == TradeKey::of(trade)
+    }
+}
+
+/// A transition of a trade's execution state
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradeUpdate {
+    pub status: ExecutionStatus,
+    /// Orders posted so far, replaces the stored count when set
+    pub attempts: Option<u32>,
+    /// Order accepted by the CLOB, appended to the trade's order IDs
+    pub order_id: Option<String>,
+    /// Tokens bought for this trade, referenced by later proportional sells
+    pub bought_tokens: Option<f64>,
+}
+
+impl TradeUpdate {
+    pub fn new(status: ExecutionStatus) -> Self {
+        Self { status, attempts: None, order_id: None, bought_tokens: None }
+    }
+}
+
+/// Journal of followed traders' trades and their execution state
+#[async_trait]
+pub trait TradeStore: Send + Sync {
+    /// Journal the trades whose `TradeKey` is not known yet
+    ///
+    /// Known trades recorded without market metadata (on-chain detection) get it filled in from
+    /// `activities`. Returns the newly journaled trades, with their id and pending.
+    async fn insert_trades_if_absent(
+        &self,
+        address: &str,
+        activities: &[UserActivityInterface],
+    ) -> AppResult<Vec<UserActivityInterface>>;
+
+    async fn count_trades(&self, address: &str) -> AppResult<u64>;
+
+    /// TRADE activities since `since` (unix seconds), oldest first
+    async fn trades_since(&self, address: &str, since: Option<i64>) -> AppResult<Vec<UserActivityInterface>>;
+
+    /// Trades not picked up by the executor yet, oldest first
+    async fn pending_trades(&self, address: &str) -> AppResult<Vec<UserActivityInterface>>;
+
+    /// Claimed or submitted trades, i.e. those interrupted if the bot is not running, oldest first
+    async fn in_flight_trades(&self, address: &str) -> AppResult<Vec<UserActivityInterface>>;
+
+    /// Skip every pending trade (first run), returns how many were updated
+    async fn skip_pending_trades(&self, address: &str, reason: &str) -> AppResult<u64>;
+
+    /// Move a pending trade to Claimed; false if it was not pending
+    ///
+    /// Only the caller that claimed a trade may place orders for it.
+    async fn claim_trade(&self, address: &str, key: TradeKey<'_>) -> AppResult<bool>;
+
+    /// Record a transition of a claimed trade
+    async fn update_trade(&self, address: &str, key: TradeKey<'_>, update: TradeUpdate) -> AppResult<()>;
+
+    /// Tokens bought by each filled BUY still tracked for an outcome token
+    async fn tracked_purchases(&self, address: &str, asset: &str, condition_id: &str) -> AppResult<Vec<f64>>;
+
+    /// Scale tracked purchases of an outcome token after a sell (0 clears them)
+    async fn scale_tracked_purchases(&self, address: &str, asset: &str, condition_id: &str, factor: f64) -> AppResult<()>;
+}
+
+/// Followed traders' positions as last reported by the data API
+#[async_trait]
+pub trait PositionStore: Send + Sync {
+    /// Insert or replace each position keyed by (asset, conditionId)
+    async fn upsert_positions(&self, address: &str, positions: &[UserPositionInterface]) -> AppResult<()>;
+
+    async fn positions(&self, address: &str) -> AppResult<Vec<UserPositionInterface>>;
+}
+
+/// Our own orders and their fills, linked to the trader activity they copied
+#[async_trait]
+pub trait LedgerStore: Send + Sync {
+    /// Record a posted order and, if it matched, its fill
+    async fn record_order(&self, order: &OrderRecord, fill: Option<&FillRecord>) -> AppResult<()>;
+
+    /// Record a later fill of an order already recorded (a resting limit order)
+    async fn record_fill(&self, fill: &FillRecord) -> AppResult<()>;
+
+    /// Orders posted to copy one trader activity, oldest first
+    async fn orders_for_trade(&self, trader_address: &str, transaction_hash: &str) -> AppResult<Vec<OrderRecord>>;
+
+    /// Fills of the orders copying one trader activity, oldest first
+    async fn fills_for_trade(&self, trader_address: &str, transaction_hash: &str) -> AppResult<Vec<FillRecord>>;
+
+    /// Every fill since `since` (unix milliseconds), oldest first
+    async fn fills_since(&self, since: Option<i64>) -> AppResult<Vec<FillRecord>>;
+}
+
+/// A complete storage backend
+#[async_trait]
+pub trait Storage: TradeStore + PositionStore + LedgerStore {
+    fn backend(&self) -> StorageBackend;
+
+    /// Check the backend is reachable
+    async fn ping(&self) -> AppResult<()>;
+
+    async fn close(&self) -> AppResult<()> {
+        Ok(())
+    }
+}
+
+static STORAGE: OnceLock<Box<dyn Storage>> = OnceLock::new();
+
+/// Open the backend selected by STORAGE_BACKEND (requires `load_env`)
+pub async fn init_storage() -> AppResult<&'static dyn Storage> {
+    if let Some(storage) = STORAGE.get() {
+        return Ok(storage.as_ref());
+    }
+    let opened: Box<dyn Storage> = match ENV().storage_backend {
+        StorageBackend::Mongo => Box::new(MongoStore::connect().await?),
+        StorageBackend::Sqlite => Box::new(SqliteStore::open(&ENV().sqlite_path)?),
+        StorageBackend::Memory => Box::new(MemoryStore::new()),
+    };
+    let _ = STORAGE.set(opened);
+    Ok(storage())
+}
+
+/// Storage handle (panics if `init_storage` has not completed)
+pub fn storage() -> &'static dyn Storage {
+    STORAGE
+        .get()
+        .map(|storage| storage.as_ref())
+        .expect("Storage not initialized - call init_storage() first")
+}
+
+pub async fn close_storage() -> AppResult<()> {
+    match STORAGE.get() {
+        Some(storage) => storage.close().await,
+        None => Ok(()),
+    }
+}
+
+fn storage_error(e: impl std::fmt::Display) -> AppError {
+    AppError::DatabaseError(e.to_string())
+}
+
+/// A freshly journaled copy of `activity`
+fn new_trade(activity: &UserActivityInterface, id: Option<ObjectId>) -> UserActivityInterface {
+    UserActivityInterface {
+        _id: id,
+        execution: ExecutionState { updated_at: now_millis(), ..Default::default() },
+        my_bought_size: None,
+        ..activity.clone()
+    }
+}
+
+fn now_millis() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// Apply a transition, stamping when the trade was claimed, first submitted and finished
+fn apply_update(state: &mut ExecutionState, update: &TradeUpdate, now: i64) {
+    state.status = update.status.clone();
+    state.updated_at = now;
+    if let Some(attempts) = update.attempts {
+        state.attempts = attempts;
+    }
+    if let Some(order_id) = &update.order_id {
+        state.order_ids.push(order_id.clone());
+    }
+    match update.status {
+        ExecutionStatus::Claimed => state.claimed_at = Some(now),
+        ExecutionStatus::Submitted => {
+            state.submitted_at.get_or_insert(now);
+        }
+        _ if update.status.is_final() => state.completed_at = Some(now),
+        _ => {}
+    }
+}
+
+/// Execution state of a record journaled with the old `bot`/`botExcutedTime` flags
+///
+/// `botExcutedTime` was 0 pending, 1 in flight, 999 historical, otherwise the attempts used
+/// before giving up.
+fn legacy_execution_state(bot: bool, executed_time: i64, bought_tokens: Option<f64>, now: i64) -> ExecutionState {
+    let status = match (bot, executed_time) {
+        (false, 0) => ExecutionStatus::Pending,
+        (false, _) => ExecutionStatus::Claimed,
+        (true, _) if bought_tokens.is_some_and(|tokens| tokens > 0.0) => ExecutionStatus::Filled,
+        (true, 999) => ExecutionStatus::Skipped { reason: HISTORICAL_TRADE.to_string() },
+        (true, 0) => ExecutionStatus::Skipped { reason: "not copied".to_string() },
+        (true, attempts) => ExecutionStatus::Failed { error: format!("gave up after {} attempts", attempts) },
+    };
+    let attempts = if matches!(status, ExecutionStatus::Failed { .. }) { executed_time as u32 } else { 0 };
+    ExecutionState { status, attempts, updated_at: now, ..Default::default() }
+}
+
+/// Copy market metadata onto a trade recorded without it
+fn fill_metadata(trade: &mut UserActivityInterface, source: &UserActivityInterface) {
+    trade.condition_id = source.condition_id.clone();
+    trade.outcome_index = source.outcome_index;
+    trade.title = source.title.clone();
+    trade.slug = source.slug.clone();
+    trade.icon = source.icon.clone();
+    trade.event_slug = source.event_slug.clone();
+    trade.outcome = source.outcome.clone();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_backend_names() {
+        assert_eq!(StorageBackend::parse("MongoDB"), Some(StorageBackend::Mongo));
+        assert_eq!(StorageBackend::parse(" sqlite "), Some(StorageBackend::Sqlite));
+        assert_eq!(StorageBackend::parse("memory"), Some(StorageBackend::Memory));
+        assert_eq!(StorageBackend::parse("postgres"), None);
+    }
+
+    #[test]
+    fn stamps_transitions() {
+        let mut state = ExecutionState::default();
+        apply_update(&mut state, &TradeUpdate::new(ExecutionStatus::Claimed), 10);
+        let submitted = |order: &str| TradeUpdate {
+            attempts: Some(2),
+            order_id: Some(order.to_string()),
+            ..TradeUpdate::new(ExecutionStatus::Submitted)
+        };
+        apply_update(&mut state, &submitted("a"), 20);
+        apply_update(&mut state, &submitted("b"), 30);
+        apply_update(&mut state, &TradeUpdate::new(ExecutionStatus::Filled), 40);
+        assert_eq!(state.status, ExecutionStatus::Filled);
+        assert_eq!(state.order_ids, vec!["a", "b"]);
+        assert_eq!(state.attempts, 2);
+        assert_eq!((state.claimed_at, state.submitted_at, state.completed_at), (Some(10), Some(20), Some(40)));
+        assert_eq!(state.updated_at, 40);
+    }
+
+    #[test]
+    fn maps_legacy_flags() {
+        let status = |bot, time, bought| legacy_execution_state(bot, time, bought, 0).status;
+        assert_eq!(status(false, 0, None), ExecutionStatus::Pending);
+        assert_eq!(status(false, 1, None), ExecutionStatus::Claimed);
+        assert_eq!(status(true, 0, Some(3.0)), ExecutionStatus::Filled);
+        assert_eq!(status(true, 999, None), ExecutionStatus::Skipped { reason: HISTORICAL_TRADE.to_string() });
+        assert_eq!(legacy_execution_state(true, 3, Some(0.0), 0).attempts, 3);
+    }
+}
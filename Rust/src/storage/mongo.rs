@@ -0,0 +1,602 @@
+// MongoDB backend: one user_activities_{address} and user_positions_{address} collection per trader
+
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use mongodb::bson::{doc, Bson, Document};
+use mongodb::Collection;
+use crate::config::db::{close_db, connect_db, get_database};
+use crate::config::env::ENV;
+use crate::interfaces::execution::{ExecutionState, ExecutionStatus};
+use crate::interfaces::ledger::{FillRecord, OrderRecord};
+use crate::interfaces::user::{UserActivityInterface, UserPositionInterface};
+use crate::models::ledger::{get_my_fill_collection, get_my_order_collection};
+use crate::models::user_history::{get_user_activity_collection, get_user_position_collection};
+use crate::storage::{
+    legacy_execution_state, new_trade, now_millis, storage_error, LedgerStore, PositionStore, Storage, StorageBackend,
+    TradeKey, TradeStore, TradeUpdate,
+};
+use crate::utils::constants::{DB_CONSTANTS, DB_FIELDS};
+use crate::utils::errors::{AppError, AppResult};
+use crate::utils::logger::Logger;
+
+pub struct MongoStore;
+
+impl MongoStore {
+    /// Connect the shared client (see `config::db`)
+    pub async fn connect() -> AppResult<Self> {
+        connect_db().await?;
+        for address in &ENV().user_addresses {
+            migrate_legacy_flags(address).await?;
+        }
+        Ok(Self)
+    }
+}
+
+/// Give records journaled with the old `bot`/`botExcutedTime` flags an execution state
+async fn migrate_legacy_flags(address: &str) -> AppResult<()> {
+    let collection = get_user_activity_collection(address);
+    let mut cursor = collection
+        .find(doc! { DB_FIELDS::EXECUTION: { "$exists": false } })
+        .projection(doc! { DB_FIELDS::BOT_EXECUTED: 1, DB_FIELDS::BOT_EXECUTED_TIME: 1, DB_FIELDS::MY_BOUGHT_SIZE: 1 })
+        .await
+        .map_err(storage_error)?;
+
+    let now = now_millis();
+    let mut statements = Vec::new();
+    while let Some(legacy) = cursor.try_next().await.map_err(storage_error)? {
+        let Ok(id) = legacy.get_object_id("_id") else {
+            continue;
+        };
+        let state = legacy_execution_state(
+            legacy.get_bool(DB_FIELDS::BOT_EXECUTED).unwrap_or(false),
+            bson_number(&legacy, DB_FIELDS::BOT_EXECUTED_TIME) as i64,
+            Some(bson_number(&legacy, DB_FIELDS::MY_BOUGHT_SIZE)),
+            now,
+        );
+        let update = doc! {
+            "$set": { DB_FIELDS::EXECUTION: to_bson(&state)? },
+            "$unset": { DB_FIELDS::BOT_EXECUTED: "", DB_FIELDS::BOT_EXECUTED_TIME: "" },
+        };
+        statements.push(update_statement(doc! { "_id": id }, update, false));
+    }
+
+    let migrated = statements.len();
+    for batch in statements.chunks(LEGACY_MIGRATION_BATCH) {
+        bulk_update(&collection, batch.to_vec()).await?;
+    }
+    if migrated > 0 {
+        Logger::info(&format!("Migrated {} trade records of {} to execution states", migrated, address));
+    }
+    Ok(())
+}
+
+/// Statements per `update` command when migrating
+const LEGACY_MIGRATION_BATCH: usize = 1_000;
+
+fn to_bson<T: serde::Serialize>(value: &T) -> AppResult<Bson> {
+    mongodb::bson::to_bson(value).map_err(storage_error)
+}
+
+/// `$set`/`$push` of a transition, mirroring `apply_update`
+fn execution_update(update: &TradeUpdate, now: i64) -> AppResult<Document> {
+    let field = |name: &str| format!("{}.{}", DB_FIELDS::EXECUTION, name);
+    let mut set = doc! { field("updatedAt"): now };
+    // The status and its reason/error are written together so stale details never linger
+    let mut unset = doc! { field("reason"): "", field("error"): "" };
+    if let Bson::Document(status) = to_bson(&update.status)? {
+        for (key, value) in status {
+            unset.remove(field(&key));
+            set.insert(field(&key), value);
+        }
+    }
+    if let Some(attempts) = update.attempts {
+        set.insert(field("attempts"), attempts as i64);
+    }
+    if let Some(bought_tokens) = update.bought_tokens {
+        set.insert(DB_FIELDS::MY_BOUGHT_SIZE, bought_tokens);
+    }
+
+    let mut document = doc! {};
+    match update.status {
+        ExecutionStatus::Claimed => {
+            set.insert(field("claimedAt"), now);
+        }
+        // $min keeps the first submission time
+        ExecutionStatus::Submitted => {
+            document.insert("$min", doc! { field("submittedAt"): now });
+        }
+        _ if update.status.is_final() => {
+            set.insert(field("completedAt"), now);
+        }
+        _ => {}
+    }
+    if let Some(order_id) = &update.order_id {
+        document.insert("$push", doc! { field("orderIds"): order_id });
+    }
+    document.insert("$set", set);
+    if !unset.is_empty() {
+        document.insert("$unset", unset);
+    }
+    Ok(document)
+}
+
+/// Read a numeric BSON field regardless of how it was stored
+fn bson_number(document: &Document, key: &str) -> f64 {
+    match document.get(key) {
+        Some(Bson::Double(value)) => *value,
+        Some(Bson::Int32(value)) => *value as f64,
+        Some(Bson::Int64(value)) => *value as f64,
+        _ => 0.0,
+    }
+}
+
+fn tracked_purchases_filter(asset: &str, condition_id: &str) -> Document {
+    doc! {
+        "asset": asset,
+        "conditionId": condition_id,
+        "side": DB_FIELDS::SIDE_BUY,
+        DB_FIELDS::EXECUTION_STATUS: { "$in": [ExecutionStatus::FILLED, ExecutionStatus::PARTIALLY_FILLED] },
+        DB_FIELDS::MY_BOUGHT_SIZE: { "$exists": true, "$gt": 0 },
+    }
+}
+
+async fn find_activities(address: &str, filter: Document) -> AppResult<Vec<UserActivityInterface>> {
+    let mut cursor = get_user_activity_collection(address)
+        .find(filter)
+        .sort(doc! { "timestamp": 1 })
+        .await
+        .map_err(storage_error)?;
+
+    let mut trades = Vec::new();
+    while let Some(document) = cursor.try_next().await.map_err(storage_error)? {
+        // Skip documents that don't match the activity shape
+        if let Ok(trade) = mongodb::bson::from_document::<UserActivityInterface>(document) {
+            trades.push(trade);
+        }
+    }
+    Ok(trades)
+}
+
+/// One statement of an `update` command
+fn update_statement(filter: Document, update: Document, upsert: bool) -> Document {
+    doc! { "q": filter, "u": update, "upsert": upsert }
+}
+
+/// Matches one journaled trade
+fn trade_filter(key: TradeKey<'_>) -> Document {
+    doc! {
+        DB_FIELDS::TRANSACTION_HASH: key.transaction_hash,
+        DB_FIELDS::ASSET: key.asset,
+        DB_FIELDS::SIDE: key.side,
+    }
+}
+
+/// Upserts that journal unknown trades, followed by metadata backfills for known on-chain records
+///
+/// A trade already journaled matches its upsert and is left untouched by `$setOnInsert`.
+fn trade_statements(activities: &[UserActivityInterface]) -> AppResult<Vec<Document>> {
+    let execution = to_bson(&ExecutionState { updated_at: now_millis(), ..Default::default() })?;
+    let upserts = activities.iter().map(|activity| {
+        // The key fields are copied from the filter on insert
+        let filter = trade_filter(TradeKey::of(activity));
+        let new_activity = doc! {
+            "proxyWallet": &activity.proxy_wallet,
+            "timestamp": activity.timestamp,
+            "conditionId": &activity.condition_id,
+            "type": &activity.r#type,
+            "size": activity.size,
+            "usdcSize": activity.usdc_size,
+            "price": activity.price,
+            "outcomeIndex": activity.outcome_index,
+            "title": &activity.title,
+            "slug": &activity.slug,
+            "icon": &activity.icon,
+            "eventSlug": &activity.event_slug,
+            "outcome": &activity.outcome,
+            "name": &activity.name,
+            "pseudonym": &activity.pseudonym,
+            "bio": &activity.bio,
+            "profileImage": &activity.profile_image,
+            "profileImageOptimized": &activity.profile_image_optimized,
+            DB_FIELDS::EXECUTION: execution.clone(),
+        };
+        update_statement(filter, doc! { "$setOnInsert": new_activity }, true)
+    });
+
+    let backfills = activities
+        .iter()
+        .filter(|activity| !activity.condition_id.is_empty())
+        .map(|activity| {
+            let mut filter = trade_filter(TradeKey::of(activity));
+            filter.insert(DB_FIELDS::CONDITION_ID, "");
+            let update = doc! {
+                "$set": {
+                    "conditionId": &activity.condition_id,
+                    "outcomeIndex": activity.outcome_index,
+                    "title": &activity.title,
+                    "slug": &activity.slug,
+                    "icon": &activity.icon,
+                    "eventSlug": &activity.event_slug,
+                    "outcome": &activity.outcome,
+                }
+            };
+            update_statement(filter, update, false)
+        });
+
+    Ok(upserts.chain(backfills).collect())
+}
+
+fn position_fields(position: &UserPositionInterface) -> Document {
+    doc! {
+        "proxyWallet": &position.proxy_wallet,
+        "asset": &position.asset,
+        "conditionId": &position.condition_id,
+        "size": position.size,
+        "avgPrice": position.avg_price,
+        "initialValue": position.initial_value,
+        "currentValue": position.current_value,
+        "cashPnl": position.cash_pnl,
+        "percentPnl": position.percent_pnl,
+        "totalBought": position.total_bought,
+        "realizedPnl": position.realized_pnl,
+        "percentRealizedPnl": position.percent_realized_pnl,
+        "curPrice": position.cur_price,
+        "redeemable": position.redeemable,
+        "mergeable": position.mergeable,
+        "title": &position.title,
+        "slug": &position.slug,
+        "icon": &position.icon,
+        "eventSlug": &position.event_slug,
+        "outcome": &position.outcome,
+        "outcomeIndex": position.outcome_index,
+        "oppositeOutcome": &position.opposite_outcome,
+        "oppositeAsset": &position.opposite_asset,
+        "endDate": &position.end_date,
+        "negativeRisk": position.negative_risk,
+    }
+}
+
+fn bson_index(value: Option<&Bson>) -> Option<usize> {
+    match value {
+        Some(Bson::Int32(index)) => usize::try_from(*index).ok(),
+        Some(Bson::Int64(index)) => usize::try_from(*index).ok(),
+        _ => None,
+    }
+}
+
+/// Upserted (statement index, _id) pairs of an `update` command reply
+///
+/// A duplicate key error only means a concurrent upsert of the same key won; the document
+/// exists either way. Any other write error fails the batch.
+fn parse_update_reply(collection: &str, reply: &Document) -> AppResult<Vec<(usize, Bson)>> {
+    if let Ok(errors) = reply.get_array("writeErrors") {
+        let failure = errors
+            .iter()
+            .filter_map(Bson::as_document)
+            .find(|error| error.get_i32("code").ok() != Some(DB_CONSTANTS::DUPLICATE_KEY_CODE));
+        if let Some(error) = failure {
+            return Err(AppError::DatabaseError(format!(
+                "Bulk write to {} failed: {}",
+                collection,
+                error.get_str("errmsg").unwrap_or("unknown error")
+            )));
+        }
+    }
+    if let Ok(error) = reply.get_document("writeConcernError") {
+        return Err(AppError::DatabaseError(format!(
+            "Bulk write to {} not acknowledged: {}",
+            collection,
+            error.get_str("errmsg").unwrap_or("write concern error")
+        )));
+    }
+
+    Ok(reply
+        .get_array("upserted")
+        .map(|upserted| {
+            upserted
+                .iter()
+                .filter_map(Bson::as_document)
+                .filter_map(|entry| Some((bson_index(entry.get("index"))?, entry.get("_id")?.clone())))
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+/// Apply all statements in one unordered `update` command (a single round trip on any server version)
+async fn bulk_update(collection: &Collection<Document>, statements: Vec<Document>) -> AppResult<Vec<(usize, Bson)>> {
+    if statements.is_empty() {
+        return Ok(Vec::new());
+    }
+    let command = doc! {
+        "update": collection.name(),
+        "updates": statements,
+        "ordered": false,
+    };
+    let reply = get_database().run_command(command).await.map_err(storage_error)?;
+    parse_update_reply(collection.name(), &reply)
+}
+
+#[async_trait]
+impl TradeStore for MongoStore {
+    async fn insert_trades_if_absent(
+        &self,
+        address: &str,
+        activities: &[UserActivityInterface],
+    ) -> AppResult<Vec<UserActivityInterface>> {
+        let collection = get_user_activity_collection(address);
+        let upserted = bulk_update(&collection, trade_statements(activities)?).await?;
+        // Upserts come first in the batch, so their indexes map back onto `activities`
+        Ok(upserted
+            .into_iter()
+            .filter_map(|(index, id)| Some(new_trade(activities.get(index)?, id.as_object_id())))
+            .collect())
+    }
+
+    async fn count_trades(&self, address: &str) -> AppResult<u64> {
+        get_user_activity_collection(address)
+            .count_documents(doc! {})
+            .await
+            .map_err(storage_error)
+    }
+
+    async fn trades_since(&self, address: &str, since: Option<i64>) -> AppResult<Vec<UserActivityInterface>> {
+        let mut filter = doc! { "type": DB_FIELDS::TYPE_TRADE };
+        if let Some(since) = since {
+            filter.insert("timestamp", doc! { "$gte": since });
+        }
+        find_activities(address, filter).await
+    }
+
+    async fn pending_trades(&self, address: &str) -> AppResult<Vec<UserActivityInterface>> {
+        find_activities(address, doc! { DB_FIELDS::EXECUTION_STATUS: ExecutionStatus::PENDING }).await
+    }
+
+    async fn in_flight_trades(&self, address: &str) -> AppResult<Vec<UserActivityInterface>> {
+        let statuses = [ExecutionStatus::CLAIMED, ExecutionStatus::SUBMITTED];
+        find_activities(address, doc! { DB_FIELDS::EXECUTION_STATUS: { "$in": statuses.as_slice() } }).await
+    }
+
+    async fn skip_pending_trades(&self, address: &str, reason: &str) -> AppResult<u64> {
+        let update = TradeUpdate::new(ExecutionStatus::Skipped { reason: reason.to_string() });
+        let result = get_user_activity_collection(address)
+            .update_many(
+                doc! { DB_FIELDS::EXECUTION_STATUS: ExecutionStatus::PENDING },
+                execution_update(&update, now_millis())?,
+            )
+            .await
+            .map_err(storage_error)?;
+        Ok(result.modified_count)
+    }
+
+    async fn claim_trade(&self, address: &str, key: TradeKey<'_>) -> AppResult<bool> {
+        // Matching on the status makes the claim a compare-and-set
+        let mut filter = trade_filter(key);
+        filter.insert(DB_FIELDS::EXECUTION_STATUS, ExecutionStatus::PENDING);
+        let update = execution_update(&TradeUpdate::new(ExecutionStatus::Claimed), now_millis())?;
+        let result = get_user_activity_collection(address)
+            .update_one(filter, update)
+            .await
+            .map_err(storage_error)?;
+        Ok(result.modified_count > 0)
+    }
+
+    async fn update_trade(&self, address: &str, key: TradeKey<'_>, update: TradeUpdate) -> AppResult<()> {
+        get_user_activity_collection(address)
+            .update_one(trade_filter(key), execution_update(&update, now_millis())?)
+            .await
+            .map_err(storage_error)?;
+        Ok(())
+    }
+
+    async fn tracked_purchases(&self, address: &str, asset: &str, condition_id: &str) -> AppResult<Vec<f64>> {
+        let mut cursor = get_user_activity_collection(address)
+            .find(tracked_purchases_filter(asset, condition_id))
+            .await
+            .map_err(storage_error)?;
+        let mut purchases = Vec::new();
+        while let Some(buy) = cursor.try_next().await.map_err(storage_error)? {
+            purchases.push(bson_number(&buy, DB_FIELDS::MY_BOUGHT_SIZE));
+        }
+        Ok(purchases)
+    }
+
+    async fn scale_tracked_purchases(&self, address: &str, asset: &str, condition_id: &str, factor: f64) -> AppResult<()> {
+        let update = if factor <= 0.0 {
+            doc! { "$set": { DB_FIELDS::MY_BOUGHT_SIZE: 0.0 } }
+        } else {
+            doc! { "$mul": { DB_FIELDS::MY_BOUGHT_SIZE: factor } }
+        };
+        get_user_activity_collection(address)
+            .update_many(tracked_purchases_filter(asset, condition_id), update)
+            .await
+            .map_err(storage_error)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl PositionStore for MongoStore {
+    async fn upsert_positions(&self, address: &str, positions: &[UserPositionInterface]) -> AppResult<()> {
+        let statements = positions
+            .iter()
+            .map(|position| {
+                let filter = doc! {
+                    DB_FIELDS::ASSET: &position.asset,
+                    DB_FIELDS::CONDITION_ID: &position.condition_id,
+                };
+                update_statement(filter, doc! { "$set": position_fields(position) }, true)
+            })
+            .collect();
+        bulk_update(&get_user_position_collection(address), statements).await?;
+        Ok(())
+    }
+
+    async fn positions(&self, address: &str) -> AppResult<Vec<UserPositionInterface>> {
+        let mut cursor = get_user_position_collection(address)
+            .find(doc! {})
+            .await
+            .map_err(storage_error)?;
+        let mut positions = Vec::new();
+        while let Some(document) = cursor.try_next().await.map_err(storage_error)? {
+            // Skip documents that don't match the position shape
+            if let Ok(position) = mongodb::bson::from_document::<UserPositionInterface>(document) {
+                positions.push(position);
+            }
+        }
+        Ok(positions)
+    }
+}
+
+/// Ledger records matching `filter`, sorted by `sort_field`
+async fn find_records<T: serde::de::DeserializeOwned>(
+    collection: Collection<Document>,
+    filter: Document,
+    sort_field: &str,
+) -> AppResult<Vec<T>> {
+    let mut cursor = collection
+        .find(filter)
+        .sort(doc! { sort_field: 1, "_id": 1 })
+        .await
+        .map_err(storage_error)?;
+    let mut records = Vec::new();
+    while let Some(document) = cursor.try_next().await.map_err(storage_error)? {
+        if let Ok(record) = mongodb::bson::from_document(document) {
+            records.push(record);
+        }
+    }
+    Ok(records)
+}
+
+fn source_filter(trader_address: &str, transaction_hash: &str) -> Document {
+    doc! { DB_FIELDS::TRADER_ADDRESS: trader_address, DB_FIELDS::SOURCE_TRANSACTION_HASH: transaction_hash }
+}
+
+#[async_trait]
+impl LedgerStore for MongoStore {
+    async fn record_order(&self, order: &OrderRecord, fill: Option<&FillRecord>) -> AppResult<()> {
+        get_my_order_collection()
+            .insert_one(mongodb::bson::to_document(order).map_err(storage_error)?)
+            .await
+            .map_err(storage_error)?;
+        if let Some(fill) = fill {
+            get_my_fill_collection()
+                .insert_one(mongodb::bson::to_document(fill).map_err(storage_error)?)
+                .await
+                .map_err(storage_error)?;
+        }
+        Ok(())
+    }
+
+    async fn record_fill(&self, fill: &FillRecord) -> AppResult<()> {
+        get_my_fill_collection()
+            .insert_one(mongodb::bson::to_document(fill).map_err(storage_error)?)
+            .await
+            .map_err(storage_error)?;
+        Ok(())
+    }
+
+    async fn orders_for_trade(&self, trader_address: &str, transaction_hash: &str) -> AppResult<Vec<OrderRecord>> {
+        let filter = source_filter(trader_address, transaction_hash);
+        find_records(get_my_order_collection(), filter, DB_FIELDS::SUBMITTED_AT).await
+    }
+
+    async fn fills_for_trade(&self, trader_address: &str, transaction_hash: &str) -> AppResult<Vec<FillRecord>> {
+        let filter = source_filter(trader_address, transaction_hash);
+        find_records(get_my_fill_collection(), filter, DB_FIELDS::FILLED_AT).await
+    }
+
+    async fn fills_since(&self, since: Option<i64>) -> AppResult<Vec<FillRecord>> {
+        let filter = match since {
+            Some(since) => doc! { DB_FIELDS::FILLED_AT: { "$gte": since } },
+            None => doc! {},
+        };
+        find_records(get_my_fill_collection(), filter, DB_FIELDS::FILLED_AT).await
+    }
+}
+
+#[async_trait]
+impl Storage for MongoStore {
+    fn backend(&self) -> StorageBackend {
+        StorageBackend::Mongo
+    }
+
+    async fn ping(&self) -> AppResult<()> {
+        get_database()
+            .run_command(doc! { "ping": 1 })
+            .await
+            .map_err(storage_error)?;
+        Ok(())
+    }
+
+    async fn close(&self) -> AppResult<()> {
+        close_db().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_bought_size_stored_as_any_number_type() {
+        let document = doc! { "a": 1.5, "b": 2_i32, "c": 3_i64, "d": "x" };
+        assert_eq!(bson_number(&document, "a"), 1.5);
+        assert_eq!(bson_number(&document, "b"), 2.0);
+        assert_eq!(bson_number(&document, "c"), 3.0);
+        assert_eq!(bson_number(&document, "d"), 0.0);
+        assert_eq!(bson_number(&document, "missing"), 0.0);
+    }
+
+    #[test]
+    fn upserts_come_before_backfills() {
+        let mut onchain: UserActivityInterface =
+            serde_json::from_value(serde_json::json!({ "timestamp": 1, "transactionHash": "0x01" })).unwrap();
+        let mut api = onchain.clone();
+        api.transaction_hash = "0x02".to_string();
+        api.condition_id = "0xabc".to_string();
+        onchain.condition_id.clear();
+
+        let statements = trade_statements(&[onchain, api]).unwrap();
+        let upserts: Vec<bool> = statements.iter().map(|s| s.get_bool("upsert").unwrap()).collect();
+        assert_eq!(upserts, vec![true, true, false]);
+        assert!(statements[0].get_document("u").unwrap().contains_key("$setOnInsert"));
+        assert_eq!(statements[2].get_document("q").unwrap().get_str("transactionHash"), Ok("0x02"));
+    }
+
+    #[test]
+    fn transitions_set_status_details_and_timestamps() {
+        let failed = TradeUpdate {
+            attempts: Some(3),
+            ..TradeUpdate::new(ExecutionStatus::Failed { error: "no liquidity".to_string() })
+        };
+        let update = execution_update(&failed, 50).unwrap();
+        let set = update.get_document("$set").unwrap();
+        assert_eq!(set.get_str("execution.status"), Ok("FAILED"));
+        assert_eq!(set.get_str("execution.error"), Ok("no liquidity"));
+        assert_eq!(set.get_i64("execution.attempts"), Ok(3));
+        assert_eq!(set.get_i64("execution.completedAt"), Ok(50));
+        let unset: Vec<&String> = update.get_document("$unset").unwrap().keys().collect();
+        assert_eq!(unset, vec!["execution.reason"]);
+
+        let submitted = TradeUpdate { order_id: Some("0x1".to_string()), ..TradeUpdate::new(ExecutionStatus::Submitted) };
+        let update = execution_update(&submitted, 60).unwrap();
+        assert_eq!(update.get_document("$min").unwrap().get_i64("execution.submittedAt"), Ok(60));
+        assert_eq!(update.get_document("$push").unwrap().get_str("execution.orderIds"), Ok("0x1"));
+    }
+
+    #[test]
+    fn reads_upserted_ids_and_tolerates_duplicate_keys() {
+        let id = mongodb::bson::oid::ObjectId::new();
+        let reply = doc! {
+            "n": 3,
+            "nModified": 0,
+            "upserted": [{ "index": 2, "_id": id }],
+            "writeErrors": [{ "index": 1, "code": 11000, "errmsg": "E11000 duplicate key error" }],
+            "ok": 1.0,
+        };
+        assert_eq!(parse_update_reply("c", &reply).unwrap(), vec![(2, Bson::ObjectId(id))]);
+
+        let reply = doc! { "writeErrors": [{ "index": 0, "code": 121, "errmsg": "Document failed validation" }] };
+        assert!(parse_update_reply("c", &reply).is_err());
+    }
+}
@@ -0,0 +1,461 @@
+// SQLite backend: a single local file, for deployments without a MongoDB server
+//
+// Trades and positions are kept as JSON next to the columns the bot queries on. Statements are
+// local and short, so they run inline under a mutex rather than on the blocking pool.
+
+use std::sync::{Mutex, MutexGuard};
+use async_trait::async_trait;
+use mongodb::bson::oid::ObjectId;
+use rusqlite::{params, Connection, OptionalExtension};
+use crate::interfaces::execution::ExecutionStatus;
+use crate::interfaces::ledger::{FillRecord, OrderRecord};
+use crate::interfaces::user::{UserActivityInterface, UserPositionInterface};
+use crate::storage::{
+    apply_update, fill_metadata, new_trade, now_millis, storage_error, LedgerStore, PositionStore,
+    Storage, StorageBackend, TradeKey, TradeStore, TradeUpdate,
+};
+use crate::utils::constants::DB_FIELDS;
+use crate::utils::errors::{AppError, AppResult};
+use crate::utils::logger::Logger;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS activities (
+        address           TEXT NOT NULL,
+        transaction_hash  TEXT NOT NULL,
+        timestamp         INTEGER NOT NULL,
+        type              TEXT NOT NULL,
+        asset             TEXT NOT NULL,
+        condition_id      TEXT NOT NULL,
+        side              TEXT NOT NULL,
+        status            TEXT NOT NULL DEFAULT 'PENDING',
+        my_bought_size    REAL,
+        data              TEXT NOT NULL,
+        PRIMARY KEY (address, transaction_hash, asset, side)
+    );
+    CREATE INDEX IF NOT EXISTS activities_by_time ON activities (address, timestamp);
+    CREATE INDEX IF NOT EXISTS activities_by_status ON activities (address, status);
+    CREATE INDEX IF NOT EXISTS activities_by_asset ON activities (address, asset, condition_id);
+    CREATE TABLE IF NOT EXISTS positions (
+        address      TEXT NOT NULL,
+        asset        TEXT NOT NULL,
+        condition_id TEXT NOT NULL,
+        data         TEXT NOT NULL,
+        PRIMARY KEY (address, asset, condition_id)
+    );
+    CREATE TABLE IF NOT EXISTS my_orders (
+        id                      INTEGER PRIMARY KEY AUTOINCREMENT,
+        trader_address          TEXT NOT NULL,
+        source_transaction_hash TEXT NOT NULL,
+        submitted_at            INTEGER NOT NULL,
+        data                    TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS my_orders_by_source ON my_orders (trader_address, source_transaction_hash);
+    CREATE TABLE IF NOT EXISTS my_fills (
+        id                      INTEGER PRIMARY KEY AUTOINCREMENT,
+        trader_address          TEXT NOT NULL,
+        source_transaction_hash TEXT NOT NULL,
+        filled_at               INTEGER NOT NULL,
+        data                    TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS my_fills_by_source ON my_fills (trader_address, source_transaction_hash);
+    CREATE INDEX IF NOT EXISTS my_fills_by_time ON my_fills (filled_at);
+";
+
+/// Columns rebuilt into a `UserActivityInterface` by `read_activity`
+const ACTIVITY_COLUMNS: &str = "data, my_bought_size";
+
+/// Matches filled BUYs of an outcome token whose bought size is still tracked
+const TRACKED_PURCHASES: &str = "address = ?1 AND asset = ?2 AND condition_id = ?3 AND side = ?4 \
+     AND status IN ('FILLED', 'PARTIALLY_FILLED') AND my_bought_size > 0";
+
+pub struct SqliteStore {
+    connection: Mutex<Connection>,
+}
+
+impl SqliteStore {
+    /// Open (or create) the database file; `:memory:` gives a private in-memory database
+    pub fn open(path: &str) -> AppResult<Self> {
+        let connection = Connection::open(path)
+            .map_err(|e| AppError::DatabaseError(format!("Cannot open SQLite database {}: {}", path, e)))?;
+        // WAL keeps readers (e.g. the backtest) from blocking the bot; not available in memory
+        let _ = connection.pragma_update(None, "journal_mode", "WAL");
+        connection
+            .execute_batch(SCHEMA)
+            .map_err(|e| AppError::DatabaseError(format!("Cannot create SQLite schema in {}: {}", path, e)))?;
+        Logger::success(&format!("SQLite storage ready ({})", path));
+        Ok(Self { connection: Mutex::new(connection) })
+    }
+
+    fn connection(&self) -> MutexGuard<'_, Connection> {
+        self.connection.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn query_activities(&self, sql: &str, params: impl rusqlite::Params) -> AppResult<Vec<UserActivityInterface>> {
+        let connection = self.connection();
+        let mut statement = connection.prepare(sql).map_err(storage_error)?;
+        let rows = statement.query_map(params, read_activity).map_err(storage_error)?;
+        let mut trades = Vec::new();
+        for row in rows {
+            // Skip rows whose JSON no longer matches the activity shape
+            if let Ok(Some(trade)) = row {
+                trades.push(trade);
+            }
+        }
+        Ok(trades)
+    }
+}
+
+/// Rebuild an activity from its JSON (which carries the execution state) and bought size
+fn read_activity(row: &rusqlite::Row<'_>) -> rusqlite::Result<Option<UserActivityInterface>> {
+    let data: String = row.get(0)?;
+    let Ok(mut trade) = serde_json::from_str::<UserActivityInterface>(&data) else {
+        return Ok(None);
+    };
+    trade.my_bought_size = row.get(1)?;
+    Ok(Some(trade))
+}
+
+/// Apply `update` to a trade stored as `data`
+fn write_update(connection: &Connection, address: &str, data: &str, update: &TradeUpdate, now: i64) -> AppResult<()> {
+    let Ok(mut trade) = serde_json::from_str::<UserActivityInterface>(data) else {
+        return Ok(());
+    };
+    apply_update(&mut trade.execution, update, now);
+    let key = TradeKey::of(&trade);
+    connection
+        .execute(
+            "UPDATE activities SET status = ?5, data = ?6, my_bought_size = COALESCE(?7, my_bought_size)
+             WHERE address = ?1 AND transaction_hash = ?2 AND asset = ?3 AND side = ?4",
+            params![
+                address,
+                key.transaction_hash,
+                key.asset,
+                key.side,
+                trade.execution.status.as_str(),
+                to_json(&trade)?,
+                update.bought_tokens,
+            ],
+        )
+        .map_err(storage_error)?;
+    Ok(())
+}
+
+/// Stored JSON of one trade, if it exists and its status passes `status`
+fn trade_data(
+    connection: &Connection,
+    address: &str,
+    key: TradeKey<'_>,
+    status: Option<&str>,
+) -> AppResult<Option<String>> {
+    connection
+        .query_row(
+            "SELECT data FROM activities
+             WHERE address = ?1 AND transaction_hash = ?2 AND asset = ?3 AND side = ?4 AND (?5 IS NULL OR status = ?5)",
+            params![address, key.transaction_hash, key.asset, key.side, status],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(storage_error)
+}
+
+/// Fill in market metadata on a known trade recorded without it
+fn backfill_metadata(connection: &Connection, address: &str, activity: &UserActivityInterface) -> AppResult<()> {
+    let data: Option<String> = connection
+        .query_row(
+            "SELECT data FROM activities
+             WHERE address = ?1 AND transaction_hash = ?2 AND asset = ?3 AND side = ?4 AND condition_id = ''",
+            params![address, activity.transaction_hash, activity.asset, activity.side],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(storage_error)?;
+    let Some(mut trade) = data.and_then(|data| serde_json::from_str::<UserActivityInterface>(&data).ok()) else {
+        return Ok(());
+    };
+
+    fill_metadata(&mut trade, activity);
+    connection
+        .execute(
+            "UPDATE activities SET condition_id = ?5, data = ?6
+             WHERE address = ?1 AND transaction_hash = ?2 AND asset = ?3 AND side = ?4",
+            params![
+                address,
+                activity.transaction_hash,
+                activity.asset,
+                activity.side,
+                trade.condition_id,
+                to_json(&trade)?,
+            ],
+        )
+        .map_err(storage_error)?;
+    Ok(())
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> AppResult<String> {
+    serde_json::to_string(value).map_err(storage_error)
+}
+
+/// Ledger records stored as JSON in the `data` column, skipping rows that no longer parse
+fn query_records<T: serde::de::DeserializeOwned>(
+    connection: &Connection,
+    sql: &str,
+    params: impl rusqlite::Params,
+) -> AppResult<Vec<T>> {
+    let mut statement = connection.prepare(sql).map_err(storage_error)?;
+    let rows = statement
+        .query_map(params, |row| row.get::<_, String>(0))
+        .map_err(storage_error)?;
+    Ok(rows.filter_map(|data| serde_json::from_str(&data.ok()?).ok()).collect())
+}
+
+#[async_trait]
+impl TradeStore for SqliteStore {
+    async fn insert_trades_if_absent(
+        &self,
+        address: &str,
+        activities: &[UserActivityInterface],
+    ) -> AppResult<Vec<UserActivityInterface>> {
+        let mut connection = self.connection();
+        let transaction = connection.transaction().map_err(storage_error)?;
+        let mut inserted = Vec::new();
+        for activity in activities {
+            let stored = new_trade(activity, Some(ObjectId::new()));
+            let added = transaction
+                .execute(
+                    "INSERT OR IGNORE INTO activities
+                         (address, transaction_hash, timestamp, type, asset, condition_id, side, data)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    params![
+                        address,
+                        stored.transaction_hash,
+                        stored.timestamp,
+                        stored.r#type,
+                        stored.asset,
+                        stored.condition_id,
+                        stored.side,
+                        to_json(&stored)?,
+                    ],
+                )
+                .map_err(storage_error)?;
+            if added > 0 {
+                inserted.push(stored);
+            } else if !activity.condition_id.is_empty() {
+                backfill_metadata(&transaction, address, activity)?;
+            }
+        }
+        transaction.commit().map_err(storage_error)?;
+        Ok(inserted)
+    }
+
+    async fn count_trades(&self, address: &str) -> AppResult<u64> {
+        let count: i64 = self
+            .connection()
+            .query_row("SELECT COUNT(*) FROM activities WHERE address = ?1", params![address], |row| row.get(0))
+            .map_err(storage_error)?;
+        Ok(count as u64)
+    }
+
+    async fn trades_since(&self, address: &str, since: Option<i64>) -> AppResult<Vec<UserActivityInterface>> {
+        self.query_activities(
+            &format!(
+                "SELECT {} FROM activities WHERE address = ?1 AND type = ?2 AND timestamp >= ?3 ORDER BY timestamp",
+                ACTIVITY_COLUMNS
+            ),
+            params![address, DB_FIELDS::TYPE_TRADE, since.unwrap_or(i64::MIN)],
+        )
+    }
+
+    async fn pending_trades(&self, address: &str) -> AppResult<Vec<UserActivityInterface>> {
+        self.query_activities(
+            &format!(
+                "SELECT {} FROM activities WHERE address = ?1 AND status = ?2 ORDER BY timestamp",
+                ACTIVITY_COLUMNS
+            ),
+            params![address, ExecutionStatus::PENDING],
+        )
+    }
+
+    async fn in_flight_trades(&self, address: &str) -> AppResult<Vec<UserActivityInterface>> {
+        self.query_activities(
+            &format!(
+                "SELECT {} FROM activities WHERE address = ?1 AND status IN (?2, ?3) ORDER BY timestamp",
+                ACTIVITY_COLUMNS
+            ),
+            params![address, ExecutionStatus::CLAIMED, ExecutionStatus::SUBMITTED],
+        )
+    }
+
+    async fn skip_pending_trades(&self, address: &str, reason: &str) -> AppResult<u64> {
+        let update = TradeUpdate::new(ExecutionStatus::Skipped { reason: reason.to_string() });
+        let now = now_millis();
+        let mut connection = self.connection();
+        let transaction = connection.transaction().map_err(storage_error)?;
+        let pending: Vec<String> = {
+            let mut statement = transaction
+                .prepare("SELECT data FROM activities WHERE address = ?1 AND status = ?2")
+                .map_err(storage_error)?;
+            let rows = statement
+                .query_map(params![address, ExecutionStatus::PENDING], |row| row.get(0))
+                .map_err(storage_error)?;
+            rows.collect::<rusqlite::Result<_>>().map_err(storage_error)?
+        };
+        for data in &pending {
+            write_update(&transaction, address, data, &update, now)?;
+        }
+        transaction.commit().map_err(storage_error)?;
+        Ok(pending.len() as u64)
+    }
+
+    async fn claim_trade(&self, address: &str, key: TradeKey<'_>) -> AppResult<bool> {
+        // The connection lock makes the check and the write one step
+        let connection = self.connection();
+        let Some(data) = trade_data(&connection, address, key, Some(ExecutionStatus::PENDING))? else {
+            return Ok(false);
+        };
+        let update = TradeUpdate::new(ExecutionStatus::Claimed);
+        write_update(&connection, address, &data, &update, now_millis())?;
+        Ok(true)
+    }
+
+    async fn update_trade(&self, address: &str, key: TradeKey<'_>, update: TradeUpdate) -> AppResult<()> {
+        let connection = self.connection();
+        if let Some(data) = trade_data(&connection, address, key, None)? {
+            write_update(&connection, address, &data, &update, now_millis())?;
+        }
+        Ok(())
+    }
+
+    async fn tracked_purchases(&self, address: &str, asset: &str, condition_id: &str) -> AppResult<Vec<f64>> {
+        let connection = self.connection();
+        let mut statement = connection
+            .prepare(&format!("SELECT my_bought_size FROM activities WHERE {}", TRACKED_PURCHASES))
+            .map_err(storage_error)?;
+        let purchases = statement
+            .query_map(params![address, asset, condition_id, DB_FIELDS::SIDE_BUY], |row| row.get(0))
+            .map_err(storage_error)?
+            .collect::<rusqlite::Result<Vec<f64>>>()
+            .map_err(storage_error)?;
+        Ok(purchases)
+    }
+
+    async fn scale_tracked_purchases(&self, address: &str, asset: &str, condition_id: &str, factor: f64) -> AppResult<()> {
+        self.connection()
+            .execute(
+                &format!("UPDATE activities SET my_bought_size = my_bought_size * ?5 WHERE {}", TRACKED_PURCHASES),
+                params![address, asset, condition_id, DB_FIELDS::SIDE_BUY, factor.max(0.0)],
+            )
+            .map_err(storage_error)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl PositionStore for SqliteStore {
+    async fn upsert_positions(&self, address: &str, positions: &[UserPositionInterface]) -> AppResult<()> {
+        let mut connection = self.connection();
+        let transaction = connection.transaction().map_err(storage_error)?;
+        for position in positions {
+            transaction
+                .execute(
+                    "INSERT INTO positions (address, asset, condition_id, data) VALUES (?1, ?2, ?3, ?4)
+                     ON CONFLICT (address, asset, condition_id) DO UPDATE SET data = excluded.data",
+                    params![address, position.asset, position.condition_id, to_json(position)?],
+                )
+                .map_err(storage_error)?;
+        }
+        transaction.commit().map_err(storage_error)
+    }
+
+    async fn positions(&self, address: &str) -> AppResult<Vec<UserPositionInterface>> {
+        query_records(&self.connection(), "SELECT data FROM positions WHERE address = ?1", params![address])
+    }
+}
+
+#[async_trait]
+impl LedgerStore for SqliteStore {
+    async fn record_order(&self, order: &OrderRecord, fill: Option<&FillRecord>) -> AppResult<()> {
+        let mut connection = self.connection();
+        let transaction = connection.transaction().map_err(storage_error)?;
+        transaction
+            .execute(
+                "INSERT INTO my_orders (trader_address, source_transaction_hash, submitted_at, data)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![order.trader_address, order.source_transaction_hash, order.submitted_at, to_json(order)?],
+            )
+            .map_err(storage_error)?;
+        if let Some(fill) = fill {
+            transaction
+                .execute(
+                    "INSERT INTO my_fills (trader_address, source_transaction_hash, filled_at, data)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    params![fill.trader_address, fill.source_transaction_hash, fill.filled_at, to_json(fill)?],
+                )
+                .map_err(storage_error)?;
+        }
+        transaction.commit().map_err(storage_error)
+    }
+
+    async fn record_fill(&self, fill: &FillRecord) -> AppResult<()> {
+        self.connection()
+            .execute(
+                "INSERT INTO my_fills (trader_address, source_transaction_hash, filled_at, data)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![fill.trader_address, fill.source_transaction_hash, fill.filled_at, to_json(fill)?],
+            )
+            .map_err(storage_error)?;
+        Ok(())
+    }
+
+    async fn orders_for_trade(&self, trader_address: &str, transaction_hash: &str) -> AppResult<Vec<OrderRecord>> {
+        query_records(
+            &self.connection(),
+            "SELECT data FROM my_orders WHERE trader_address = ?1 AND source_transaction_hash = ?2
+             ORDER BY submitted_at, id",
+            params![trader_address, transaction_hash],
+        )
+    }
+
+    async fn fills_for_trade(&self, trader_address: &str, transaction_hash: &str) -> AppResult<Vec<FillRecord>> {
+        query_records(
+            &self.connection(),
+            "SELECT data FROM my_fills WHERE trader_address = ?1 AND source_transaction_hash = ?2
+             ORDER BY filled_at, id",
+            params![trader_address, transaction_hash],
+        )
+    }
+
+    async fn fills_since(&self, since: Option<i64>) -> AppResult<Vec<FillRecord>> {
+        query_records(
+            &self.connection(),
+            "SELECT data FROM my_fills WHERE filled_at >= ?1 ORDER BY filled_at, id",
+            params![since.unwrap_or(i64::MIN)],
+        )
+    }
+}
+
+#[async_trait]
+impl Storage for SqliteStore {
+    fn backend(&self) -> StorageBackend {
+        StorageBackend::Sqlite
+    }
+
+    async fn ping(&self) -> AppResult<()> {
+        self.connection()
+            .query_row("SELECT 1", [], |_| Ok(()))
+            .map_err(storage_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::memory::tests::{exercise_ledger_store, exercise_position_store, exercise_trade_store};
+
+    #[tokio::test]
+    async fn sqlite_store_behaves_like_the_journal() {
+        let store = SqliteStore::open(":memory:").unwrap();
+        exercise_trade_store(&store).await;
+        exercise_position_store(&store).await;
+        exercise_ledger_store(&store).await;
+    }
+}
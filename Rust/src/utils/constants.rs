@@ -0,0 +1,207 @@
+// Application-wide constants
+// Namespaced as modules so call sites read like the TypeScript `CONSTANTS.FIELD` objects
+
+#![allow(non_snake_case)] // Intentionally UPPER_CASE to match TypeScript constant objects
+#![allow(dead_code)] // Not every constant is referenced by the Rust port yet
+
+/// Polymarket API endpoints
+pub mod POLYMARKET_API {
+    pub const DATA_API_BASE: &str = "https://data-api.polymarket.com";
+    pub const ACTIVITY_ENDPOINT: &str = "/activity";
+    pub const POSITIONS_ENDPOINT: &str = "/positions";
+    /// Market metadata, including resolution outcome prices
+    pub const GAMMA_API_BASE: &str = "https://gamma-api.polymarket.com";
+    pub const MARKETS_ENDPOINT: &str = "/markets";
+}
+
+/// Polymarket CLOB / CTF Exchange constants
+pub mod CLOB_CONSTANTS {
+    /// Polygon mainnet chain ID
+    pub const CHAIN_ID: u64 = 137;
+    /// CTF Exchange (EIP-712 verifying contract for regular markets)
+    pub const EXCHANGE_ADDRESS: &str = "0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E";
+    /// NegRisk CTF Exchange (EIP-712 verifying contract for neg-risk markets)
+    pub const NEG_RISK_EXCHANGE_ADDRESS: &str = "0xC5d563A36AE78145C45a50134d48A1215220f80a";
+    /// EIP-712 domain name of both exchanges
+    pub const EXCHANGE_DOMAIN_NAME: &str = "Polymarket CTF Exchange";
+    /// EIP-712 domain version of both exchanges
+    pub const EXCHANGE_DOMAIN_VERSION: &str = "1";
+    /// Taker used for public orders (anyone can fill)
+    pub const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+    /// USDC and conditional tokens both use 6 decimals
+    pub const COLLATERAL_DECIMALS: u32 = 6;
+    /// Tick size assumed when the CLOB does not report one
+    pub const DEFAULT_TICK_SIZE: f64 = 0.01;
+    /// Order submission (POST) and cancellation (DELETE) endpoint
+    pub const POST_ORDER_ENDPOINT: &str = "/order";
+    /// L1: mint a new API key
+    pub const CREATE_API_KEY_ENDPOINT: &str = "/auth/api-key";
+    /// L1: fetch the existing API key for a nonce
+    pub const DERIVE_API_KEY_ENDPOINT: &str = "/auth/derive-api-key";
+    /// Order book endpoint
+    pub const BOOK_ENDPOINT: &str = "/book";
+    /// Tick size lookup endpoint
+    pub const TICK_SIZE_ENDPOINT: &str = "/tick-size";
+    /// Neg-risk flag lookup endpoint
+    pub const NEG_RISK_ENDPOINT: &str = "/neg-risk";
+    /// Fee rate lookup endpoint; orders must be signed with the token's rate
+    pub const FEE_RATE_ENDPOINT: &str = "/fee-rate";
+    /// L2: single order lookup, followed by the order ID
+    pub const ORDER_ENDPOINT: &str = "/data/order/";
+    /// L2: our fills, paginated
+    pub const TRADES_ENDPOINT: &str = "/data/trades";
+    /// `next_cursor` value marking the last page
+    pub const END_CURSOR: &str = "LTE=";
+    /// The CLOB only honours a GTD expiration this many seconds beyond the intended lifetime
+    pub const GTD_EXPIRATION_BUFFER_SECONDS: i64 = 60;
+}
+
+/// CLOB WebSocket constants
+pub mod WS_CONSTANTS {
+    /// Public channel with book updates and trade prints per asset
+    pub const MARKET_CHANNEL: &str = "market";
+    /// Application-level heartbeat (the server answers "PONG")
+    pub const PING_INTERVAL_MS: u64 = 10_000;
+    /// Reconnect if nothing (not even a PONG) arrived for this long
+    pub const STALE_TIMEOUT_MS: u64 = 30_000;
+    /// First reconnect delay, doubled on each consecutive failure
+    pub const RECONNECT_BASE_DELAY_MS: u64 = 1_000;
+    /// Upper bound for the reconnect delay
+    pub const RECONNECT_MAX_DELAY_MS: u64 = 30_000;
+    /// Second activity lookup for traders a trade print did not turn up, to ride out data API indexing lag
+    pub const TRIGGER_RETRY_DELAY_MS: u64 = 1_500;
+    /// Shortest time between two print-triggered syncs of the same trader; later prints wait for it
+    pub const TRIGGER_DEBOUNCE_MS: u64 = 2_000;
+}
+
+/// On-chain trade detection constants
+pub mod ONCHAIN_CONSTANTS {
+    /// Emitted by both exchanges for every order (or part of an order) that is filled
+    pub const ORDER_FILLED_EVENT: &str =
+        "OrderFilled(bytes32,address,address,uint256,uint256,uint256,uint256,uint256)";
+    /// Emitted once per matched taker order, after its OrderFilled events
+    pub const ORDERS_MATCHED_EVENT: &str =
+        "OrdersMatched(bytes32,address,uint256,uint256,uint256,uint256)";
+    /// How often to poll the RPC for new blocks
+    pub const POLL_INTERVAL_MS: u64 = 2_000;
+    /// Largest block range requested in a single eth_getLogs call
+    pub const MAX_BLOCK_RANGE: u64 = 500;
+    /// Blocks to stay behind the head so logs of reorged blocks are not copied
+    pub const DEFAULT_CONFIRMATIONS: u64 = 5;
+}
+
+/// Trading constants
+pub mod TRADING_CONSTANTS {
+    /// Minimum order size in USD for BUY orders
+    pub const MIN_ORDER_SIZE_USD: f64 = 1.0;
+    /// Minimum order size in tokens for SELL/MERGE orders
+    pub const MIN_ORDER_SIZE_TOKENS: f64 = 1.0;
+    /// Safety buffer for balance checks (1% reserved)
+    pub const BALANCE_SAFETY_BUFFER: f64 = 0.99;
+    /// PRICE_DRIFT_LIMIT_EXPIRY_SECONDS when unset
+    pub const DEFAULT_DRIFT_LIMIT_EXPIRY_SECONDS: u64 = 30;
+    /// How often resting limit orders are checked for fills and expiry
+    pub const LIMIT_ORDER_CHECK_INTERVAL_SECONDS: u64 = 10;
+    /// Trade aggregation minimum total USD
+    pub const TRADE_AGGREGATION_MIN_TOTAL_USD: f64 = 1.0;
+    /// How often equity is checked against the kill switch limits
+    pub const KILL_SWITCH_CHECK_INTERVAL_SECONDS: u64 = 60;
+    /// How often copied positions are checked against their exit thresholds
+    pub const EXIT_CHECK_INTERVAL_SECONDS: u64 = 30;
+}
+
+/// Backtesting constants
+pub mod BACKTEST_CONSTANTS {
+    /// Simulated wallet size when none is given
+    pub const DEFAULT_STARTING_BALANCE: f64 = 1000.0;
+    /// Default price penalty per fill (0.5%)
+    pub const DEFAULT_SLIPPAGE_BPS: f64 = 50.0;
+    /// Polymarket charges no trading fee on most markets
+    pub const DEFAULT_FEE_BPS: f64 = 0.0;
+    /// Largest page the data API returns for /activity
+    pub const ACTIVITY_PAGE_SIZE: usize = 500;
+    /// The data API rejects offsets beyond this
+    pub const MAX_ACTIVITY_OFFSET: usize = 10_000;
+    /// Concurrent market lookups when resolving outcomes
+    pub const RESOLUTION_CONCURRENCY: usize = 8;
+}
+
+/// Retry configuration
+pub mod RETRY_CONFIG {
+    /// Default retry limit
+    pub const DEFAULT_RETRY_LIMIT: u32 = 3;
+    /// Maximum retry limit
+    pub const MAX_RETRY_LIMIT: u32 = 10;
+    /// Minimum retry limit
+    pub const MIN_RETRY_LIMIT: u32 = 1;
+    /// Default delay between retries (ms)
+    pub const DEFAULT_RETRY_DELAY: u64 = 1000;
+}
+
+/// Time constants (in milliseconds)
+pub mod TIME_CONSTANTS {
+    /// One second in milliseconds
+    pub const SECOND_MS: u64 = 1000;
+    /// One minute in milliseconds
+    pub const MINUTE_MS: u64 = 60 * 1000;
+    /// One hour in milliseconds
+    pub const HOUR_MS: u64 = 60 * 60 * 1000;
+    /// One day in milliseconds
+    pub const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+}
+
+/// MongoDB connection defaults (URI options take precedence)
+pub mod DB_CONSTANTS {
+    /// Database used when the URI has no /database path
+    pub const DEFAULT_DATABASE: &str = "polymarket_copytrading";
+    /// appName reported to the server when the URI sets none
+    pub const APP_NAME: &str = "polymarket-copy-trading-bot";
+    /// Fail fast when the server is unreachable (driver default is 30s)
+    pub const SERVER_SELECTION_TIMEOUT_MS: u64 = 10_000;
+    /// Upper bound on pooled connections when the URI sets no maxPoolSize
+    pub const MAX_POOL_SIZE: u32 = 20;
+    /// Server error code for a unique index violation
+    pub const DUPLICATE_KEY_CODE: i32 = 11000;
+    /// Collection prefixes of the per-trader collections
+    pub const ACTIVITY_COLLECTION_PREFIX: &str = "user_activities_";
+    pub const POSITION_COLLECTION_PREFIX: &str = "user_positions_";
+    /// Own-order and fill ledger collections
+    pub const MY_ORDERS_COLLECTION: &str = "my_orders";
+    pub const MY_FILLS_COLLECTION: &str = "my_fills";
+    /// Paper trading collections, kept apart from the real trading history
+    pub const PAPER_WALLET_COLLECTION: &str = "paper_wallet";
+    pub const PAPER_POSITIONS_COLLECTION: &str = "paper_positions";
+    pub const PAPER_FILLS_COLLECTION: &str = "paper_fills";
+}
+
+/// Database field names
+pub mod DB_FIELDS {
+    /// Pre-`execution` flags, only read when migrating old records
+    pub const BOT_EXECUTED: &str = "bot";
+    pub const BOT_EXECUTED_TIME: &str = "botExcutedTime";
+    pub const EXECUTION: &str = "execution";
+    pub const EXECUTION_STATUS: &str = "execution.status";
+    pub const MY_BOUGHT_SIZE: &str = "myBoughtSize";
+    pub const TRANSACTION_HASH: &str = "transactionHash";
+    pub const ASSET: &str = "asset";
+    pub const SIDE: &str = "side";
+    pub const CONDITION_ID: &str = "conditionId";
+    pub const TRADER_ADDRESS: &str = "traderAddress";
+    pub const SOURCE_TRANSACTION_HASH: &str = "sourceTransactionHash";
+    pub const SUBMITTED_AT: &str = "submittedAt";
+    pub const FILLED_AT: &str = "filledAt";
+    pub const TYPE_TRADE: &str = "TRADE";
+    pub const TYPE_REDEEM: &str = "REDEEM";
+    pub const SIDE_BUY: &str = "BUY";
+    pub const SIDE_SELL: &str = "SELL";
+}
+
+/// Logging constants
+pub mod LOG_CONSTANTS {
+    /// Logs directory name
+    pub const LOGS_DIR: &str = "logs";
+    /// Log file prefix
+    pub const LOG_FILE_PREFIX: &str = "bot-";
+    /// Maximum line length for console output
+    pub const MAX_LINE_LENGTH: usize = 70;
+}
@@ -1,8 +0,0 @@
-// CLOB client utility (placeholder)
-// TODO: Implement actual CLOB client
-
-#[allow(dead_code)] // Reserved for future CLOB client implementation
-pub fn create_clob_client() -> Result<(), Box<dyn std::error::Error>> {
-    // Placeholder
-    Ok(())
-}
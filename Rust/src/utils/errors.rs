@@ -27,7 +27,6 @@ pub enum AppError {
     Unknown(String),
 }
 
-#[allow(dead_code)] // Reserved for future use
 pub type AppResult<T> = Result<T, AppError>;
 
 /// Normalized error structure
@@ -1,7 +1,7 @@
 // Health check utilities
 
 use crate::utils::logger::HealthCheckResult;
-use crate::config::db::get_database;
+use crate::storage::storage;
 use crate::config::env::ENV;
 use crate::utils::get_my_balance::get_my_balance;
 use crate::utils::fetch_data::fetch_data;
@@ -10,9 +10,9 @@ use crate::utils::constants::POLYMARKET_API;
 pub async fn perform_health_check() -> Result<HealthCheckResult, Box<dyn std::error::Error>> {
     let mut checks = std::collections::HashMap::new();
     
-    // Check MongoDB connection
-    let db_status = match get_database().run_command(mongodb::bson::doc! {"ping": 1}).await {
-        Ok(_) => ("ok".to_string(), "Connected".to_string()),
+    // Check the trade journal backend
+    let db_status = match storage().ping().await {
+        Ok(_) => ("ok".to_string(), format!("Connected ({})", storage().backend().as_str())),
         Err(e) => ("error".to_string(), format!("Connection failed: {}", e)),
     };
     checks.insert("database".to_string(), db_status);
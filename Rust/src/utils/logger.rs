@@ -40,7 +40,7 @@ impl Logger {
         }
     }
     
-    fn format_address(address: &str) -> String {
+    pub fn format_address(address: &str) -> String {
         if address.len() >= 10 {
             format!("{}...{}", &address[..6], &address[address.len()-4..])
         } else {
@@ -109,7 +109,7 @@ impl Logger {
             let count = counts.get(index).copied().unwrap_or(0);
             println!("   {}: {} trades", Self::format_address(address), count);
         }
-        println!("");
+        println!();
     }
     
     pub fn waiting(trader_count: usize, extra_info: Option<&str>) {
@@ -181,8 +181,12 @@ impl Logger {
         println!("  Trader total capital: ${:.2} ({})", trader_balance, Self::format_address(trader_address));
     }
     
-    #[allow(dead_code)] // Reserved for future order logging
-    pub fn order_result(success: bool, message: &str) {
+    /// `slippage_bps` is the `(estimated, realized)` slippage of a filled order, when known
+    pub fn order_result(success: bool, message: &str, slippage_bps: Option<(f64, f64)>) {
+        let slippage = slippage_bps
+            .map(|(estimated, realized)| format!(" | slippage: est {:.0} bps, realized {:.0} bps", estimated, realized))
+            .unwrap_or_default();
+        let message = format!("{}{}", message, slippage);
         if success {
             println!("✓ Order executed: {}", message);
             Self::write_to_file(&format!("ORDER SUCCESS: {}", message));
@@ -231,7 +235,7 @@ impl Logger {
     ) {
         println!("\n💼 YOUR POSITIONS");
         println!("   Wallet: {}", Self::format_address(wallet));
-        println!("");
+        println!();
         
         let balance_str = format!("${:.2}", current_balance);
         let total_portfolio = current_balance + total_value;
@@ -248,7 +252,7 @@ impl Logger {
             let value_str = format!("${:.2}", total_value);
             let initial_str = format!("${:.2}", initial_value);
             
-            println!("");
+            println!();
             println!("   📈 Open Positions:    {} position{}", count, if count > 1 { "s" } else { "" });
             println!("      Invested:          {}", initial_str);
             println!("      Current Value:     {}", value_str);
@@ -276,7 +280,7 @@ impl Logger {
                 }
             }
         }
-        println!("");
+        println!();
     }
     
     pub fn traders_positions(
@@ -335,7 +339,50 @@ impl Logger {
                 }
             }
         }
-        println!("");
+        println!();
+    }
+
+    /// PnL from our own fills, per followed trader and for the biggest markets
+    pub fn pnl_report(report: &crate::services::pnl::PnlReport, slugs: &std::collections::HashMap<String, String>) {
+        let signed = |value: f64| format!("{}${:.2}", if value >= 0.0 { "+" } else { "-" }, value.abs());
+        println!("\n🧾 YOUR PNL (from fills)");
+        if report.by_trader.is_empty() {
+            println!("   No fills recorded yet");
+            println!();
+            return;
+        }
+        let overall = &report.overall;
+        println!(
+            "   Total: {} | Realized: {} | Unrealized: {} | Fees: ${:.2}",
+            signed(overall.total()),
+            signed(overall.realized),
+            signed(overall.unrealized),
+            overall.fees
+        );
+
+        println!("   👥 By Trader:");
+        for (trader, totals) in &report.by_trader {
+            println!(
+                "      • {}: {} (realized {}, open ${:.2})",
+                Self::format_address(trader),
+                signed(totals.total()),
+                signed(totals.realized),
+                totals.open_value
+            );
+        }
+
+        let mut markets: Vec<_> = report.by_market.iter().collect();
+        markets.sort_by(|a, b| b.1.total().abs().total_cmp(&a.1.total().abs()));
+        println!("   🏷️  Top Markets:");
+        for (condition_id, totals) in markets.into_iter().take(5) {
+            let name = slugs
+                .get(condition_id)
+                .filter(|slug| !slug.is_empty())
+                .cloned()
+                .unwrap_or_else(|| Self::format_address(condition_id));
+            println!("      • {}: {}", name, signed(totals.total()));
+        }
+        println!();
     }
 }
 
@@ -1,5 +1,4 @@
 pub mod constants;
-pub mod create_clob_client;
 pub mod errors;
 pub mod fetch_data;
 pub mod get_my_balance;
@@ -1,29 +1,874 @@
 // Order posting utilities
 
+use crate::config::copy_strategy::get_trade_multiplier;
 use crate::config::env::ENV;
 use crate::utils::logger::Logger;
-use crate::utils::constants::{TRADING_CONSTANTS, DB_FIELDS};
-use mongodb::bson::doc;
-use crate::interfaces::user::{UserActivityInterface, UserPositionInterface};
-use crate::models::user_history::get_user_activity_collection;
+use crate::utils::constants::{CLOB_CONSTANTS, DB_FIELDS, TRADING_CONSTANTS};
+use chrono::Utc;
+use crate::interfaces::execution::ExecutionStatus;
+use crate::interfaces::ledger::{FillRecord, OrderRecord};
+use crate::interfaces::user::{OrderBook, UserActivityInterface, UserPositionInterface};
+use crate::services::clob_client::{match_fee, order_fills, MarketOrderArgs, OrderArgs, OrderResponse, OrderType, Side};
 use crate::services::create_clob_client::ClobClient;
+use crate::services::kill_switch::buying_halted;
+use crate::services::paper_wallet::{post_paper_limit_order, post_paper_order};
+use crate::services::price_drift::{DriftAction, PriceDrift};
+use crate::services::resting_orders::{self, RestingLimit};
+use crate::services::risk_manager::Exposure;
+use crate::services::slippage::{estimate_fill, slippage_bps, FillEstimate};
+use crate::services::pnl::ledger_engine;
+use crate::services::trade_pipeline::{fetch_my_portfolio, journal};
+use crate::storage::{storage, TradeKey, TradeUpdate};
+use crate::utils::errors::{AppError, AppResult};
 
-const RETRY_LIMIT: u32 = 3;
+const MIN_ORDER_SIZE_USD: f64 = TRADING_CONSTANTS::MIN_ORDER_SIZE_USD;
 const MIN_ORDER_SIZE_TOKENS: f64 = TRADING_CONSTANTS::MIN_ORDER_SIZE_TOKENS;
 
+/// Token amounts below this are rounding noise
+const EPSILON: f64 = 1e-6;
+
+/// Check if error message indicates insufficient balance or allowance
+fn is_insufficient_balance_or_allowance_error(message: Option<&str>) -> bool {
+    match message {
+        Some(message) => {
+            let lower = message.to_lowercase();
+            lower.contains("not enough balance") || lower.contains("allowance")
+        }
+        None => false,
+    }
+}
+
+/// `(usdc, tokens)` exchanged by an accepted order
+///
+/// Prefers the matched amounts reported by the CLOB over the requested ones.
+fn fill_amounts(order_args: &MarketOrderArgs, resp: &OrderResponse) -> (f64, f64) {
+    match (order_args.side, resp.filled_amounts()) {
+        // For a BUY making is USDC spent; for a SELL it is the tokens given up
+        (Side::Buy, Some((making, taking))) => (making, taking),
+        (Side::Sell, Some((making, taking))) => (taking, making),
+        (Side::Buy, None) => (order_args.amount, order_args.amount / order_args.price),
+        (Side::Sell, None) => (order_args.amount * order_args.price, order_args.amount),
+    }
+}
+
+/// Walk `order_book` for `remaining` (USDC for a BUY, tokens for a SELL) and log the estimate
+///
+/// Copies stay within the drift limit price and MAX_SLIPPAGE_BPS, shrinking when the VWAP would slip
+/// further; bot exits (`bounded` false) take whatever depth the book has.
+fn estimate_order(
+    order_book: &OrderBook,
+    side: Side,
+    remaining: f64,
+    trade: &UserActivityInterface,
+    bounded: bool,
+) -> FillEstimate {
+    let (limit_price, max_bps) = if bounded {
+        (ENV().price_drift.worst_price(side, trade.price), ENV().max_slippage_bps)
+    } else {
+        (None, None)
+    };
+    let estimate = estimate_fill(order_book, side, remaining, limit_price, max_bps);
+    Logger::info(&format!(
+        "Estimated fill: {:.2} tokens for ${:.2} over {} level(s), VWAP ${:.4}, worst ${} ({:.0} bps)",
+        estimate.tokens,
+        estimate.usdc,
+        estimate.levels,
+        estimate.vwap,
+        estimate.worst_price,
+        estimate.bps(side)
+    ));
+    if let (true, Some(max_bps)) = (estimate.slippage_limited, max_bps) {
+        Logger::warning(&format!(
+            "📐 Slippage would exceed {:.0} bps - order shrunk from {:.2} to {:.2}",
+            max_bps,
+            remaining,
+            estimate.amount(side)
+        ));
+    }
+    estimate
+}
+
+/// `(estimated, realized)` slippage of a fill, when the response reports what matched
+fn slippage_report(estimate: &FillEstimate, side: Side, resp: &OrderResponse) -> Option<(f64, f64)> {
+    let (usdc, tokens) = match (side, resp.filled_amounts()?) {
+        (Side::Buy, (making, taking)) => (making, taking),
+        (Side::Sell, (making, taking)) => (taking, making),
+    };
+    (tokens > 0.0).then(|| (estimate.bps(side), slippage_bps(side, estimate.best_price, usdc / tokens)))
+}
+
+/// Ledger records of a posted order and, if accepted, its fill
+///
+/// `timing` is `(submitted_at, responded_at)` in unix milliseconds.
+fn ledger_entries(
+    user_address: &str,
+    trade: &UserActivityInterface,
+    order_args: &MarketOrderArgs,
+    resp: &OrderResponse,
+    fee_rate_bps: f64,
+    paper: bool,
+    (submitted_at, responded_at): (i64, i64),
+) -> (OrderRecord, Option<FillRecord>) {
+    let traded_at = trade.timestamp * 1000;
+    let order = OrderRecord {
+        order_id: resp.order_id.clone(),
+        trader_address: user_address.to_string(),
+        source_transaction_hash: trade.transaction_hash.clone(),
+        source_timestamp: trade.timestamp,
+        asset: order_args.token_id.clone(),
+        condition_id: trade.condition_id.clone(),
+        slug: trade.slug.clone(),
+        side: order_args.side.as_str().to_string(),
+        order_type: OrderType::Fok.as_str().to_string(),
+        price: order_args.price,
+        amount: order_args.amount,
+        fee_rate_bps,
+        accepted: resp.success,
+        status: resp.status.clone(),
+        error: resp.error_message().map(str::to_string),
+        paper,
+        trigger: None,
+        submitted_at,
+        responded_at,
+        latency_ms: submitted_at - traded_at,
+    };
+    if !resp.success {
+        return (order, None);
+    }
+
+    let (usdc, size) = fill_amounts(order_args, resp);
+    let price = if size > 0.0 { usdc / size } else { order_args.price };
+    let fill = FillRecord {
+        order_id: order.order_id.clone(),
+        trader_address: order.trader_address.clone(),
+        source_transaction_hash: order.source_transaction_hash.clone(),
+        asset: order.asset.clone(),
+        condition_id: order.condition_id.clone(),
+        event_slug: trade.event_slug.clone(),
+        side: order.side.clone(),
+        price,
+        size,
+        usdc,
+        // Until `charged_fee` finds the match, the signed rate is the best estimate
+        fee: match_fee(fee_rate_bps, price, size),
+        transaction_hashes: resp.transactions_hashes.clone(),
+        paper: order.paper,
+        trigger: None,
+        filled_at: responded_at,
+        latency_ms: responded_at - traded_at,
+    };
+    (order, Some(fill))
+}
+
+/// Fee the CLOB charged on `tokens` of `order_id`, from our trades since `since` (unix seconds)
+///
+/// None when the match is not listed yet or cannot be looked up; the caller keeps its estimate.
+async fn charged_fee(clob_client: &ClobClient, asset: &str, order_id: &str, since: i64, tokens: f64) -> Option<f64> {
+    let trades = match clob_client.get_trades(asset, since).await {
+        Ok(trades) => trades,
+        Err(e) => {
+            Logger::warning(&format!("Could not look up the fee charged on {}: {}", order_id, e));
+            return None;
+        }
+    };
+    let (matched, fee) = order_fills(&trades, order_id)?;
+    (matched > 0.0).then(|| fee * tokens / matched)
+}
+
+/// Post a FOK market order, or fill it into the paper wallet in dry-run mode
+///
+/// Every response, accepted or rejected, is recorded in the order ledger against `trade`, and
+/// against `trigger` for exits the bot places on its own.
+async fn submit_market_order(
+    clob_client: &ClobClient,
+    order_args: &MarketOrderArgs,
+    user_address: &str,
+    trade: &UserActivityInterface,
+    trigger: Option<&str>,
+) -> AppResult<OrderResponse> {
+    let submitted_at = Utc::now().timestamp_millis();
+    let (resp, fee_rate_bps) = if ENV().dry_run {
+        // Paper fills are charged the rate a real order would be signed with
+        let fee_rate_bps = clob_client.get_fee_rate_bps(&order_args.token_id).await? as f64;
+        (post_paper_order(clob_client, order_args).await?, fee_rate_bps)
+    } else {
+        let signed_order = clob_client.create_market_order(order_args).await?;
+        let fee_rate_bps = signed_order.order.fee_rate_bps.low_u64() as f64;
+        (clob_client.post_order(&signed_order, OrderType::Fok).await?, fee_rate_bps)
+    };
+
+    let timing = (submitted_at, Utc::now().timestamp_millis());
+    let (mut order, mut fill) = ledger_entries(user_address, trade, order_args, &resp, fee_rate_bps, ENV().dry_run, timing);
+    if let Some(fill) = fill.as_mut().filter(|fill| !fill.paper && fill.size > 0.0) {
+        let since = submitted_at / 1000;
+        if let Some(fee) = charged_fee(clob_client, &fill.asset, &fill.order_id, since, fill.size).await {
+            fill.fee = fee;
+        }
+    }
+    if let Some(trigger) = trigger {
+        order.trigger = Some(trigger.to_string());
+        if let Some(fill) = fill.as_mut() {
+            fill.trigger = order.trigger.clone();
+        }
+    }
+    if let Err(e) = storage().record_order(&order, fill.as_ref()).await {
+        Logger::error(&format!("Failed to record order for {} in the ledger: {}", trade.transaction_hash, e));
+    }
+    Ok(resp)
+}
+
+/// Outcome of walking the book with FOK market orders
+#[derive(Default)]
+struct OrderOutcome {
+    /// Tokens bought (BUY) or sold (SELL)
+    filled_tokens: f64,
+    /// Orders posted, rejected ones included
+    attempts: u32,
+    /// Consecutive failures
+    retry: u32,
+    abort_due_to_funds: bool,
+    /// Why the loop stopped before filling everything, when no order failed
+    stopped: Option<&'static str>,
+    last_error: Option<String>,
+    /// A limit order is still on the book; the trade is journaled when it settles
+    resting: bool,
+}
+
+impl OrderOutcome {
+    /// Final status of the trade once the loop is done
+    fn status(&self, retry_limit: u32) -> ExecutionStatus {
+        let gave_up = self.abort_due_to_funds || self.retry >= retry_limit;
+        if self.filled_tokens > 0.0 {
+            return if gave_up || self.stopped.is_some() {
+                ExecutionStatus::PartiallyFilled
+            } else {
+                ExecutionStatus::Filled
+            };
+        }
+        let error = self.last_error.clone().unwrap_or_else(|| "order failed".to_string());
+        if self.abort_due_to_funds {
+            ExecutionStatus::Failed { error }
+        } else if gave_up {
+            ExecutionStatus::Failed { error: format!("gave up after {} attempts: {}", self.retry, error) }
+        } else {
+            ExecutionStatus::Skipped { reason: self.stopped.unwrap_or("nothing left to fill").to_string() }
+        }
+    }
+
+    fn record_failure(&mut self, error: String) {
+        self.retry += 1;
+        self.last_error = Some(error);
+    }
+}
+
+/// Fraction of their position the trader sold, from their position before the trade
+///
+/// A missing position means the trader closed it entirely.
+pub fn trader_sell_fraction(trade_size: f64, trader_position_before: Option<f64>) -> f64 {
+    match trader_position_before {
+        Some(position_before) => {
+            if position_before <= 0.0 {
+                1.0
+            } else {
+                (trade_size / position_before).clamp(0.0, 1.0)
+            }
+        }
+        None => 1.0,
+    }
+}
+
+/// Sell `remaining` tokens into the best bids until filled, out of retries, or out of funds
+///
+/// Exits (`trigger` set) have no journaled trade to update; their orders only go to the ledger.
+async fn sell_on_bids(
+    clob_client: &ClobClient,
+    token_id: &str,
+    mut remaining: f64,
+    retry_limit: u32,
+    user_address: &str,
+    trade: &UserActivityInterface,
+    trigger: Option<&str>,
+) -> OrderOutcome {
+    let mut outcome = OrderOutcome::default();
+    
+    while remaining > 0.0 && outcome.retry < retry_limit {
+        let order_book = match clob_client.get_order_book(token_id).await {
+            Ok(book) => book,
+            Err(e) => {
+                outcome.record_failure(e.to_string());
+                Logger::warning(&format!(
+                    "Failed to fetch order book (attempt {}/{}) - {}",
+                    outcome.retry, retry_limit, e
+                ));
+                continue;
+            }
+        };
+        
+        let max_price_bid = match order_book.best_bid() {
+            Some(bid) => bid,
+            None => {
+                Logger::warning("No bids available in order book");
+                outcome.stopped = Some("no bids in the order book");
+                break;
+            }
+        };
+        
+        Logger::info(&format!("Best bid: {} @ ${}", max_price_bid.size, max_price_bid.price));
+        // Exits get out at any price; copies do not chase a collapsed bid
+        if trigger.is_none() {
+            if let Some(drift) = ENV().price_drift.check(Side::Sell, trade.price, max_price_bid.price()) {
+                handle_drift(clob_client, user_address, trade, Side::Sell, &drift, remaining, &mut outcome).await;
+                break;
+            }
+        }
+        
+        // Check if remaining amount is below minimum before creating order
+        if remaining < MIN_ORDER_SIZE_TOKENS {
+            Logger::info(&format!(
+                "Remaining amount ({:.2} tokens) below minimum - completing trade",
+                remaining
+            ));
+            break;
+        }
+        
+        let estimate = estimate_order(&order_book, Side::Sell, remaining, trade, trigger.is_none());
+        let sell_amount = estimate.tokens.min(remaining);
+        
+        // Final check: don't create orders below minimum
+        if sell_amount < MIN_ORDER_SIZE_TOKENS {
+            Logger::info(&format!(
+                "Order amount ({:.2} tokens) below minimum - completing trade",
+                sell_amount
+            ));
+            if estimate.slippage_limited {
+                outcome.stopped = Some("SLIPPAGE");
+            }
+            break;
+        }
+        if estimate.slippage_limited {
+            // Whatever the bound leaves out is not copied
+            outcome.stopped = Some("SLIPPAGE");
+            remaining = sell_amount;
+        }
+        
+        let order_args = MarketOrderArgs {
+            token_id: token_id.to_string(),
+            amount: sell_amount,
+            price: estimate.worst_price,
+            side: Side::Sell,
+        };
+        
+        outcome.attempts += 1;
+        let resp = submit_market_order(clob_client, &order_args, user_address, trade, trigger).await;
+        let resp = match resp {
+            Ok(resp) => resp,
+            Err(e) => {
+                outcome.record_failure(e.to_string());
+                Logger::warning(&format!("Order failed (attempt {}/{}) - {}", outcome.retry, retry_limit, e));
+                continue;
+            }
+        };
+        
+        if resp.success {
+            outcome.retry = 0;
+            if trigger.is_none() {
+                journal_submitted(user_address, trade, &resp, outcome.attempts).await;
+            }
+            let (_, tokens_sold) = fill_amounts(&order_args, &resp);
+            outcome.filled_tokens += tokens_sold;
+            Logger::order_result(
+                true,
+                &format!("Sold {:.2} tokens at ${}", tokens_sold, order_args.price),
+                slippage_report(&estimate, Side::Sell, &resp),
+            );
+            remaining -= tokens_sold;
+        } else {
+            let error_message = resp.error_message();
+            outcome.last_error = Some(error_message.unwrap_or("order rejected").to_string());
+            if is_insufficient_balance_or_allowance_error(error_message) {
+                outcome.abort_due_to_funds = true;
+                Logger::warning(&format!(
+                    "Order rejected: {}",
+                    error_message.unwrap_or("Insufficient balance or allowance")
+                ));
+                Logger::warning(
+                    "Skipping remaining attempts. Top up funds or check your token allowance before retrying.",
+                );
+                break;
+            }
+            outcome.retry += 1;
+            Logger::warning(&format!(
+                "Order failed (attempt {}/{}){}",
+                outcome.retry,
+                retry_limit,
+                error_message.map(|m| format!(" - {}", m)).unwrap_or_default()
+            ));
+        }
+    }
+    
+    outcome
+}
+
+/// Sell every open position into the bids, recording the orders in the ledger under `trigger`
+///
+/// Tokens are attributed to the followed traders whose copies bought them, per the fill ledger,
+/// and sold through `exit_position`; tokens the ledger does not know about are sold unattributed.
+pub async fn flatten_positions(clob_client: &ClobClient, trigger: &str) {
+    let positions = match fetch_my_portfolio().await {
+        Ok((positions, _)) => positions,
+        Err(e) => {
+            Logger::error(&format!("Cannot flatten: failed to fetch positions: {}", e));
+            return;
+        }
+    };
+    let lots = match ledger_engine().await {
+        Ok((engine, _)) => engine.open_lots(),
+        Err(e) => {
+            Logger::warning(&format!("Fill ledger unavailable, flattening unattributed: {}", e));
+            Vec::new()
+        }
+    };
+    
+    let retry_limit = ENV().retry_limit;
+    let mut sold_positions = 0;
+    for position in positions.iter().filter(|pos| pos.size >= MIN_ORDER_SIZE_TOKENS) {
+        Logger::info(&format!("Flattening {:.2} tokens of {}", position.size, position.slug));
+        let position_lots: Vec<_> = lots.iter().filter(|lot| lot.asset == position.asset).collect();
+        let mut exit = UserActivityInterface {
+            timestamp: Utc::now().timestamp(),
+            asset: position.asset.clone(),
+            condition_id: position.condition_id.clone(),
+            slug: position.slug.clone(),
+            event_slug: position.event_slug.clone(),
+            side: DB_FIELDS::SIDE_SELL.to_string(),
+            ..Default::default()
+        };
+        
+        let mut remaining = position.size;
+        let mut sales: Vec<(&str, f64)> =
+            position_lots.iter().map(|lot| (lot.trader_address.as_str(), lot.tokens)).collect();
+        sales.push(("", f64::INFINITY));
+        for (trader_address, tokens) in sales {
+            let amount = tokens.min(remaining);
+            if amount < MIN_ORDER_SIZE_TOKENS {
+                continue;
+            }
+            if let Some(lot) = position_lots.iter().find(|lot| lot.trader_address == trader_address) {
+                // Paper positions carry no market metadata
+                exit.condition_id = lot.condition_id.clone();
+                exit.event_slug = lot.event_slug.clone();
+            }
+            // Attributed sales shrink the trader's tracked purchases like any other exit
+            let sold = if trader_address.is_empty() {
+                sell_on_bids(clob_client, &position.asset, amount, retry_limit, trader_address, &exit, Some(trigger))
+                    .await
+                    .filled_tokens
+            } else {
+                exit_position(clob_client, trader_address, &exit, amount, trigger).await
+            };
+            remaining -= sold;
+        }
+        if remaining < position.size {
+            sold_positions += 1;
+        }
+    }
+    Logger::info(&format!("Flattened {} of {} position(s) ({})", sold_positions, positions.len(), trigger));
+}
+
+/// Sell `tokens` of the lot copied from `trader_address` on the bot's own initiative
+///
+/// The order and its fills are recorded in the ledger with `trigger`, and the tracked purchases
+/// of the lot shrink by the share sold so a later SELL by the trader is not copied twice.
+/// Returns the tokens sold.
+pub async fn exit_position(
+    clob_client: &ClobClient,
+    trader_address: &str,
+    exit: &UserActivityInterface,
+    tokens: f64,
+    trigger: &str,
+) -> f64 {
+    let retry_limit = ENV().retry_limit;
+    let outcome =
+        sell_on_bids(clob_client, &exit.asset, tokens, retry_limit, trader_address, exit, Some(trigger)).await;
+    if outcome.filled_tokens > 0.0 {
+        let remaining_share = (1.0 - outcome.filled_tokens / tokens).max(0.0);
+        if let Err(e) = storage()
+            .scale_tracked_purchases(trader_address, &exit.asset, &exit.condition_id, remaining_share)
+            .await
+        {
+            Logger::warning(&format!("Failed to update tracked purchases after {} exit: {}", trigger, e));
+        }
+    }
+    outcome.filled_tokens
+}
+
+/// Shrink the tracked purchases of a copied SELL's token by the share of `total_bought_tokens` sold
+///
+/// Also used by the execution recovery for SELLs interrupted after they matched.
+pub async fn reduce_tracked_purchases(
+    user_address: &str,
+    trade: &UserActivityInterface,
+    sold_tokens: f64,
+    total_bought_tokens: f64,
+) {
+    if sold_tokens <= 0.0 || total_bought_tokens <= 0.0 {
+        return;
+    }
+    let sell_percentage = sold_tokens / total_bought_tokens;
+    
+    if sell_percentage >= 0.99 {
+        // Sold essentially all tracked tokens - clear tracking
+        let _ = storage()
+            .scale_tracked_purchases(user_address, &trade.asset, &trade.condition_id, 0.0)
+            .await;
+        Logger::info(&format!(
+            "🧹 Cleared purchase tracking (sold {:.1}% of position)",
+            sell_percentage * 100.0
+        ));
+    } else {
+        // Partial sell - reduce tracked purchases proportionally
+        let _ = storage()
+            .scale_tracked_purchases(user_address, &trade.asset, &trade.condition_id, 1.0 - sell_percentage)
+            .await;
+        Logger::info(&format!(
+            "📝 Updated purchase tracking (sold {:.1}% of tracked position)",
+            sell_percentage * 100.0
+        ));
+    }
+}
+
+/// Skip the rest of a copy the book has drifted away from, or chase it with a limit order
+///
+/// `remaining` is USDC for a BUY and tokens for a SELL, as in the order loops.
+async fn handle_drift(
+    clob_client: &ClobClient,
+    user_address: &str,
+    trade: &UserActivityInterface,
+    side: Side,
+    drift: &PriceDrift,
+    remaining: f64,
+    outcome: &mut OrderOutcome,
+) {
+    Logger::warning(&format!(
+        "📉 Price drift: best {} ${} is {:.1}¢ ({:.1}%) worse than the trader's ${} (max {:.1}¢)",
+        if side == Side::Buy { "ask" } else { "bid" },
+        drift.book_price,
+        drift.cents(),
+        drift.percent(),
+        drift.leader_price,
+        drift.tolerance * 100.0
+    ));
+    match ENV().price_drift.action {
+        DriftAction::Skip => {
+            Logger::warning("Not chasing the price - skipping the rest of the trade");
+            outcome.stopped = Some("PRICE_DRIFT");
+        }
+        DriftAction::Limit => post_drift_limit(clob_client, user_address, trade, side, drift, remaining, outcome).await,
+    }
+}
+
+/// Post what is left of a copy as a GTD limit order at the worst price MAX_PRICE_DRIFT allows
+///
+/// What matches at once is added to `outcome`. If the rest stays on the book, the trade is
+/// journaled by `settle_limit_orders` once the order fills or expires. Paper orders cannot rest,
+/// so their unmatched rest expires immediately.
+async fn post_drift_limit(
+    clob_client: &ClobClient,
+    user_address: &str,
+    trade: &UserActivityInterface,
+    side: Side,
+    drift: &PriceDrift,
+    remaining: f64,
+    outcome: &mut OrderOutcome,
+) {
+    let tick_size = clob_client.get_tick_size(&trade.asset).await.unwrap_or(CLOB_CONSTANTS::DEFAULT_TICK_SIZE);
+    let price = drift.limit_price(side, tick_size);
+    let size = match side {
+        Side::Buy => remaining / price,
+        Side::Sell => remaining,
+    };
+    if size < MIN_ORDER_SIZE_TOKENS {
+        Logger::info(&format!("Limit order of {:.2} tokens below minimum - skipping the rest", size));
+        outcome.stopped = Some("PRICE_DRIFT");
+        return;
+    }
+    // Ledger amounts follow the market orders: USDC to spend for a BUY, tokens for a SELL
+    let order_args = MarketOrderArgs {
+        token_id: trade.asset.clone(),
+        amount: if side == Side::Buy { size * price } else { size },
+        price,
+        side,
+    };
+    let expiry_seconds = ENV().price_drift.limit_expiry_seconds;
+    let expires_at = Utc::now().timestamp() + expiry_seconds as i64;
+    Logger::info(&format!(
+        "Posting GTD limit {} of {:.2} tokens @ ${} for {}s",
+        side.as_str(),
+        size,
+        price,
+        expiry_seconds
+    ));
+
+    outcome.attempts += 1;
+    let submitted_at = Utc::now().timestamp_millis();
+    let posted: AppResult<(OrderResponse, f64)> = async {
+        if ENV().dry_run {
+            let fee_rate_bps = clob_client.get_fee_rate_bps(&trade.asset).await? as f64;
+            return Ok((post_paper_limit_order(clob_client, &order_args).await?, fee_rate_bps));
+        }
+        let signed_order = clob_client
+            .create_order(&OrderArgs {
+                token_id: trade.asset.clone(),
+                price,
+                size,
+                side,
+                expiration: (expires_at + CLOB_CONSTANTS::GTD_EXPIRATION_BUFFER_SECONDS) as u64,
+            })
+            .await?;
+        let fee_rate_bps = signed_order.order.fee_rate_bps.low_u64() as f64;
+        Ok((clob_client.post_order(&signed_order, OrderType::Gtd).await?, fee_rate_bps))
+    }
+    .await;
+    let (resp, fee_rate_bps) = match posted {
+        Ok(posted) => posted,
+        Err(e) => {
+            outcome.record_failure(e.to_string());
+            Logger::warning(&format!("Limit order failed - {}", e));
+            return;
+        }
+    };
+
+    let timing = (submitted_at, Utc::now().timestamp_millis());
+    let paper = ENV().dry_run;
+    let (mut order, mut fill) = ledger_entries(user_address, trade, &order_args, &resp, fee_rate_bps, paper, timing);
+    order.order_type = OrderType::Gtd.as_str().to_string();
+    // Unlike a FOK order, an accepted limit order may not have matched anything yet
+    if resp.filled_amounts().is_none() {
+        fill = None;
+    }
+    if let Some(fill) = fill.as_mut().filter(|fill| !fill.paper && fill.size > 0.0) {
+        let since = submitted_at / 1000;
+        if let Some(fee) = charged_fee(clob_client, &fill.asset, &fill.order_id, since, fill.size).await {
+            fill.fee = fee;
+        }
+    }
+    if let Err(e) = storage().record_order(&order, fill.as_ref()).await {
+        Logger::error(&format!("Failed to record order for {} in the ledger: {}", trade.transaction_hash, e));
+    }
+    if !resp.success {
+        let error = resp.error_message().unwrap_or("order rejected");
+        Logger::warning(&format!("Limit order rejected - {}", error));
+        outcome.record_failure(error.to_string());
+        return;
+    }
+
+    journal_submitted(user_address, trade, &resp, outcome.attempts).await;
+    let matched = fill.as_ref().map_or(0.0, |fill| fill.size);
+    if matched > 0.0 {
+        Logger::order_result(true, &format!("Limit matched {:.2} tokens at ${}", matched, price), None);
+        outcome.filled_tokens += matched;
+    }
+    if matched + EPSILON >= size {
+        return;
+    }
+    if ENV().dry_run {
+        outcome.stopped = Some("PRICE_DRIFT_LIMIT_UNFILLED");
+        return;
+    }
+
+    Logger::info(&format!("⏳ {:.2} tokens resting on the book for up to {}s", size - matched, expiry_seconds));
+    outcome.resting = true;
+    resting_orders::track(RestingLimit {
+        order,
+        event_slug: trade.event_slug.clone(),
+        trade_side: trade.side.clone(),
+        size,
+        recorded_tokens: matched,
+        prior_tokens: outcome.filled_tokens - matched,
+        attempts: outcome.attempts,
+        expires_at,
+    });
+}
+
+/// Settle a resting limit order once it filled or expired; returns whether it was settled
+async fn settle_limit(clob_client: &ClobClient, limit: &RestingLimit, now: i64) -> AppResult<bool> {
+    let order_id = &limit.order.order_id;
+    let expired = now >= limit.expires_at;
+    let matched = match clob_client.get_order(order_id).await? {
+        Some(order) if order.matched_size() + EPSILON >= limit.size => order.matched_size(),
+        Some(order) if expired && order.status.eq_ignore_ascii_case("LIVE") => {
+            clob_client.cancel_order(order_id).await?;
+            // Matches may have landed before the cancel
+            clob_client.get_order(order_id).await?.map_or(order.matched_size(), |order| order.matched_size())
+        }
+        Some(order) if expired => order.matched_size(),
+        None if expired => limit.recorded_tokens,
+        _ => return Ok(false),
+    };
+    let matched = matched.clamp(limit.recorded_tokens, limit.size);
+
+    let order = &limit.order;
+    let late_tokens = matched - limit.recorded_tokens;
+    if late_tokens > EPSILON {
+        let filled_at = Utc::now().timestamp_millis();
+        let usdc = late_tokens * order.price;
+        let since = order.submitted_at / 1000;
+        let fee = match charged_fee(clob_client, &order.asset, order_id, since, late_tokens).await {
+            Some(fee) => fee,
+            None => match_fee(order.fee_rate_bps, order.price, late_tokens),
+        };
+        let fill = FillRecord {
+            order_id: order_id.clone(),
+            trader_address: order.trader_address.clone(),
+            source_transaction_hash: order.source_transaction_hash.clone(),
+            asset: order.asset.clone(),
+            condition_id: order.condition_id.clone(),
+            event_slug: limit.event_slug.clone(),
+            side: order.side.clone(),
+            price: order.price,
+            size: late_tokens,
+            usdc,
+            fee,
+            transaction_hashes: Vec::new(),
+            paper: false,
+            trigger: None,
+            filled_at,
+            latency_ms: filled_at - order.source_timestamp * 1000,
+        };
+        storage()
+            .record_fill(&fill)
+            .await
+            .map_err(|e| AppError::DatabaseError(format!("Failed to record fill of {}: {}", order_id, e)))?;
+
+        // Tokens a copied SELL sells later still come out of the tracked purchases
+        if order.side == DB_FIELDS::SIDE_SELL {
+            let purchases =
+                storage().tracked_purchases(&order.trader_address, &order.asset, &order.condition_id).await?;
+            let tracked: f64 = purchases.iter().sum();
+            if tracked > 0.0 {
+                let factor = (1.0 - late_tokens / tracked).max(0.0);
+                storage()
+                    .scale_tracked_purchases(&order.trader_address, &order.asset, &order.condition_id, factor)
+                    .await?;
+            }
+        }
+    }
+
+    let total_tokens = limit.prior_tokens + matched;
+    let status = if matched + EPSILON >= limit.size {
+        ExecutionStatus::Filled
+    } else if total_tokens > 0.0 {
+        ExecutionStatus::PartiallyFilled
+    } else {
+        ExecutionStatus::Skipped { reason: "PRICE_DRIFT_LIMIT_EXPIRED".to_string() }
+    };
+    Logger::clear_line();
+    Logger::info(&format!(
+        "Limit {} {} settled: {:.2} of {:.2} tokens matched at ${} ({})",
+        order.side,
+        order_id,
+        matched,
+        limit.size,
+        order.price,
+        status.as_str()
+    ));
+    let update = TradeUpdate {
+        attempts: Some(limit.attempts),
+        bought_tokens: (order.side == DB_FIELDS::SIDE_BUY).then_some(total_tokens),
+        ..TradeUpdate::new(status)
+    };
+    let key = TradeKey {
+        transaction_hash: &order.source_transaction_hash,
+        asset: &order.asset,
+        side: &limit.trade_side,
+    };
+    journal(&order.trader_address, key, update).await;
+    Ok(true)
+}
+
+/// Settle the limit orders posted for drifted copies that have filled or expired
+///
+/// Late fills go to the ledger and the copied trades get their final status. Orders that
+/// cannot be looked up are retried on the next call; a restart leaves their trades to the
+/// execution recovery.
+pub async fn settle_limit_orders(clob_client: &ClobClient) {
+    let resting = resting_orders::take_all();
+    if resting.is_empty() {
+        return;
+    }
+    let now = Utc::now().timestamp();
+    let mut still_resting = Vec::new();
+    for limit in resting {
+        match settle_limit(clob_client, &limit, now).await {
+            Ok(true) => {}
+            Ok(false) => still_resting.push(limit),
+            Err(e) => {
+                Logger::warning(&format!("Could not settle limit order {}: {}", limit.order.order_id, e));
+                still_resting.push(limit);
+            }
+        }
+    }
+    resting_orders::restore(still_resting);
+}
+
+/// Journal an order the CLOB accepted, before acting on its fill
+async fn journal_submitted(user_address: &str, trade: &UserActivityInterface, resp: &OrderResponse, attempts: u32) {
+    let update = TradeUpdate {
+        attempts: Some(attempts),
+        order_id: Some(resp.order_id.clone()).filter(|id| !id.is_empty()),
+        ..TradeUpdate::new(ExecutionStatus::Submitted)
+    };
+    journal(user_address, TradeKey::of(trade), update).await;
+}
+
+/// Journal that the trade is not copied
+async fn skip(user_address: &str, trade: &UserActivityInterface, reason: &str) {
+    let update = TradeUpdate::new(ExecutionStatus::Skipped { reason: reason.to_string() });
+    journal(user_address, TradeKey::of(trade), update).await;
+}
+
+/// Journal the final status of a trade once its order loop is done
+async fn finish(
+    user_address: &str,
+    trade: &UserActivityInterface,
+    outcome: &OrderOutcome,
+    retry_limit: u32,
+    bought_tokens: Option<f64>,
+) {
+    if outcome.resting {
+        // Journaled by settle_limit_orders
+        return;
+    }
+    let update = TradeUpdate {
+        attempts: Some(outcome.attempts),
+        bought_tokens,
+        ..TradeUpdate::new(outcome.status(retry_limit))
+    };
+    journal(user_address, TradeKey::of(trade), update).await;
+}
+
 /// Post order to Polymarket based on trade condition
+#[allow(clippy::too_many_arguments)] // Mirrors the TypeScript postOrder signature
 pub async fn post_order(
-    _clob_client: &ClobClient,
+    clob_client: &ClobClient,
     condition: &str, // "buy", "sell", or "merge"
     my_position: Option<&UserPositionInterface>,
-    _user_position: Option<&UserPositionInterface>,
+    user_position: Option<&UserPositionInterface>,
+    user_position_before: Option<f64>,
     trade: &UserActivityInterface,
     my_balance: f64,
     _user_balance: f64,
     user_address: &str,
 ) -> anyhow::Result<()> {
-    let collection = get_user_activity_collection(user_address);
-    let copy_strategy_config = &ENV().copy_strategy_config;
+    let trader_config = ENV().trader_configs.for_trader(user_address);
+    let copy_strategy_config = &trader_config.copy_strategy;
+    let retry_limit = ENV().retry_limit;
+    
+    if let Some(reason) = trader_config.rejection(trade) {
+        Logger::info(&format!("Skipping trade: {}", reason));
+        skip(user_address, trade, &reason).await;
+        return Ok(());
+    }
     
     if condition == "merge" {
         Logger::info("Executing MERGE strategy...");
@@ -32,9 +877,7 @@ pub async fn post_order(
             Some(pos) => pos,
             None => {
                 Logger::warning("No position to merge");
-                let filter = doc! { "transactionHash": &trade.transaction_hash };
-                let update = doc! { "$set": { DB_FIELDS::BOT_EXECUTED: true } };
-                let _ = collection.update_one(filter, update).await;
+                skip(user_address, trade, "no position to merge").await;
                 return Ok(());
             }
         };
@@ -47,52 +890,23 @@ pub async fn post_order(
                 "Position size ({:.2} tokens) too small to merge - skipping",
                 remaining
             ));
-            let filter = doc! { "transactionHash": &trade.transaction_hash };
-            let update = doc! { "$set": { DB_FIELDS::BOT_EXECUTED: true } };
-            let _ = collection.update_one(filter, update).await;
+            skip(user_address, trade, "position too small to merge").await;
             return Ok(());
         }
         
-        let mut retry = 0;
-        let mut abort_due_to_funds = false;
-        
-        while remaining > 0.0 && retry < RETRY_LIMIT {
-            // TODO: Implement get_order_book and create_market_order
-            // For now, this is a placeholder
-            Logger::info("MERGE order execution - CLOB client methods need implementation");
-            
-            // Placeholder logic
-            if retry >= RETRY_LIMIT - 1 {
-                abort_due_to_funds = true;
-                break;
-            }
-            retry += 1;
-        }
-        
-        let filter = doc! { "transactionHash": &trade.transaction_hash };
-        let update = if abort_due_to_funds {
-            doc! {
-                "$set": {
-                    DB_FIELDS::BOT_EXECUTED: true,
-                    DB_FIELDS::BOT_EXECUTED_TIME: RETRY_LIMIT as i64
-                }
-            }
-        } else if retry >= RETRY_LIMIT {
-            doc! {
-                "$set": {
-                    DB_FIELDS::BOT_EXECUTED: true,
-                    DB_FIELDS::BOT_EXECUTED_TIME: retry as i64
-                }
-            }
-        } else {
-            doc! { "$set": { DB_FIELDS::BOT_EXECUTED: true } }
-        };
+        let outcome = sell_on_bids(clob_client, &my_pos.asset, remaining, retry_limit, user_address, trade, None).await;
         
-        let _ = collection.update_one(filter, update).await;
+        finish(user_address, trade, &outcome, retry_limit, None).await;
         
     } else if condition == "buy" {
         Logger::info("Executing BUY strategy...");
         
+        if let Some(halt) = buying_halted() {
+            Logger::warning(&format!("🛑 New BUYs are halted by the kill switch ({}) - skipping", halt.trigger));
+            skip(user_address, trade, &format!("KILL_SWITCH_{}", halt.trigger)).await;
+            return Ok(());
+        }
+        
         Logger::info(&format!("Your balance: ${:.2}", my_balance));
         Logger::info(&format!("Trader bought: ${:.2}", trade.usdc_size));
         
@@ -102,14 +916,40 @@ pub async fn post_order(
             .unwrap_or(0.0);
         
         // Use copy strategy to calculate order size
-        let order_calc = copy_strategy_config.calculate_order_size(
+        let mut order_calc = copy_strategy_config.calculate_order_size(
             trade.usdc_size,
             my_balance,
             current_position_value,
         );
         
+        // Exposure caps; without the ledger to check them against, nothing is bought
+        let risk = &ENV().risk;
+        if risk.has_exposure_caps() && order_calc.final_amount > 0.0 {
+            match Exposure::load().await {
+                Ok(exposure) => {
+                    let min_order_usd = copy_strategy_config.min_order_size_usd.max(MIN_ORDER_SIZE_USD);
+                    risk.apply(&mut order_calc, &exposure, trade, user_address, my_balance, min_order_usd);
+                }
+                Err(e) => {
+                    Logger::error(&format!("Cannot check exposure caps: {} - skipping BUY", e));
+                    skip(user_address, trade, "exposure unavailable").await;
+                    return Ok(());
+                }
+            }
+        }
+        
         // Log the calculation reasoning
         Logger::info(&format!("📊 {}", order_calc.reasoning));
+        for limit in &order_calc.risk_limits {
+            Logger::warning(&format!(
+                "🛡️  {}: ${:.2} → ${:.2} (cap ${:.2}, ${:.2} held)",
+                limit.code(),
+                limit.requested_usd,
+                limit.allowed_usd,
+                limit.limit_usd,
+                limit.exposure_usd
+            ));
+        }
         
         // Check if order should be executed
         if order_calc.final_amount == 0.0 {
@@ -117,81 +957,326 @@ pub async fn post_order(
             if order_calc.below_minimum {
                 Logger::warning("💡 Increase COPY_SIZE or wait for larger trades");
             }
-            let filter = doc! { "transactionHash": &trade.transaction_hash };
-            let update = doc! { "$set": { DB_FIELDS::BOT_EXECUTED: true } };
-            let _ = collection.update_one(filter, update).await;
+            // Journal the cap that blocked the order by its code
+            let reason = match order_calc.risk_limits.iter().find(|limit| limit.rejected()) {
+                Some(limit) => limit.code(),
+                None => order_calc.reasoning.clone(),
+            };
+            skip(user_address, trade, &reason).await;
             return Ok(());
         }
         
         let mut remaining = order_calc.final_amount;
-        let mut retry = 0;
-        let mut abort_due_to_funds = false;
-        let total_bought_tokens = 0.0;
-        
-        while remaining > 0.0 && retry < RETRY_LIMIT {
-            // TODO: Implement get_order_book and create_market_order
-            // For now, this is a placeholder
-            Logger::info(&format!("BUY order execution - ${:.2} remaining", remaining));
+        let mut outcome = OrderOutcome::default(); // filled_tokens tracks total tokens bought for this trade
+        
+        while remaining > 0.0 && outcome.retry < retry_limit {
+            let order_book = match clob_client.get_order_book(&trade.asset).await {
+                Ok(book) => book,
+                Err(e) => {
+                    outcome.record_failure(e.to_string());
+                    Logger::warning(&format!(
+                        "Failed to fetch order book (attempt {}/{}) - {}",
+                        outcome.retry, retry_limit, e
+                    ));
+                    continue;
+                }
+            };
+            
+            let min_price_ask = match order_book.best_ask() {
+                Some(ask) => ask,
+                None => {
+                    Logger::warning("No asks available in order book");
+                    outcome.stopped = Some("no asks in the order book");
+                    break;
+                }
+            };
+            let ask_price = min_price_ask.price();
+            
+            Logger::info(&format!("Best ask: {} @ ${}", min_price_ask.size, min_price_ask.price));
+            if let Some(drift) = ENV().price_drift.check(Side::Buy, trade.price, ask_price) {
+                handle_drift(clob_client, user_address, trade, Side::Buy, &drift, remaining, &mut outcome).await;
+                break;
+            }
             
-            // Placeholder: simulate order execution
-            // In production, this would:
-            // 1. Get order book from CLOB client
-            // 2. Find best ask price
-            // 3. Create market order
-            // 4. Post order
-            // 5. Update remaining and retry logic
+            // Check if remaining amount is below minimum before creating order
+            if remaining < MIN_ORDER_SIZE_USD {
+                Logger::info(&format!(
+                    "Remaining amount (${:.2}) below minimum - completing trade",
+                    remaining
+                ));
+                break;
+            }
             
-            if retry >= RETRY_LIMIT - 1 {
-                abort_due_to_funds = true;
+            let estimate = estimate_order(&order_book, Side::Buy, remaining, trade, true);
+            let order_size = estimate.usdc.min(remaining);
+            if order_size < MIN_ORDER_SIZE_USD {
+                Logger::info(&format!(
+                    "Order amount (${:.2}) below minimum - completing trade",
+                    order_size
+                ));
+                if estimate.slippage_limited {
+                    outcome.stopped = Some("SLIPPAGE");
+                }
                 break;
             }
-            retry += 1;
-            remaining = 0.0; // Placeholder - would be updated based on actual execution
+            if estimate.slippage_limited {
+                // Whatever the bound leaves out is not copied
+                outcome.stopped = Some("SLIPPAGE");
+                remaining = order_size;
+            }
+            
+            let order_args = MarketOrderArgs {
+                token_id: trade.asset.clone(),
+                amount: order_size,
+                price: estimate.worst_price,
+                side: Side::Buy,
+            };
+            
+            Logger::info(&format!(
+                "Creating order: ${:.2} up to ${} (Balance: ${:.2})",
+                order_size, order_args.price, my_balance
+            ));
+            outcome.attempts += 1;
+            let resp = submit_market_order(clob_client, &order_args, user_address, trade, None).await;
+            let resp = match resp {
+                Ok(resp) => resp,
+                Err(e) => {
+                    outcome.record_failure(e.to_string());
+                    Logger::warning(&format!("Order failed (attempt {}/{}) - {}", outcome.retry, retry_limit, e));
+                    continue;
+                }
+            };
+            
+            if resp.success {
+                outcome.retry = 0;
+                journal_submitted(user_address, trade, &resp, outcome.attempts).await;
+                let (usd_spent, tokens_bought) = fill_amounts(&order_args, &resp);
+                outcome.filled_tokens += tokens_bought;
+                Logger::order_result(
+                    true,
+                    &format!(
+                        "Bought ${:.2} at ${} ({:.2} tokens)",
+                        usd_spent, order_args.price, tokens_bought
+                    ),
+                    slippage_report(&estimate, Side::Buy, &resp),
+                );
+                remaining -= usd_spent;
+            } else {
+                let error_message = resp.error_message();
+                outcome.last_error = Some(error_message.unwrap_or("order rejected").to_string());
+                if is_insufficient_balance_or_allowance_error(error_message) {
+                    outcome.abort_due_to_funds = true;
+                    Logger::warning(&format!(
+                        "Order rejected: {}",
+                        error_message.unwrap_or("Insufficient balance or allowance")
+                    ));
+                    Logger::warning(
+                        "Skipping remaining attempts. Top up funds or check your USDC allowance before retrying.",
+                    );
+                    break;
+                }
+                outcome.retry += 1;
+                Logger::warning(&format!(
+                    "Order failed (attempt {}/{}){}",
+                    outcome.retry,
+                    retry_limit,
+                    error_message.map(|m| format!(" - {}", m)).unwrap_or_default()
+                ));
+            }
         }
         
         // Update trade status
-        let filter = doc! { "transactionHash": &trade.transaction_hash };
-        let update = if abort_due_to_funds {
-            doc! {
-                "$set": {
-                    DB_FIELDS::BOT_EXECUTED: true,
-                    DB_FIELDS::BOT_EXECUTED_TIME: RETRY_LIMIT as i64
-                }
+        finish(user_address, trade, &outcome, retry_limit, Some(outcome.filled_tokens)).await;
+        
+        // Log the tracked purchase for later sell reference
+        if outcome.filled_tokens > 0.0 {
+            Logger::info(&format!(
+                "📝 Tracked purchase: {:.2} tokens for future sell calculations",
+                outcome.filled_tokens
+            ));
+        }
+        
+    } else if condition == "sell" {
+        Logger::info("Executing SELL strategy...");
+        
+        let my_pos = match my_position {
+            Some(pos) => pos,
+            None => {
+                Logger::warning("No position to sell");
+                skip(user_address, trade, "no position to sell").await;
+                return Ok(());
             }
-        } else if retry >= RETRY_LIMIT {
-            doc! {
-                "$set": {
-                    DB_FIELDS::BOT_EXECUTED: true,
-                    DB_FIELDS::BOT_EXECUTED_TIME: retry as i64
-                }
+        };
+        
+        // Get all previous BUY trades for this asset to calculate total bought
+        let purchases = storage()
+            .tracked_purchases(user_address, &trade.asset, &trade.condition_id)
+            .await?;
+        let previous_buys = purchases.len();
+        let total_bought_tokens: f64 = purchases.iter().sum();
+        
+        if total_bought_tokens > 0.0 {
+            Logger::info(&format!(
+                "📊 Found {} previous purchases: {:.2} tokens bought",
+                previous_buys, total_bought_tokens
+            ));
+        }
+        
+        let trader_sell_percent = trader_sell_fraction(trade.size, user_position_before);
+        let mut remaining = match user_position {
+            None => {
+                // Trader sold entire position - we sell entire position too
+                Logger::info(&format!(
+                    "Trader closed entire position → Selling all your {:.2} tokens",
+                    my_pos.size
+                ));
+                my_pos.size
             }
-        } else {
-            doc! {
-                "$set": {
-                    DB_FIELDS::BOT_EXECUTED: true,
-                    DB_FIELDS::MY_BOUGHT_SIZE: total_bought_tokens
+            Some(_) => {
+                Logger::info(&format!(
+                    "Position comparison: Trader has {:.2} tokens, You have {:.2} tokens",
+                    user_position_before.unwrap_or(trade.size),
+                    my_pos.size
+                ));
+                Logger::info(&format!(
+                    "Trader selling: {:.2} tokens ({:.2}% of their position)",
+                    trade.size,
+                    trader_sell_percent * 100.0
+                ));
+                
+                // Use tracked bought tokens if available, otherwise fallback to current position
+                let base_sell_size = if total_bought_tokens > 0.0 {
+                    let size = total_bought_tokens * trader_sell_percent;
+                    Logger::info(&format!(
+                        "Calculating from tracked purchases: {:.2} × {:.2}% = {:.2} tokens",
+                        total_bought_tokens,
+                        trader_sell_percent * 100.0,
+                        size
+                    ));
+                    size
+                } else {
+                    let size = my_pos.size * trader_sell_percent;
+                    Logger::warning(&format!(
+                        "No tracked purchases found, using current position: {:.2} × {:.2}% = {:.2} tokens",
+                        my_pos.size,
+                        trader_sell_percent * 100.0,
+                        size
+                    ));
+                    size
+                };
+                
+                // Apply the same multiplier as BUY so sells stay symmetrical
+                let multiplier = get_trade_multiplier(copy_strategy_config, trade.usdc_size);
+                let size = base_sell_size * multiplier;
+                if multiplier != 1.0 {
+                    Logger::info(&format!(
+                        "Applying {}x multiplier (based on trader's ${:.2} order): {:.2} → {:.2} tokens",
+                        multiplier, trade.usdc_size, base_sell_size, size
+                    ));
                 }
+                size
             }
         };
         
-        let _ = collection.update_one(filter, update).await;
+        // Check minimum order size
+        if remaining < MIN_ORDER_SIZE_TOKENS {
+            Logger::warning(&format!(
+                "❌ Cannot execute: Sell amount {:.2} tokens below minimum ({} token)",
+                remaining, MIN_ORDER_SIZE_TOKENS
+            ));
+            Logger::warning("💡 This happens when position sizes are too small or mismatched");
+            skip(user_address, trade, "sell amount below minimum").await;
+            return Ok(());
+        }
+        
+        // Cap sell amount to available position size
+        if remaining > my_pos.size {
+            Logger::warning(&format!(
+                "⚠️  Calculated sell {:.2} tokens > Your position {:.2} tokens",
+                remaining, my_pos.size
+            ));
+            Logger::warning(&format!("Capping to maximum available: {:.2} tokens", my_pos.size));
+            remaining = my_pos.size;
+        }
         
-    } else if condition == "sell" {
-        Logger::info("Executing SELL strategy...");
+        let outcome = sell_on_bids(clob_client, &trade.asset, remaining, retry_limit, user_address, trade, None).await;
         
-        // TODO: Implement SELL strategy
-        // Similar to BUY but in reverse
-        // This would:
-        // 1. Check if we have position to sell
-        // 2. Calculate sell size based on copy strategy
-        // 3. Get order book for best bid
-        // 4. Execute sell order
-        // 5. Update trade status
+        // Update tracked purchases after successful sell
+        reduce_tracked_purchases(user_address, trade, outcome.filled_tokens, total_bought_tokens).await;
         
-        let filter = doc! { "transactionHash": &trade.transaction_hash };
-        let update = doc! { "$set": { DB_FIELDS::BOT_EXECUTED: true } };
-        let _ = collection.update_one(filter, update).await;
+        finish(user_address, trade, &outcome, retry_limit, None).await;
+    } else {
+        Logger::error(&format!("Unknown condition: {}", condition));
+        skip(user_address, trade, "unknown condition").await;
     }
     
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sell_fraction_uses_pre_trade_position() {
+        // Trader held 100 and sold 25
+        assert_eq!(trader_sell_fraction(25.0, Some(100.0)), 0.25);
+        // Sold everything but the position is still listed with size 0
+        assert_eq!(trader_sell_fraction(40.0, Some(40.0)), 1.0);
+        // Position no longer listed
+        assert_eq!(trader_sell_fraction(40.0, None), 1.0);
+        assert_eq!(trader_sell_fraction(0.0, Some(0.0)), 1.0);
+    }
+
+    #[test]
+    fn insufficient_funds_errors_are_detected() {
+        assert!(is_insufficient_balance_or_allowance_error(Some("not enough balance / allowance")));
+        assert!(is_insufficient_balance_or_allowance_error(Some("Insufficient ALLOWANCE")));
+        assert!(!is_insufficient_balance_or_allowance_error(Some("order couldn't be fully filled")));
+        assert!(!is_insufficient_balance_or_allowance_error(None));
+    }
+
+    #[test]
+    fn order_outcome_maps_to_final_status() {
+        let filled = OrderOutcome { filled_tokens: 5.0, attempts: 1, ..Default::default() };
+        assert_eq!(filled.status(3), ExecutionStatus::Filled);
+        let cut_short = OrderOutcome { stopped: Some("no asks in the order book"), ..filled };
+        assert_eq!(cut_short.status(3), ExecutionStatus::PartiallyFilled);
+
+        let mut failing = OrderOutcome::default();
+        for _ in 0..3 {
+            failing.record_failure("timeout".to_string());
+        }
+        assert_eq!(failing.status(3), ExecutionStatus::Failed { error: "gave up after 3 attempts: timeout".to_string() });
+
+        let no_liquidity = OrderOutcome { stopped: Some("price slippage too high"), ..Default::default() };
+        assert_eq!(no_liquidity.status(3), ExecutionStatus::Skipped { reason: "price slippage too high".to_string() });
+    }
+
+    #[test]
+    fn ledger_links_orders_and_fills_to_the_copied_trade() {
+        let trade: UserActivityInterface = serde_json::from_value(serde_json::json!({
+            "timestamp": 100, "transactionHash": "0xtx", "conditionId": "0xc", "side": "BUY"
+        }))
+        .unwrap();
+        let args = MarketOrderArgs { token_id: "tok".to_string(), amount: 50.0, price: 0.6, side: Side::Buy };
+        let accepted = OrderResponse {
+            success: true,
+            order_id: "0xo".to_string(),
+            making_amount: "45".to_string(),
+            taking_amount: "90".to_string(),
+            ..Default::default()
+        };
+
+        let (order, fill) = ledger_entries("0xtrader", &trade, &args, &accepted, 100.0, false, (101_500, 102_000));
+        assert_eq!((order.side.as_str(), order.order_type.as_str(), order.latency_ms), ("BUY", "FOK", 1_500));
+        let fill = fill.unwrap();
+        assert_eq!((fill.source_transaction_hash.as_str(), fill.order_id.as_str()), ("0xtx", "0xo"));
+        assert_eq!((fill.usdc, fill.size, fill.price, fill.fee, fill.latency_ms), (45.0, 90.0, 0.5, 0.45, 2_000));
+
+        let rejected = OrderResponse { error_msg: "not enough balance".to_string(), ..Default::default() };
+        let (order, fill) = ledger_entries("0xtrader", &trade, &args, &rejected, 0.0, false, (101_500, 102_000));
+        assert_eq!(order.error.as_deref(), Some("not enough balance"));
+        assert!(!order.accepted && fill.is_none());
+    }
+}
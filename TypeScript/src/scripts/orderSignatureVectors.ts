@@ -0,0 +1,44 @@
+import { ethers } from 'ethers';
+import { ExchangeOrderBuilder, Side, SignatureType } from '@polymarket/order-utils';
+
+// Golden order signatures for the Rust CLOB client tests (Rust/src/services/clob_client.rs).
+// Signs the same fixed order with the builder clob-client's createOrder uses, so the Rust
+// signer is checked against the reference implementation rather than against itself.
+
+// Well-known Hardhat test key; never fund it
+const TEST_PRIVATE_KEY = '0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80';
+const TEST_TOKEN_ID =
+    '71321045679252212594626385532706912750332728571942532289631379312455583992563';
+const SALT = '479249096354';
+const CHAIN_ID = 137;
+const EXCHANGES: [string, string][] = [
+    ['GOLDEN_SIGNATURE', '0x4bFb41d5B3570DeFd03C39a9A4D8dE6Bd8B8982E'],
+    ['GOLDEN_SIGNATURE_NEG_RISK', '0xC5d563A36AE78145C45a50134d48A1215220f80a'],
+];
+
+async function main() {
+    const wallet = new ethers.Wallet(TEST_PRIVATE_KEY);
+    for (const [name, exchange] of EXCHANGES) {
+        // Fixed salt so the signature is reproducible
+        const builder = new ExchangeOrderBuilder(exchange, CHAIN_ID, wallet, () => SALT);
+        const signed = await builder.buildSignedOrder({
+            maker: wallet.address,
+            signer: wallet.address,
+            taker: ethers.constants.AddressZero,
+            tokenId: TEST_TOKEN_ID,
+            makerAmount: '10000000',
+            takerAmount: '20000000',
+            expiration: '0',
+            nonce: '0',
+            feeRateBps: '0',
+            side: Side.BUY,
+            signatureType: SignatureType.EOA,
+        });
+        console.log(`${name}: ${signed.signature}`);
+    }
+}
+
+main().catch((error) => {
+    console.error(error);
+    process.exit(1);
+});